@@ -1,43 +1,482 @@
 import type { Principal } from '@dfinity/principal';
 import type { ActorMethod } from '@dfinity/agent';
 
+export interface BalanceMismatch {
+  'principal' : Principal,
+  'balance' : bigint,
+  'journal_balance' : bigint,
+}
+export type CanisterCreation = { 'Cmc' : { 'subnet_type' : [] | [string] } } |
+  { 'TopUp' : null };
+export interface CanisterStatusEntry {
+  'status' : [] | [CanisterStatusType],
+  'memory_size' : [] | [bigint],
+  'error_message' : [] | [string],
+  'cycles' : [] | [bigint],
+  'topped_up_cycles' : [] | [bigint],
+  'checked_at' : bigint,
+}
+export type CanisterStatusType = { 'stopped' : null } |
+  { 'stopping' : null } |
+  { 'running' : null };
+export type ControllerUpdate = { 'Add' : Principal } |
+  { 'Remove' : Principal } |
+  { 'HandOver' : Array<Principal> };
+export interface CorruptEntry {
+  'key' : string,
+  'map' : string,
+  'size' : bigint,
+  'error' : string,
+  'version' : [] | [number],
+}
+export interface CyclesReport {
+  'attached' : bigint,
+  'deposited' : bigint,
+  'balance' : bigint,
+  'opening_balance' : bigint,
+  'overhead' : bigint,
+  'opened_at' : bigint,
+  'minted' : bigint,
+  'created_canisters' : bigint,
+  'spending_exceeds_recovery' : boolean,
+  'received' : bigint,
+}
+export interface DepositAccount {
+  'owner' : Principal,
+  'subaccount' : Uint8Array | number[],
+  'account_identifier' : string,
+}
+export interface Event {
+  'id' : bigint,
+  'principal' : [] | [Principal],
+  'block_index' : [] | [bigint],
+  'group_identifier' : [] | [Principal],
+  'kind' : EventKind,
+  'canister_id' : [] | [Principal],
+  'error_message' : [] | [string],
+  'created_at' : bigint,
+  'icp_block_index' : [] | [bigint],
+  'cycles' : [] | [bigint],
+  'amount' : [] | [Tokens],
+}
+export interface EventFilter {
+  'principal' : [] | [Principal],
+  'group_identifier' : [] | [Principal],
+  'canister_id' : [] | [Principal],
+  'created_after' : [] | [bigint],
+  'icp_block_index' : [] | [bigint],
+  'created_before' : [] | [bigint],
+  'kinds' : [] | [Array<EventKind>],
+}
+export type EventKind = { 'SpawnCompleted' : null } |
+  { 'LedgerTransferFailed' : null } |
+  { 'FeePaid' : null } |
+  { 'SpawnStarted' : null } |
+  { 'Deposit' : null } |
+  { 'CyclesMinted' : null } |
+  { 'CanisterCreated' : null } |
+  { 'LedgerTransfer' : null } |
+  { 'SpawnFailed' : null } |
+  { 'SpawnResumed' : null } |
+  { 'Withdrawal' : null } |
+  { 'WithdrawalFailed' : null } |
+  { 'CmcNotified' : null } |
+  { 'PaymentPulled' : null } |
+  { 'RefundClaimOpened' : null } |
+  { 'MultisigRegistered' : null } |
+  { 'CodeInstalled' : null } |
+  { 'PaymentAccepted' : null } |
+  { 'CmcNotifyFailed' : null } |
+  { 'RefundPaidOut' : null };
+export interface EventsPage {
+  'total' : bigint,
+  'next' : [] | [bigint],
+  'events' : Array<Event>,
+}
+export interface FeePayout {
+  'status' : FeePayoutStatus,
+  'updated_at' : bigint,
+  'error_message' : [] | [string],
+  'recipient' : [] | [Principal],
+  'attempts' : number,
+  'created_at' : bigint,
+  'transfer_id' : [] | [string],
+  'icp_block_index' : bigint,
+  'amount' : Tokens,
+}
+export type FeePayoutStatus = { 'Paid' : bigint } |
+  { 'Pending' : null };
+export interface FeePayoutsPage {
+  'total' : bigint,
+  'next' : [] | [bigint],
+  'owed' : Tokens,
+  'paid' : Tokens,
+  'payouts' : Array<FeePayout>,
+}
+export interface GetFeePayoutsArgs {
+  'direction' : [] | [SortDirection],
+  'pending_only' : [] | [boolean],
+  'limit' : [] | [bigint],
+  'start' : [] | [bigint],
+}
+export interface GetMultisigsArgs {
+  'direction' : [] | [SortDirection],
+  'created_by' : [] | [Principal],
+  'created_after' : [] | [bigint],
+  'limit' : [] | [bigint],
+  'start' : [] | [Principal],
+  'has_group_identifier' : [] | [boolean],
+  'created_before' : [] | [bigint],
+}
+export interface GetTransactionsArgs {
+  'direction' : [] | [SortDirection],
+  'initialized_by' : [] | [Principal],
+  'statuses' : [] | [Array<TransactionStatus>],
+  'created_after' : [] | [bigint],
+  'limit' : [] | [bigint],
+  'start' : [] | [bigint],
+  'to_block' : [] | [bigint],
+  'from_block' : [] | [bigint],
+  'created_before' : [] | [bigint],
+}
+export interface HttpRequest {
+  'url' : string,
+  'method' : string,
+  'body' : Uint8Array | number[],
+  'headers' : Array<[string, string]>,
+  'certificate_version' : [] | [number],
+}
+export interface HttpResponse {
+  'body' : Uint8Array | number[],
+  'headers' : Array<[string, string]>,
+  'status_code' : number,
+}
+export interface IndexConfig {
+  'catalyze_e8s_fee' : Tokens,
+  'cmc_canister_id' : [] | [Principal],
+  'top_up_cycles' : [] | [bigint],
+  'fee_recipient' : Principal,
+  'multisig_cycles' : [] | [bigint],
+  'min_e8s_for_spinup' : Tokens,
+  'catalyze_fee_percentage' : [] | [bigint],
+  'batch_fee_payouts' : [] | [boolean],
+  'ledger_canister_id' : [] | [Principal],
+  'top_up_threshold_cycles' : [] | [bigint],
+}
+export interface InitArgs {
+  'catalyze_e8s_fee' : [] | [bigint],
+  'cmc_canister_id' : [] | [Principal],
+  'top_up_cycles' : [] | [bigint],
+  'fee_recipient' : [] | [Principal],
+  'multisig_cycles' : [] | [bigint],
+  'min_e8s_for_spinup' : [] | [bigint],
+  'catalyze_fee_percentage' : [] | [bigint],
+  'admins' : [] | [Array<Principal>],
+  'batch_fee_payouts' : [] | [boolean],
+  'ledger_canister_id' : [] | [Principal],
+  'top_up_threshold_cycles' : [] | [bigint],
+}
 export type InitializeStatus = { 'Error' : null } |
   { 'Done' : null } |
   { 'Initializing' : null };
+export type JournalAccount = { 'Cmc' : null } |
+  { 'Multisig' : Principal } |
+  { 'FeeRecipient' : null } |
+  { 'Principal' : Principal } |
+  { 'Ledger' : null };
+export interface JournalEntry {
+  'id' : bigint,
+  'to' : JournalAccount,
+  'block_index' : [] | [bigint],
+  'from' : JournalAccount,
+  'kind' : JournalEntryKind,
+  'created_at' : bigint,
+  'amount' : Tokens,
+}
+export type JournalEntryKind = { 'ProtocolFee' : null } |
+  { 'Deposit' : null } |
+  { 'Refund' : null } |
+  { 'OpeningBalance' : null } |
+  { 'MultisigFunding' : null } |
+  { 'Withdrawal' : null } |
+  { 'MultisigTopUp' : null } |
+  { 'SpawnSpend' : null } |
+  { 'FeePayout' : null };
 export interface MultisigData {
   'updated_at' : bigint,
+  'controllers' : [] | [Array<Principal>],
   'group_identifier' : [] | [Principal],
+  'wasm_version' : [] | [string],
   'canister_id' : Principal,
   'created_at' : bigint,
   'created_by' : Principal,
+  'wasm_hash' : [] | [string],
+}
+export type MultisigIndexError = { 'NoBalance' : null } |
+  { 'InitializationInProgress' : Principal } |
+  { 'CmcNotify' : NotifyError } |
+  { 'LastAdmin' : null } |
+  { 'WasmVersionExists' : string } |
+  { 'WasmVersionNotFound' : string } |
+  { 'InvalidConfig' : string } |
+  { 'RefundClaimNotFound' : bigint } |
+  { 'NotController' : Principal } |
+  { 'InsufficientIcp' : { 'available' : Tokens, 'required' : Tokens } } |
+  { 'InvalidControllers' : string } |
+  { 'PriceUnavailable' : null } |
+  { 'CorruptRecord' : string } |
+  { 'BlockAlreadyProcessed' : bigint } |
+  { 'LedgerTransfer' : TransferError } |
+  { 'InvalidTransaction' : TransactionValidationError } |
+  { 'RefundUnavailable' : string } |
+  { 'Unauthorized' : null } |
+  { 'SpawnNotFound' : Principal } |
+  { 'LedgerTransferFrom' : TransferFromError } |
+  { 'CallRejected' : { 'code' : RejectionCode, 'message' : string } } |
+  { 'MultisigNotFound' : Principal } |
+  { 'SpawnIncomplete' : Principal } |
+  { 'BlockLocked' : bigint } |
+  { 'PrincipalLocked' : Principal } |
+  { 'WasmHashMismatch' : { 'actual' : string, 'expected' : string } } |
+  { 'TransferNeedsReview' : string } |
+  { 'TransferNotInReview' : string };
+export interface MultisigUpgradeData {
+  'status' : UpgradeStatus,
+  'updated_at' : bigint,
+  'wasm_version' : string,
+  'canister_id' : Principal,
+  'error_message' : [] | [string],
+  'started_at' : [] | [bigint],
+  'wasm_hash' : string,
+}
+export interface MultisigsPage {
+  'total' : bigint,
+  'next' : [] | [Principal],
+  'multisigs' : Array<MultisigData>,
 }
-export type Result = { 'Ok' : bigint } |
-  { 'Err' : string };
-export type Result_1 = { 'Ok' : Principal } |
-  { 'Err' : string };
-export type Result_2 = { 'Ok' : null } |
-  { 'Err' : string };
+export type NotifyError = {
+    'Refunded' : { 'block_index' : [] | [bigint], 'reason' : string }
+  } |
+  { 'InvalidTransaction' : string } |
+  { 'Other' : { 'error_message' : string, 'error_code' : bigint } } |
+  { 'Processing' : null } |
+  { 'TransactionTooOld' : bigint };
+export interface OutgoingTransfer {
+  'id' : string,
+  'status' : OutgoingTransferStatus,
+  'updated_at' : bigint,
+  'args' : TransferArgs,
+  'attempts' : number,
+  'created_at' : bigint,
+}
+export type OutgoingTransferStatus = { 'Failed' : string } |
+  { 'NeedsReview' : string } |
+  { 'Completed' : bigint } |
+  { 'Pending' : null };
+export interface PaymentPull {
+  'id' : string,
+  'status' : OutgoingTransferStatus,
+  'updated_at' : bigint,
+  'principal' : Principal,
+  'attempts' : number,
+  'created_at' : bigint,
+  'created_at_time' : bigint,
+  'price' : SpawnPrice,
+}
+export interface PendingTopUp { 'cmc_block_index' : bigint, 'amount' : Tokens }
+export interface PrepaidBalance {
+  'icp' : Tokens,
+  'updated_at' : bigint,
+  'canister_id' : Principal,
+  'cycles' : bigint,
+  'pending_transfer' : [] | [string],
+  'pending_top_up' : [] | [PendingTopUp],
+}
+export interface RefundClaim {
+  'status' : RefundStatus,
+  'method' : [] | [RefundMethod],
+  'updated_at' : bigint,
+  'group_identifier' : Principal,
+  'claimant' : Principal,
+  'catalyze_fee' : [] | [Tokens],
+  'canister_id' : [] | [Principal],
+  'error_message' : [] | [string],
+  'created_at' : bigint,
+  'icp_block_index' : bigint,
+  'cycles' : [] | [bigint],
+  'cmc_amount' : [] | [Tokens],
+  'paid_e8s' : [] | [Tokens],
+  'icp_amount' : [] | [Tokens],
+  'review_note' : [] | [string],
+}
+export type RefundMethod = { 'Icp' : null } |
+  { 'Cycles' : Principal };
+export type RefundResolution = { 'Approve' : bigint } |
+  { 'Reject' : string };
+export type RefundStatus = { 'Open' : null } |
+  { 'PendingReview' : null } |
+  { 'PaidOut' : null } |
+  { 'Credited' : null };
+export type RejectionCode = { 'NoError' : null } |
+  { 'CanisterError' : null } |
+  { 'SysTransient' : null } |
+  { 'DestinationInvalid' : null } |
+  { 'Unknown' : null } |
+  { 'SysFatal' : null } |
+  { 'CanisterReject' : null };
+export type Result = { 'Ok' : RefundClaim } |
+  { 'Err' : MultisigIndexError };
+export type Result_1 = { 'Ok' : WasmData } |
+  { 'Err' : MultisigIndexError };
+export type Result_10 = { 'Ok' : IndexConfig } |
+  { 'Err' : MultisigIndexError };
+export type Result_11 = { 'Ok' : MultisigData } |
+  { 'Err' : MultisigIndexError };
+export type Result_12 = { 'Ok' : Array<MultisigUpgradeData> } |
+  { 'Err' : MultisigIndexError };
+export type Result_2 = { 'Ok' : PrepaidBalance } |
+  { 'Err' : MultisigIndexError };
+export type Result_3 = { 'Ok' : bigint } |
+  { 'Err' : MultisigIndexError };
+export type Result_4 = { 'Ok' : CyclesReport } |
+  { 'Err' : MultisigIndexError };
+export type Result_5 = { 'Ok' : SpawnPrice } |
+  { 'Err' : MultisigIndexError };
+export type Result_6 = { 'Ok' : Array<string> } |
+  { 'Err' : MultisigIndexError };
+export type Result_7 = { 'Ok' : TransactionData } |
+  { 'Err' : MultisigIndexError };
+export type Result_8 = { 'Ok' : null } |
+  { 'Err' : MultisigIndexError };
+export type Result_9 = { 'Ok' : Principal } |
+  { 'Err' : MultisigIndexError };
+export type SortDirection = { 'Asc' : null } |
+  { 'Desc' : null };
+export interface SpawnData {
+  'updated_at' : bigint,
+  'group_identifier' : Principal,
+  'creation' : [] | [CanisterCreation],
+  'wasm_version' : [] | [string],
+  'canister_id' : [] | [Principal],
+  'error_message' : [] | [string],
+  'created_at' : bigint,
+  'created_by' : Principal,
+  'icp_block_index' : bigint,
+  'cycles' : [] | [bigint],
+  'completed_steps' : Array<SpawnStep>,
+  'price' : [] | [SpawnPrice],
+}
+export interface SpawnPrice {
+  'catalyze_fee' : Tokens,
+  'multisig_cycles' : [] | [bigint],
+  'total_e8s' : Tokens,
+  'xdr_permyriad_per_icp' : [] | [bigint],
+  'expires_at' : [] | [bigint],
+}
+export type SpawnStep = { 'FeeTransferred' : null } |
+  { 'CanisterCreated' : null } |
+  { 'ToppedUp' : null } |
+  { 'CodeInstalled' : null } |
+  { 'Registered' : null };
+export interface StorageReport {
+  'schema_version' : bigint,
+  'corrupt_entries' : Array<CorruptEntry>,
+}
+export interface Timestamp { 'timestamp_nanos' : bigint }
 export interface Tokens { 'e8s' : bigint }
 export interface TransactionData {
   'status' : TransactionStatus,
+  'creation' : [] | [CanisterCreation],
   'cmc_transfer_block_index' : [] | [bigint],
   'cycles_amount' : [] | [bigint],
+  'catalyze_fee' : [] | [Tokens],
   'error_message' : [] | [string],
+  'attempts' : [] | [number],
   'initialized_by' : Principal,
   'created_at' : bigint,
   'icp_transfer_block_index' : bigint,
+  'last_attempt_at' : [] | [bigint],
   'icp_amount' : [] | [Tokens],
 }
-export type TransactionStatus = { 'InsufficientIcp' : null } |
+export type TransactionStatus = { 'Reserved' : null } |
+  { 'InsufficientIcp' : null } |
   { 'CyclesToIndexFailed' : null } |
+  { 'RecoveryFailed' : null } |
   { 'Success' : null } |
   { 'IcpToCmcFailed' : null } |
   { 'IcpToIndexFailed' : null } |
   { 'Pending' : null };
+export type TransactionValidationError = { 'NotATransfer' : null } |
+  { 'BlockNotFound' : bigint } |
+  { 'InvalidReceiver' : null } |
+  { 'InvalidSender' : null } |
+  { 'NoOperation' : null };
+export interface TransactionsPage {
+  'total' : bigint,
+  'next' : [] | [bigint],
+  'transactions' : Array<TransactionData>,
+}
+export interface TransferArgs {
+  'to' : Uint8Array | number[],
+  'fee' : Tokens,
+  'memo' : bigint,
+  'from_subaccount' : [] | [Uint8Array | number[]],
+  'created_at_time' : [] | [Timestamp],
+  'amount' : Tokens,
+}
+export type TransferError = {
+    'TxTooOld' : { 'allowed_window_nanos' : bigint }
+  } |
+  { 'BadFee' : { 'expected_fee' : Tokens } } |
+  { 'TxDuplicate' : { 'duplicate_of' : bigint } } |
+  { 'TxCreatedInFuture' : null } |
+  { 'InsufficientFunds' : { 'balance' : Tokens } };
+export type TransferFromError = {
+    'GenericError' : { 'message' : string, 'error_code' : bigint }
+  } |
+  { 'TemporarilyUnavailable' : null } |
+  { 'InsufficientAllowance' : { 'allowance' : bigint } } |
+  { 'BadBurn' : { 'min_burn_amount' : bigint } } |
+  { 'Duplicate' : { 'duplicate_of' : bigint } } |
+  { 'BadFee' : { 'expected_fee' : bigint } } |
+  { 'CreatedInFuture' : { 'ledger_time' : bigint } } |
+  { 'TooOld' : null } |
+  { 'InsufficientFunds' : { 'balance' : bigint } };
+export type TransferResolution = { 'Failed' : null } |
+  { 'Completed' : bigint };
+export type UpgradeStatus = { 'Failed' : null } |
+  { 'Done' : null } |
+  { 'Upgrading' : null };
+export interface WasmData {
+  'hash' : string,
+  'size' : bigint,
+  'created_at' : bigint,
+  'version' : string,
+  'release_notes' : string,
+  'uploaded_by' : Principal,
+}
 export interface _SERVICE {
+  'add_admin' : ActorMethod<[Principal], undefined>,
+  'claim_refund' : ActorMethod<[bigint, RefundMethod], Result>,
+  'clear_wasm_chunks' : ActorMethod<[string], undefined>,
+  'finalize_wasm' : ActorMethod<[string, string, string], Result_1>,
+  'fund_multisig_cycles' : ActorMethod<[Principal], Result_2>,
+  'fund_multisig_icp' : ActorMethod<[Principal, bigint], Result_2>,
+  'get_admins' : ActorMethod<[], Array<Principal>>,
+  'get_balance_history' : ActorMethod<[Principal], Array<JournalEntry>>,
+  'get_balance_mismatches' : ActorMethod<[], Array<BalanceMismatch>>,
   'get_caller_local_balance' : ActorMethod<[], bigint>,
-  'get_cmc_icp_balance' : ActorMethod<[], Result>,
+  'get_cmc_icp_balance' : ActorMethod<[], Result_3>,
+  'get_config' : ActorMethod<[], IndexConfig>,
   'get_cycles' : ActorMethod<[], bigint>,
+  'get_cycles_report' : ActorMethod<[], Result_4>,
+  'get_default_wasm_version' : ActorMethod<[], [] | [string]>,
+  'get_deposit_account' : ActorMethod<[], DepositAccount>,
+  'get_events' : ActorMethod<
+    [[] | [bigint], [] | [bigint], [] | [EventFilter]],
+    EventsPage
+  >,
+  'get_fee_payouts' : ActorMethod<[GetFeePayoutsArgs], FeePayoutsPage>,
   'get_initialization_status' : ActorMethod<
     [Principal],
     [] | [InitializeStatus]
@@ -46,12 +485,56 @@ export interface _SERVICE {
     [Principal],
     [] | [MultisigData]
   >,
-  'get_multisigs' : ActorMethod<[], Array<MultisigData>>,
+  'get_multisigs' : ActorMethod<[GetMultisigsArgs], MultisigsPage>,
+  'get_multisigs_by_creator' : ActorMethod<[Principal], Array<MultisigData>>,
+  'get_pending_pulls' : ActorMethod<[], Array<PaymentPull>>,
+  'get_pending_transfers' : ActorMethod<[], Array<OutgoingTransfer>>,
+  'get_prepaid_balance' : ActorMethod<[Principal], PrepaidBalance>,
   'get_principal_local_balance' : ActorMethod<[Principal], bigint>,
-  'get_transactions' : ActorMethod<
-    [[] | [TransactionStatus]],
+  'get_refund_claims' : ActorMethod<[Principal], Array<RefundClaim>>,
+  'get_refund_review_queue' : ActorMethod<[], Array<RefundClaim>>,
+  'get_spawn' : ActorMethod<[Principal], [] | [SpawnData]>,
+  'get_spawn_price' : ActorMethod<[], Result_5>,
+  'get_status_history' : ActorMethod<[Principal], Array<CanisterStatusEntry>>,
+  'get_storage_report' : ActorMethod<[], StorageReport>,
+  'get_subnet_types' : ActorMethod<[], Result_6>,
+  'get_transactions' : ActorMethod<[GetTransactionsArgs], TransactionsPage>,
+  'get_transactions_by_initializer' : ActorMethod<
+    [Principal],
     Array<TransactionData>
   >,
-  'spawn_multisig' : ActorMethod<[bigint, Principal], Result_1>,
-  'withdraw_balance' : ActorMethod<[], Result_2>,
+  'get_upgrade_statuses' : ActorMethod<[], Array<MultisigUpgradeData>>,
+  'get_wasm_versions' : ActorMethod<[], Array<WasmData>>,
+  'http_request' : ActorMethod<[HttpRequest], HttpResponse>,
+  'monitor_multisigs' : ActorMethod<
+    [],
+    Array<[Principal, CanisterStatusEntry]>
+  >,
+  'notify_deposit' : ActorMethod<[], Result_7>,
+  'pay_out_fees' : ActorMethod<[], Array<FeePayout>>,
+  'reconcile_transactions' : ActorMethod<[], Array<TransactionData>>,
+  'remove_admin' : ActorMethod<[Principal], Result_8>,
+  'resolve_refund_claim' : ActorMethod<[bigint, RefundResolution], Result>,
+  'resolve_transfer' : ActorMethod<[string, TransferResolution], Result_8>,
+  'resume_spawn' : ActorMethod<[Principal], Result_9>,
+  'set_batch_fee_payouts' : ActorMethod<[boolean], Result_10>,
+  'set_catalyze_e8s_fee' : ActorMethod<[bigint], Result_10>,
+  'set_default_wasm_version' : ActorMethod<[string], Result_8>,
+  'set_fee_recipient' : ActorMethod<[Principal], Result_10>,
+  'set_min_e8s_for_spinup' : ActorMethod<[bigint], Result_10>,
+  'set_spawn_pricing' : ActorMethod<[[] | [bigint], [] | [bigint]], Result_10>,
+  'set_top_up_config' : ActorMethod<[[] | [bigint], [] | [bigint]], Result_10>,
+  'spawn_multisig' : ActorMethod<[bigint, Principal, [] | [string]], Result_9>,
+  'spawn_multisig_icrc2' : ActorMethod<[Principal, [] | [string]], Result_9>,
+  'spawn_multisig_via_cmc' : ActorMethod<
+    [bigint, Principal, [] | [string], [] | [string]],
+    Result_9
+  >,
+  'update_multisig_controllers' : ActorMethod<
+    [Principal, ControllerUpdate],
+    Result_11
+  >,
+  'upgrade_multisigs' : ActorMethod<[[] | [bigint], [] | [boolean]], Result_12>,
+  'upload_wasm_chunk' : ActorMethod<[string, Uint8Array | number[]], Result_3>,
+  'withdraw_balance' : ActorMethod<[], Result_8>,
 }
@@ -1,5 +1,310 @@
 export const idlFactory = ({ IDL }) => {
-  const Result = IDL.Variant({ 'Ok' : IDL.Nat64, 'Err' : IDL.Text });
+  const InitArgs = IDL.Record({
+    'catalyze_e8s_fee' : IDL.Opt(IDL.Nat64),
+    'cmc_canister_id' : IDL.Opt(IDL.Principal),
+    'top_up_cycles' : IDL.Opt(IDL.Nat64),
+    'fee_recipient' : IDL.Opt(IDL.Principal),
+    'multisig_cycles' : IDL.Opt(IDL.Nat64),
+    'min_e8s_for_spinup' : IDL.Opt(IDL.Nat64),
+    'catalyze_fee_percentage' : IDL.Opt(IDL.Nat64),
+    'admins' : IDL.Opt(IDL.Vec(IDL.Principal)),
+    'batch_fee_payouts' : IDL.Opt(IDL.Bool),
+    'ledger_canister_id' : IDL.Opt(IDL.Principal),
+    'top_up_threshold_cycles' : IDL.Opt(IDL.Nat64),
+  });
+  const RefundMethod = IDL.Variant({
+    'Icp' : IDL.Null,
+    'Cycles' : IDL.Principal,
+  });
+  const RefundStatus = IDL.Variant({
+    'Open' : IDL.Null,
+    'PendingReview' : IDL.Null,
+    'PaidOut' : IDL.Null,
+    'Credited' : IDL.Null,
+  });
+  const Tokens = IDL.Record({ 'e8s' : IDL.Nat64 });
+  const RefundClaim = IDL.Record({
+    'status' : RefundStatus,
+    'method' : IDL.Opt(RefundMethod),
+    'updated_at' : IDL.Nat64,
+    'group_identifier' : IDL.Principal,
+    'claimant' : IDL.Principal,
+    'catalyze_fee' : IDL.Opt(Tokens),
+    'canister_id' : IDL.Opt(IDL.Principal),
+    'error_message' : IDL.Opt(IDL.Text),
+    'created_at' : IDL.Nat64,
+    'icp_block_index' : IDL.Nat64,
+    'cycles' : IDL.Opt(IDL.Nat),
+    'cmc_amount' : IDL.Opt(Tokens),
+    'paid_e8s' : IDL.Opt(Tokens),
+    'icp_amount' : IDL.Opt(Tokens),
+    'review_note' : IDL.Opt(IDL.Text),
+  });
+  const NotifyError = IDL.Variant({
+    'Refunded' : IDL.Record({
+      'block_index' : IDL.Opt(IDL.Nat64),
+      'reason' : IDL.Text,
+    }),
+    'InvalidTransaction' : IDL.Text,
+    'Other' : IDL.Record({
+      'error_message' : IDL.Text,
+      'error_code' : IDL.Nat64,
+    }),
+    'Processing' : IDL.Null,
+    'TransactionTooOld' : IDL.Nat64,
+  });
+  const TransferError = IDL.Variant({
+    'TxTooOld' : IDL.Record({ 'allowed_window_nanos' : IDL.Nat64 }),
+    'BadFee' : IDL.Record({ 'expected_fee' : Tokens }),
+    'TxDuplicate' : IDL.Record({ 'duplicate_of' : IDL.Nat64 }),
+    'TxCreatedInFuture' : IDL.Null,
+    'InsufficientFunds' : IDL.Record({ 'balance' : Tokens }),
+  });
+  const TransactionValidationError = IDL.Variant({
+    'NotATransfer' : IDL.Null,
+    'BlockNotFound' : IDL.Nat64,
+    'InvalidReceiver' : IDL.Null,
+    'InvalidSender' : IDL.Null,
+    'NoOperation' : IDL.Null,
+  });
+  const TransferFromError = IDL.Variant({
+    'GenericError' : IDL.Record({
+      'message' : IDL.Text,
+      'error_code' : IDL.Nat,
+    }),
+    'TemporarilyUnavailable' : IDL.Null,
+    'InsufficientAllowance' : IDL.Record({ 'allowance' : IDL.Nat }),
+    'BadBurn' : IDL.Record({ 'min_burn_amount' : IDL.Nat }),
+    'Duplicate' : IDL.Record({ 'duplicate_of' : IDL.Nat }),
+    'BadFee' : IDL.Record({ 'expected_fee' : IDL.Nat }),
+    'CreatedInFuture' : IDL.Record({ 'ledger_time' : IDL.Nat64 }),
+    'TooOld' : IDL.Null,
+    'InsufficientFunds' : IDL.Record({ 'balance' : IDL.Nat }),
+  });
+  const RejectionCode = IDL.Variant({
+    'NoError' : IDL.Null,
+    'CanisterError' : IDL.Null,
+    'SysTransient' : IDL.Null,
+    'DestinationInvalid' : IDL.Null,
+    'Unknown' : IDL.Null,
+    'SysFatal' : IDL.Null,
+    'CanisterReject' : IDL.Null,
+  });
+  const MultisigIndexError = IDL.Variant({
+    'NoBalance' : IDL.Null,
+    'InitializationInProgress' : IDL.Principal,
+    'CmcNotify' : NotifyError,
+    'LastAdmin' : IDL.Null,
+    'WasmVersionExists' : IDL.Text,
+    'WasmVersionNotFound' : IDL.Text,
+    'InvalidConfig' : IDL.Text,
+    'RefundClaimNotFound' : IDL.Nat64,
+    'NotController' : IDL.Principal,
+    'InsufficientIcp' : IDL.Record({
+      'available' : Tokens,
+      'required' : Tokens,
+    }),
+    'InvalidControllers' : IDL.Text,
+    'PriceUnavailable' : IDL.Null,
+    'CorruptRecord' : IDL.Text,
+    'BlockAlreadyProcessed' : IDL.Nat64,
+    'LedgerTransfer' : TransferError,
+    'InvalidTransaction' : TransactionValidationError,
+    'RefundUnavailable' : IDL.Text,
+    'Unauthorized' : IDL.Null,
+    'SpawnNotFound' : IDL.Principal,
+    'LedgerTransferFrom' : TransferFromError,
+    'CallRejected' : IDL.Record({
+      'code' : RejectionCode,
+      'message' : IDL.Text,
+    }),
+    'MultisigNotFound' : IDL.Principal,
+    'SpawnIncomplete' : IDL.Principal,
+    'BlockLocked' : IDL.Nat64,
+    'PrincipalLocked' : IDL.Principal,
+    'WasmHashMismatch' : IDL.Record({
+      'actual' : IDL.Text,
+      'expected' : IDL.Text,
+    }),
+    'TransferNeedsReview' : IDL.Text,
+    'TransferNotInReview' : IDL.Text,
+  });
+  const Result = IDL.Variant({
+    'Ok' : RefundClaim,
+    'Err' : MultisigIndexError,
+  });
+  const WasmData = IDL.Record({
+    'hash' : IDL.Text,
+    'size' : IDL.Nat64,
+    'created_at' : IDL.Nat64,
+    'version' : IDL.Text,
+    'release_notes' : IDL.Text,
+    'uploaded_by' : IDL.Principal,
+  });
+  const Result_1 = IDL.Variant({ 'Ok' : WasmData, 'Err' : MultisigIndexError });
+  const PendingTopUp = IDL.Record({
+    'cmc_block_index' : IDL.Nat64,
+    'amount' : Tokens,
+  });
+  const PrepaidBalance = IDL.Record({
+    'icp' : Tokens,
+    'updated_at' : IDL.Nat64,
+    'canister_id' : IDL.Principal,
+    'cycles' : IDL.Nat,
+    'pending_transfer' : IDL.Opt(IDL.Text),
+    'pending_top_up' : IDL.Opt(PendingTopUp),
+  });
+  const Result_2 = IDL.Variant({
+    'Ok' : PrepaidBalance,
+    'Err' : MultisigIndexError,
+  });
+  const JournalAccount = IDL.Variant({
+    'Cmc' : IDL.Null,
+    'Multisig' : IDL.Principal,
+    'FeeRecipient' : IDL.Null,
+    'Principal' : IDL.Principal,
+    'Ledger' : IDL.Null,
+  });
+  const JournalEntryKind = IDL.Variant({
+    'ProtocolFee' : IDL.Null,
+    'Deposit' : IDL.Null,
+    'Refund' : IDL.Null,
+    'OpeningBalance' : IDL.Null,
+    'MultisigFunding' : IDL.Null,
+    'Withdrawal' : IDL.Null,
+    'MultisigTopUp' : IDL.Null,
+    'SpawnSpend' : IDL.Null,
+    'FeePayout' : IDL.Null,
+  });
+  const JournalEntry = IDL.Record({
+    'id' : IDL.Nat64,
+    'to' : JournalAccount,
+    'block_index' : IDL.Opt(IDL.Nat64),
+    'from' : JournalAccount,
+    'kind' : JournalEntryKind,
+    'created_at' : IDL.Nat64,
+    'amount' : Tokens,
+  });
+  const BalanceMismatch = IDL.Record({
+    'principal' : IDL.Principal,
+    'balance' : IDL.Nat64,
+    'journal_balance' : IDL.Nat64,
+  });
+  const Result_3 = IDL.Variant({
+    'Ok' : IDL.Nat64,
+    'Err' : MultisigIndexError,
+  });
+  const IndexConfig = IDL.Record({
+    'catalyze_e8s_fee' : Tokens,
+    'cmc_canister_id' : IDL.Opt(IDL.Principal),
+    'top_up_cycles' : IDL.Opt(IDL.Nat64),
+    'fee_recipient' : IDL.Principal,
+    'multisig_cycles' : IDL.Opt(IDL.Nat64),
+    'min_e8s_for_spinup' : Tokens,
+    'catalyze_fee_percentage' : IDL.Opt(IDL.Nat64),
+    'batch_fee_payouts' : IDL.Opt(IDL.Bool),
+    'ledger_canister_id' : IDL.Opt(IDL.Principal),
+    'top_up_threshold_cycles' : IDL.Opt(IDL.Nat64),
+  });
+  const CyclesReport = IDL.Record({
+    'attached' : IDL.Nat,
+    'deposited' : IDL.Nat,
+    'balance' : IDL.Nat,
+    'opening_balance' : IDL.Nat,
+    'overhead' : IDL.Nat,
+    'opened_at' : IDL.Nat64,
+    'minted' : IDL.Nat,
+    'created_canisters' : IDL.Nat64,
+    'spending_exceeds_recovery' : IDL.Bool,
+    'received' : IDL.Nat,
+  });
+  const Result_4 = IDL.Variant({
+    'Ok' : CyclesReport,
+    'Err' : MultisigIndexError,
+  });
+  const DepositAccount = IDL.Record({
+    'owner' : IDL.Principal,
+    'subaccount' : IDL.Vec(IDL.Nat8),
+    'account_identifier' : IDL.Text,
+  });
+  const EventKind = IDL.Variant({
+    'SpawnCompleted' : IDL.Null,
+    'LedgerTransferFailed' : IDL.Null,
+    'FeePaid' : IDL.Null,
+    'SpawnStarted' : IDL.Null,
+    'Deposit' : IDL.Null,
+    'CyclesMinted' : IDL.Null,
+    'CanisterCreated' : IDL.Null,
+    'LedgerTransfer' : IDL.Null,
+    'SpawnFailed' : IDL.Null,
+    'SpawnResumed' : IDL.Null,
+    'Withdrawal' : IDL.Null,
+    'WithdrawalFailed' : IDL.Null,
+    'CmcNotified' : IDL.Null,
+    'PaymentPulled' : IDL.Null,
+    'RefundClaimOpened' : IDL.Null,
+    'MultisigRegistered' : IDL.Null,
+    'CodeInstalled' : IDL.Null,
+    'PaymentAccepted' : IDL.Null,
+    'CmcNotifyFailed' : IDL.Null,
+    'RefundPaidOut' : IDL.Null,
+  });
+  const EventFilter = IDL.Record({
+    'principal' : IDL.Opt(IDL.Principal),
+    'group_identifier' : IDL.Opt(IDL.Principal),
+    'canister_id' : IDL.Opt(IDL.Principal),
+    'created_after' : IDL.Opt(IDL.Nat64),
+    'icp_block_index' : IDL.Opt(IDL.Nat64),
+    'created_before' : IDL.Opt(IDL.Nat64),
+    'kinds' : IDL.Opt(IDL.Vec(EventKind)),
+  });
+  const Event = IDL.Record({
+    'id' : IDL.Nat64,
+    'principal' : IDL.Opt(IDL.Principal),
+    'block_index' : IDL.Opt(IDL.Nat64),
+    'group_identifier' : IDL.Opt(IDL.Principal),
+    'kind' : EventKind,
+    'canister_id' : IDL.Opt(IDL.Principal),
+    'error_message' : IDL.Opt(IDL.Text),
+    'created_at' : IDL.Nat64,
+    'icp_block_index' : IDL.Opt(IDL.Nat64),
+    'cycles' : IDL.Opt(IDL.Nat),
+    'amount' : IDL.Opt(Tokens),
+  });
+  const EventsPage = IDL.Record({
+    'total' : IDL.Nat64,
+    'next' : IDL.Opt(IDL.Nat64),
+    'events' : IDL.Vec(Event),
+  });
+  const SortDirection = IDL.Variant({ 'Asc' : IDL.Null, 'Desc' : IDL.Null });
+  const GetFeePayoutsArgs = IDL.Record({
+    'direction' : IDL.Opt(SortDirection),
+    'pending_only' : IDL.Opt(IDL.Bool),
+    'limit' : IDL.Opt(IDL.Nat64),
+    'start' : IDL.Opt(IDL.Nat64),
+  });
+  const FeePayoutStatus = IDL.Variant({
+    'Paid' : IDL.Nat64,
+    'Pending' : IDL.Null,
+  });
+  const FeePayout = IDL.Record({
+    'status' : FeePayoutStatus,
+    'updated_at' : IDL.Nat64,
+    'error_message' : IDL.Opt(IDL.Text),
+    'recipient' : IDL.Opt(IDL.Principal),
+    'attempts' : IDL.Nat32,
+    'created_at' : IDL.Nat64,
+    'transfer_id' : IDL.Opt(IDL.Text),
+    'icp_block_index' : IDL.Nat64,
+    'amount' : Tokens,
+  });
+  const FeePayoutsPage = IDL.Record({
+    'total' : IDL.Nat64,
+    'next' : IDL.Opt(IDL.Nat64),
+    'owed' : Tokens,
+    'paid' : Tokens,
+    'payouts' : IDL.Vec(FeePayout),
+  });
   const InitializeStatus = IDL.Variant({
     'Error' : IDL.Null,
     'Done' : IDL.Null,
@@ -7,36 +312,260 @@ export const idlFactory = ({ IDL }) => {
   });
   const MultisigData = IDL.Record({
     'updated_at' : IDL.Nat64,
+    'controllers' : IDL.Opt(IDL.Vec(IDL.Principal)),
     'group_identifier' : IDL.Opt(IDL.Principal),
+    'wasm_version' : IDL.Opt(IDL.Text),
     'canister_id' : IDL.Principal,
     'created_at' : IDL.Nat64,
     'created_by' : IDL.Principal,
+    'wasm_hash' : IDL.Opt(IDL.Text),
+  });
+  const GetMultisigsArgs = IDL.Record({
+    'direction' : IDL.Opt(SortDirection),
+    'created_by' : IDL.Opt(IDL.Principal),
+    'created_after' : IDL.Opt(IDL.Nat64),
+    'limit' : IDL.Opt(IDL.Nat64),
+    'start' : IDL.Opt(IDL.Principal),
+    'has_group_identifier' : IDL.Opt(IDL.Bool),
+    'created_before' : IDL.Opt(IDL.Nat64),
+  });
+  const MultisigsPage = IDL.Record({
+    'total' : IDL.Nat64,
+    'next' : IDL.Opt(IDL.Principal),
+    'multisigs' : IDL.Vec(MultisigData),
+  });
+  const OutgoingTransferStatus = IDL.Variant({
+    'Failed' : IDL.Text,
+    'NeedsReview' : IDL.Text,
+    'Completed' : IDL.Nat64,
+    'Pending' : IDL.Null,
+  });
+  const SpawnPrice = IDL.Record({
+    'catalyze_fee' : Tokens,
+    'multisig_cycles' : IDL.Opt(IDL.Nat64),
+    'total_e8s' : Tokens,
+    'xdr_permyriad_per_icp' : IDL.Opt(IDL.Nat64),
+    'expires_at' : IDL.Opt(IDL.Nat64),
+  });
+  const PaymentPull = IDL.Record({
+    'id' : IDL.Text,
+    'status' : OutgoingTransferStatus,
+    'updated_at' : IDL.Nat64,
+    'principal' : IDL.Principal,
+    'attempts' : IDL.Nat32,
+    'created_at' : IDL.Nat64,
+    'created_at_time' : IDL.Nat64,
+    'price' : SpawnPrice,
+  });
+  const Timestamp = IDL.Record({ 'timestamp_nanos' : IDL.Nat64 });
+  const TransferArgs = IDL.Record({
+    'to' : IDL.Vec(IDL.Nat8),
+    'fee' : Tokens,
+    'memo' : IDL.Nat64,
+    'from_subaccount' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'created_at_time' : IDL.Opt(Timestamp),
+    'amount' : Tokens,
+  });
+  const OutgoingTransfer = IDL.Record({
+    'id' : IDL.Text,
+    'status' : OutgoingTransferStatus,
+    'updated_at' : IDL.Nat64,
+    'args' : TransferArgs,
+    'attempts' : IDL.Nat32,
+    'created_at' : IDL.Nat64,
+  });
+  const CanisterCreation = IDL.Variant({
+    'Cmc' : IDL.Record({ 'subnet_type' : IDL.Opt(IDL.Text) }),
+    'TopUp' : IDL.Null,
+  });
+  const SpawnStep = IDL.Variant({
+    'FeeTransferred' : IDL.Null,
+    'CanisterCreated' : IDL.Null,
+    'ToppedUp' : IDL.Null,
+    'CodeInstalled' : IDL.Null,
+    'Registered' : IDL.Null,
+  });
+  const SpawnData = IDL.Record({
+    'updated_at' : IDL.Nat64,
+    'group_identifier' : IDL.Principal,
+    'creation' : IDL.Opt(CanisterCreation),
+    'wasm_version' : IDL.Opt(IDL.Text),
+    'canister_id' : IDL.Opt(IDL.Principal),
+    'error_message' : IDL.Opt(IDL.Text),
+    'created_at' : IDL.Nat64,
+    'created_by' : IDL.Principal,
+    'icp_block_index' : IDL.Nat64,
+    'cycles' : IDL.Opt(IDL.Nat),
+    'completed_steps' : IDL.Vec(SpawnStep),
+    'price' : IDL.Opt(SpawnPrice),
+  });
+  const Result_5 = IDL.Variant({
+    'Ok' : SpawnPrice,
+    'Err' : MultisigIndexError,
+  });
+  const CanisterStatusType = IDL.Variant({
+    'stopped' : IDL.Null,
+    'stopping' : IDL.Null,
+    'running' : IDL.Null,
+  });
+  const CanisterStatusEntry = IDL.Record({
+    'status' : IDL.Opt(CanisterStatusType),
+    'memory_size' : IDL.Opt(IDL.Nat),
+    'error_message' : IDL.Opt(IDL.Text),
+    'cycles' : IDL.Opt(IDL.Nat),
+    'topped_up_cycles' : IDL.Opt(IDL.Nat),
+    'checked_at' : IDL.Nat64,
+  });
+  const CorruptEntry = IDL.Record({
+    'key' : IDL.Text,
+    'map' : IDL.Text,
+    'size' : IDL.Nat64,
+    'error' : IDL.Text,
+    'version' : IDL.Opt(IDL.Nat8),
+  });
+  const StorageReport = IDL.Record({
+    'schema_version' : IDL.Nat64,
+    'corrupt_entries' : IDL.Vec(CorruptEntry),
+  });
+  const Result_6 = IDL.Variant({
+    'Ok' : IDL.Vec(IDL.Text),
+    'Err' : MultisigIndexError,
   });
   const TransactionStatus = IDL.Variant({
+    'Reserved' : IDL.Null,
     'InsufficientIcp' : IDL.Null,
     'CyclesToIndexFailed' : IDL.Null,
+    'RecoveryFailed' : IDL.Null,
     'Success' : IDL.Null,
     'IcpToCmcFailed' : IDL.Null,
     'IcpToIndexFailed' : IDL.Null,
     'Pending' : IDL.Null,
   });
-  const Tokens = IDL.Record({ 'e8s' : IDL.Nat64 });
+  const GetTransactionsArgs = IDL.Record({
+    'direction' : IDL.Opt(SortDirection),
+    'initialized_by' : IDL.Opt(IDL.Principal),
+    'statuses' : IDL.Opt(IDL.Vec(TransactionStatus)),
+    'created_after' : IDL.Opt(IDL.Nat64),
+    'limit' : IDL.Opt(IDL.Nat64),
+    'start' : IDL.Opt(IDL.Nat64),
+    'to_block' : IDL.Opt(IDL.Nat64),
+    'from_block' : IDL.Opt(IDL.Nat64),
+    'created_before' : IDL.Opt(IDL.Nat64),
+  });
   const TransactionData = IDL.Record({
     'status' : TransactionStatus,
+    'creation' : IDL.Opt(CanisterCreation),
     'cmc_transfer_block_index' : IDL.Opt(IDL.Nat64),
     'cycles_amount' : IDL.Opt(IDL.Nat),
+    'catalyze_fee' : IDL.Opt(Tokens),
     'error_message' : IDL.Opt(IDL.Text),
+    'attempts' : IDL.Opt(IDL.Nat32),
     'initialized_by' : IDL.Principal,
     'created_at' : IDL.Nat64,
     'icp_transfer_block_index' : IDL.Nat64,
+    'last_attempt_at' : IDL.Opt(IDL.Nat64),
     'icp_amount' : IDL.Opt(Tokens),
   });
-  const Result_1 = IDL.Variant({ 'Ok' : IDL.Principal, 'Err' : IDL.Text });
-  const Result_2 = IDL.Variant({ 'Ok' : IDL.Null, 'Err' : IDL.Text });
+  const TransactionsPage = IDL.Record({
+    'total' : IDL.Nat64,
+    'next' : IDL.Opt(IDL.Nat64),
+    'transactions' : IDL.Vec(TransactionData),
+  });
+  const UpgradeStatus = IDL.Variant({
+    'Failed' : IDL.Null,
+    'Done' : IDL.Null,
+    'Upgrading' : IDL.Null,
+  });
+  const MultisigUpgradeData = IDL.Record({
+    'status' : UpgradeStatus,
+    'updated_at' : IDL.Nat64,
+    'wasm_version' : IDL.Text,
+    'canister_id' : IDL.Principal,
+    'error_message' : IDL.Opt(IDL.Text),
+    'started_at' : IDL.Opt(IDL.Nat64),
+    'wasm_hash' : IDL.Text,
+  });
+  const HttpRequest = IDL.Record({
+    'url' : IDL.Text,
+    'method' : IDL.Text,
+    'body' : IDL.Vec(IDL.Nat8),
+    'headers' : IDL.Vec(IDL.Tuple(IDL.Text, IDL.Text)),
+    'certificate_version' : IDL.Opt(IDL.Nat16),
+  });
+  const HttpResponse = IDL.Record({
+    'body' : IDL.Vec(IDL.Nat8),
+    'headers' : IDL.Vec(IDL.Tuple(IDL.Text, IDL.Text)),
+    'status_code' : IDL.Nat16,
+  });
+  const Result_7 = IDL.Variant({
+    'Ok' : TransactionData,
+    'Err' : MultisigIndexError,
+  });
+  const Result_8 = IDL.Variant({ 'Ok' : IDL.Null, 'Err' : MultisigIndexError });
+  const RefundResolution = IDL.Variant({
+    'Approve' : IDL.Nat64,
+    'Reject' : IDL.Text,
+  });
+  const TransferResolution = IDL.Variant({
+    'Failed' : IDL.Null,
+    'Completed' : IDL.Nat64,
+  });
+  const Result_9 = IDL.Variant({
+    'Ok' : IDL.Principal,
+    'Err' : MultisigIndexError,
+  });
+  const Result_10 = IDL.Variant({
+    'Ok' : IndexConfig,
+    'Err' : MultisigIndexError,
+  });
+  const ControllerUpdate = IDL.Variant({
+    'Add' : IDL.Principal,
+    'Remove' : IDL.Principal,
+    'HandOver' : IDL.Vec(IDL.Principal),
+  });
+  const Result_11 = IDL.Variant({
+    'Ok' : MultisigData,
+    'Err' : MultisigIndexError,
+  });
+  const Result_12 = IDL.Variant({
+    'Ok' : IDL.Vec(MultisigUpgradeData),
+    'Err' : MultisigIndexError,
+  });
   return IDL.Service({
+    'add_admin' : IDL.Func([IDL.Principal], [], []),
+    'claim_refund' : IDL.Func([IDL.Nat64, RefundMethod], [Result], []),
+    'clear_wasm_chunks' : IDL.Func([IDL.Text], [], []),
+    'finalize_wasm' : IDL.Func([IDL.Text, IDL.Text, IDL.Text], [Result_1], []),
+    'fund_multisig_cycles' : IDL.Func([IDL.Principal], [Result_2], []),
+    'fund_multisig_icp' : IDL.Func([IDL.Principal, IDL.Nat64], [Result_2], []),
+    'get_admins' : IDL.Func([], [IDL.Vec(IDL.Principal)], ['query']),
+    'get_balance_history' : IDL.Func(
+        [IDL.Principal],
+        [IDL.Vec(JournalEntry)],
+        ['query'],
+      ),
+    'get_balance_mismatches' : IDL.Func(
+        [],
+        [IDL.Vec(BalanceMismatch)],
+        ['query'],
+      ),
     'get_caller_local_balance' : IDL.Func([], [IDL.Nat64], ['query']),
-    'get_cmc_icp_balance' : IDL.Func([], [Result], []),
+    'get_cmc_icp_balance' : IDL.Func([], [Result_3], []),
+    'get_config' : IDL.Func([], [IndexConfig], ['query']),
     'get_cycles' : IDL.Func([], [IDL.Nat64], ['query']),
+    'get_cycles_report' : IDL.Func([], [Result_4], ['query']),
+    'get_default_wasm_version' : IDL.Func([], [IDL.Opt(IDL.Text)], ['query']),
+    'get_deposit_account' : IDL.Func([], [DepositAccount], ['query']),
+    'get_events' : IDL.Func(
+        [IDL.Opt(IDL.Nat64), IDL.Opt(IDL.Nat64), IDL.Opt(EventFilter)],
+        [EventsPage],
+        ['query'],
+      ),
+    'get_fee_payouts' : IDL.Func(
+        [GetFeePayoutsArgs],
+        [FeePayoutsPage],
+        ['query'],
+      ),
     'get_initialization_status' : IDL.Func(
         [IDL.Principal],
         [IDL.Opt(InitializeStatus)],
@@ -47,19 +576,141 @@ export const idlFactory = ({ IDL }) => {
         [IDL.Opt(MultisigData)],
         ['query'],
       ),
-    'get_multisigs' : IDL.Func([], [IDL.Vec(MultisigData)], ['query']),
+    'get_multisigs' : IDL.Func([GetMultisigsArgs], [MultisigsPage], ['query']),
+    'get_multisigs_by_creator' : IDL.Func(
+        [IDL.Principal],
+        [IDL.Vec(MultisigData)],
+        ['query'],
+      ),
+    'get_pending_pulls' : IDL.Func([], [IDL.Vec(PaymentPull)], ['query']),
+    'get_pending_transfers' : IDL.Func(
+        [],
+        [IDL.Vec(OutgoingTransfer)],
+        ['query'],
+      ),
+    'get_prepaid_balance' : IDL.Func(
+        [IDL.Principal],
+        [PrepaidBalance],
+        ['query'],
+      ),
     'get_principal_local_balance' : IDL.Func(
         [IDL.Principal],
         [IDL.Nat64],
         ['query'],
       ),
+    'get_refund_claims' : IDL.Func(
+        [IDL.Principal],
+        [IDL.Vec(RefundClaim)],
+        ['query'],
+      ),
+    'get_refund_review_queue' : IDL.Func([], [IDL.Vec(RefundClaim)], ['query']),
+    'get_spawn' : IDL.Func([IDL.Principal], [IDL.Opt(SpawnData)], ['query']),
+    'get_spawn_price' : IDL.Func([], [Result_5], ['query']),
+    'get_status_history' : IDL.Func(
+        [IDL.Principal],
+        [IDL.Vec(CanisterStatusEntry)],
+        ['query'],
+      ),
+    'get_storage_report' : IDL.Func([], [StorageReport], ['query']),
+    'get_subnet_types' : IDL.Func([], [Result_6], []),
     'get_transactions' : IDL.Func(
-        [IDL.Opt(TransactionStatus)],
+        [GetTransactionsArgs],
+        [TransactionsPage],
+        ['query'],
+      ),
+    'get_transactions_by_initializer' : IDL.Func(
+        [IDL.Principal],
         [IDL.Vec(TransactionData)],
         ['query'],
       ),
-    'spawn_multisig' : IDL.Func([IDL.Nat64, IDL.Principal], [Result_1], []),
-    'withdraw_balance' : IDL.Func([], [Result_2], []),
+    'get_upgrade_statuses' : IDL.Func(
+        [],
+        [IDL.Vec(MultisigUpgradeData)],
+        ['query'],
+      ),
+    'get_wasm_versions' : IDL.Func([], [IDL.Vec(WasmData)], ['query']),
+    'http_request' : IDL.Func([HttpRequest], [HttpResponse], ['query']),
+    'monitor_multisigs' : IDL.Func(
+        [],
+        [IDL.Vec(IDL.Tuple(IDL.Principal, CanisterStatusEntry))],
+        [],
+      ),
+    'notify_deposit' : IDL.Func([], [Result_7], []),
+    'pay_out_fees' : IDL.Func([], [IDL.Vec(FeePayout)], []),
+    'reconcile_transactions' : IDL.Func([], [IDL.Vec(TransactionData)], []),
+    'remove_admin' : IDL.Func([IDL.Principal], [Result_8], []),
+    'resolve_refund_claim' : IDL.Func(
+        [IDL.Nat64, RefundResolution],
+        [Result],
+        [],
+      ),
+    'resolve_transfer' : IDL.Func(
+        [IDL.Text, TransferResolution],
+        [Result_8],
+        [],
+      ),
+    'resume_spawn' : IDL.Func([IDL.Principal], [Result_9], []),
+    'set_batch_fee_payouts' : IDL.Func([IDL.Bool], [Result_10], []),
+    'set_catalyze_e8s_fee' : IDL.Func([IDL.Nat64], [Result_10], []),
+    'set_default_wasm_version' : IDL.Func([IDL.Text], [Result_8], []),
+    'set_fee_recipient' : IDL.Func([IDL.Principal], [Result_10], []),
+    'set_min_e8s_for_spinup' : IDL.Func([IDL.Nat64], [Result_10], []),
+    'set_spawn_pricing' : IDL.Func(
+        [IDL.Opt(IDL.Nat64), IDL.Opt(IDL.Nat64)],
+        [Result_10],
+        [],
+      ),
+    'set_top_up_config' : IDL.Func(
+        [IDL.Opt(IDL.Nat64), IDL.Opt(IDL.Nat64)],
+        [Result_10],
+        [],
+      ),
+    'spawn_multisig' : IDL.Func(
+        [IDL.Nat64, IDL.Principal, IDL.Opt(IDL.Text)],
+        [Result_9],
+        [],
+      ),
+    'spawn_multisig_icrc2' : IDL.Func(
+        [IDL.Principal, IDL.Opt(IDL.Text)],
+        [Result_9],
+        [],
+      ),
+    'spawn_multisig_via_cmc' : IDL.Func(
+        [IDL.Nat64, IDL.Principal, IDL.Opt(IDL.Text), IDL.Opt(IDL.Text)],
+        [Result_9],
+        [],
+      ),
+    'update_multisig_controllers' : IDL.Func(
+        [IDL.Principal, ControllerUpdate],
+        [Result_11],
+        [],
+      ),
+    'upgrade_multisigs' : IDL.Func(
+        [IDL.Opt(IDL.Nat64), IDL.Opt(IDL.Bool)],
+        [Result_12],
+        [],
+      ),
+    'upload_wasm_chunk' : IDL.Func(
+        [IDL.Text, IDL.Vec(IDL.Nat8)],
+        [Result_3],
+        [],
+      ),
+    'withdraw_balance' : IDL.Func([], [Result_8], []),
+  });
+};
+export const init = ({ IDL }) => {
+  const InitArgs = IDL.Record({
+    'catalyze_e8s_fee' : IDL.Opt(IDL.Nat64),
+    'cmc_canister_id' : IDL.Opt(IDL.Principal),
+    'top_up_cycles' : IDL.Opt(IDL.Nat64),
+    'fee_recipient' : IDL.Opt(IDL.Principal),
+    'multisig_cycles' : IDL.Opt(IDL.Nat64),
+    'min_e8s_for_spinup' : IDL.Opt(IDL.Nat64),
+    'catalyze_fee_percentage' : IDL.Opt(IDL.Nat64),
+    'admins' : IDL.Opt(IDL.Vec(IDL.Principal)),
+    'batch_fee_payouts' : IDL.Opt(IDL.Bool),
+    'ledger_canister_id' : IDL.Opt(IDL.Principal),
+    'top_up_threshold_cycles' : IDL.Opt(IDL.Nat64),
   });
+  return [IDL.Opt(InitArgs)];
 };
-export const init = ({ IDL }) => { return []; };
@@ -0,0 +1,581 @@
+use std::{env, fs, path::PathBuf};
+
+use candid::{utils::ArgumentEncoder, CandidType, Encode, Nat, Principal};
+use ic_ledger_types::{
+    AccountBalanceArgs, AccountIdentifier, Memo, Tokens, TransferArgs, TransferResult,
+    DEFAULT_SUBACCOUNT,
+};
+use pocket_ic::{call_candid_as, common::rest::RawEffectivePrincipal, query_candid_as, PocketIc};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+pub mod types;
+
+use types::{
+    Account, ApproveArgs, ApproveError, CanisterStatusEntry, ControllerUpdate, CyclesReport,
+    DepositAccount, EventFilter, EventsPage, FeePayout, FeePayoutsPage, GetFeePayoutsArgs,
+    GetMultisigsArgs, GetTransactionsArgs, HttpRequest, HttpResponse, IndexConfig, InitArgs,
+    InitializeStatus, JournalEntry, LedgerArg, LedgerFeatureFlags, LedgerInitArgs, MultisigData,
+    MultisigIndexError, MultisigsPage, NotifyError, OutgoingTransfer, PrepaidBalance, RefundClaim,
+    RefundMethod, RefundResolution, SpawnData, SpawnPrice, StorageReport, TransactionData,
+    TransactionsPage, TransferResolution, WasmData,
+};
+
+pub static ICP_TRANSACTION_FEE: u64 = 10_000;
+pub static MIN_E8S_FOR_SPINUP: u64 = 110_000_000;
+pub static CATALYZE_E8S_FEE: u64 = 10_000_000;
+pub static INITIAL_USER_E8S: u64 = 100_000_000_000;
+
+// Test environment with the index, a local ICP ledger and the mock CMC installed.
+// The wasm files are read from the paths in the environment, see `scripts/test.sh`
+pub struct TestEnv {
+    pub pic: PocketIc,
+    pub index: Principal,
+    pub ledger: Principal,
+    pub cmc: Principal,
+    pub admin: Principal,
+    pub treasury: Principal,
+    pub user: Principal,
+}
+
+// The test environment for a test, the test is skipped when no PocketIC server is set in `POCKET_IC_BIN`.
+// `scripts/test.sh` builds the wasm files of the canisters and runs the tests with a server
+#[macro_export]
+macro_rules! test_env {
+    () => {
+        match $crate::TestEnv::try_new() {
+            Some(env) => env,
+            None => return,
+        }
+    };
+}
+
+impl TestEnv {
+    pub fn try_new() -> Option<Self> {
+        if env::var_os("POCKET_IC_BIN").is_none() {
+            eprintln!("POCKET_IC_BIN is not set, skipping the test");
+            return None;
+        }
+        Some(Self::new())
+    }
+
+    pub fn new() -> Self {
+        let pic = PocketIc::new();
+
+        let admin = Principal::from_slice(&[1; 29]);
+        let treasury = Principal::from_slice(&[2; 29]);
+        let user = Principal::from_slice(&[3; 29]);
+        let minter = Principal::from_slice(&[4; 29]);
+
+        let ledger = pic.create_canister();
+        pic.add_cycles(ledger, 100_000_000_000_000);
+        pic.install_canister(
+            ledger,
+            read_wasm("ICP_LEDGER_WASM", "wasm/ledger-canister.wasm.gz"),
+            Encode!(&LedgerArg::Init(LedgerInitArgs {
+                minting_account: AccountIdentifier::new(&minter, &DEFAULT_SUBACCOUNT).to_hex(),
+                initial_values: vec![(
+                    AccountIdentifier::new(&user, &DEFAULT_SUBACCOUNT).to_hex(),
+                    Tokens::from_e8s(INITIAL_USER_E8S),
+                )],
+                send_whitelist: vec![],
+                transfer_fee: Some(Tokens::from_e8s(ICP_TRANSACTION_FEE)),
+                token_symbol: Some("ICP".to_string()),
+                token_name: Some("Internet Computer".to_string()),
+                feature_flags: Some(LedgerFeatureFlags { icrc2: true }),
+            }))
+            .unwrap(),
+            None,
+        );
+
+        let cmc = pic.create_canister();
+        pic.add_cycles(cmc, 1_000_000_000_000_000);
+        pic.install_canister(
+            cmc,
+            read_wasm(
+                "MOCK_CMC_WASM",
+                "target/wasm32-unknown-unknown/release/mock_cmc.wasm",
+            ),
+            Encode!().unwrap(),
+            None,
+        );
+
+        let index = pic.create_canister();
+        pic.add_cycles(index, 10_000_000_000_000);
+        pic.install_canister(
+            index,
+            index_wasm(),
+            Encode!(&Some(InitArgs {
+                admins: Some(vec![admin]),
+                fee_recipient: Some(treasury),
+                catalyze_e8s_fee: Some(CATALYZE_E8S_FEE),
+                min_e8s_for_spinup: Some(MIN_E8S_FOR_SPINUP),
+                ledger_canister_id: Some(ledger),
+                cmc_canister_id: Some(cmc),
+                multisig_cycles: None,
+                catalyze_fee_percentage: None,
+                top_up_threshold_cycles: None,
+                top_up_cycles: None,
+                batch_fee_payouts: None,
+            }))
+            .unwrap(),
+            None,
+        );
+
+        TestEnv {
+            pic,
+            index,
+            ledger,
+            cmc,
+            admin,
+            treasury,
+            user,
+        }
+    }
+
+    pub fn update<Input, Output>(&self, sender: Principal, method: &str, input: Input) -> Output
+    where
+        Input: ArgumentEncoder,
+        Output: for<'a> Deserialize<'a> + CandidType,
+    {
+        let (output,): (Output,) = call_candid_as(
+            &self.pic,
+            self.index,
+            RawEffectivePrincipal::None,
+            sender,
+            method,
+            input,
+        )
+        .unwrap_or_else(|err| panic!("Update call {} failed: {:?}", method, err));
+        output
+    }
+
+    pub fn query<Input, Output>(&self, method: &str, input: Input) -> Output
+    where
+        Input: ArgumentEncoder,
+        Output: for<'a> Deserialize<'a> + CandidType,
+    {
+        let (output,): (Output,) =
+            query_candid_as(&self.pic, self.index, Principal::anonymous(), method, input)
+                .unwrap_or_else(|err| panic!("Query call {} failed: {:?}", method, err));
+        output
+    }
+
+    // Transfer ICP from the principal to the default account of the index, returns the block index
+    pub fn transfer_to_index(&self, from: Principal, e8s: u64) -> u64 {
+        self.transfer(
+            from,
+            AccountIdentifier::new(&self.index, &DEFAULT_SUBACCOUNT),
+            e8s,
+        )
+    }
+
+    pub fn transfer(&self, from: Principal, to: AccountIdentifier, e8s: u64) -> u64 {
+        let (result,): (TransferResult,) = call_candid_as(
+            &self.pic,
+            self.ledger,
+            RawEffectivePrincipal::None,
+            from,
+            "transfer",
+            (TransferArgs {
+                memo: Memo(0),
+                amount: Tokens::from_e8s(e8s),
+                fee: Tokens::from_e8s(ICP_TRANSACTION_FEE),
+                from_subaccount: None,
+                to,
+                created_at_time: None,
+            },),
+        )
+        .expect("Ledger transfer call failed");
+        result.expect("Ledger transfer failed")
+    }
+
+    // Approve the index to pull the amount from the default account of the principal
+    pub fn approve_index(&self, from: Principal, e8s: u64) {
+        let (result,): (Result<Nat, ApproveError>,) = call_candid_as(
+            &self.pic,
+            self.ledger,
+            RawEffectivePrincipal::None,
+            from,
+            "icrc2_approve",
+            (ApproveArgs {
+                fee: None,
+                memo: None,
+                from_subaccount: None,
+                created_at_time: None,
+                amount: Nat::from(e8s),
+                expected_allowance: None,
+                expires_at: None,
+                spender: Account {
+                    owner: self.index,
+                    subaccount: None,
+                },
+            },),
+        )
+        .expect("Ledger approve call failed");
+        result.expect("Ledger approve failed");
+    }
+
+    pub fn ledger_balance(&self, principal: Principal) -> u64 {
+        let (tokens,): (Tokens,) = query_candid_as(
+            &self.pic,
+            self.ledger,
+            Principal::anonymous(),
+            "account_balance",
+            (AccountBalanceArgs {
+                account: AccountIdentifier::new(&principal, &DEFAULT_SUBACCOUNT),
+            },),
+        )
+        .expect("Ledger balance call failed");
+        tokens.e8s()
+    }
+
+    pub fn set_cmc_error(&self, error: Option<NotifyError>) {
+        let () = call_candid_as(
+            &self.pic,
+            self.cmc,
+            RawEffectivePrincipal::None,
+            Principal::anonymous(),
+            "set_notify_error",
+            (error,),
+        )
+        .expect("Mock CMC call failed");
+    }
+
+    pub fn spawn_multisig(
+        &self,
+        caller: Principal,
+        block_index: u64,
+        group_identifier: Principal,
+        wasm_version: Option<String>,
+    ) -> Result<Principal, MultisigIndexError> {
+        self.update(
+            caller,
+            "spawn_multisig",
+            (block_index, group_identifier, wasm_version),
+        )
+    }
+
+    pub fn spawn_multisig_via_cmc(
+        &self,
+        caller: Principal,
+        block_index: u64,
+        group_identifier: Principal,
+        subnet_type: Option<String>,
+    ) -> Result<Principal, MultisigIndexError> {
+        self.update(
+            caller,
+            "spawn_multisig_via_cmc",
+            (block_index, group_identifier, None::<String>, subnet_type),
+        )
+    }
+
+    pub fn get_subnet_types(&self) -> Result<Vec<String>, MultisigIndexError> {
+        self.update(self.user, "get_subnet_types", ())
+    }
+
+    pub fn spawn_multisig_icrc2(
+        &self,
+        caller: Principal,
+        group_identifier: Principal,
+    ) -> Result<Principal, MultisigIndexError> {
+        self.update(
+            caller,
+            "spawn_multisig_icrc2",
+            (group_identifier, None::<String>),
+        )
+    }
+
+    pub fn set_spawn_pricing(
+        &self,
+        multisig_cycles: Option<u64>,
+        catalyze_fee_percentage: Option<u64>,
+    ) -> Result<IndexConfig, MultisigIndexError> {
+        self.update(
+            self.admin,
+            "set_spawn_pricing",
+            (multisig_cycles, catalyze_fee_percentage),
+        )
+    }
+
+    pub fn get_spawn_price(&self) -> Result<SpawnPrice, MultisigIndexError> {
+        self.query("get_spawn_price", ())
+    }
+
+    pub fn get_deposit_account(&self, caller: Principal) -> DepositAccount {
+        let (output,): (DepositAccount,) =
+            query_candid_as(&self.pic, self.index, caller, "get_deposit_account", ())
+                .expect("Query call get_deposit_account failed");
+        output
+    }
+
+    pub fn notify_deposit(&self, caller: Principal) -> Result<TransactionData, MultisigIndexError> {
+        self.update(caller, "notify_deposit", ())
+    }
+
+    pub fn withdraw_balance(&self, caller: Principal) -> Result<(), MultisigIndexError> {
+        self.update(caller, "withdraw_balance", ())
+    }
+
+    // Register a wasm version in the registry of the index as admin
+    pub fn upload_wasm(&self, version: &str, wasm: Vec<u8>) -> WasmData {
+        let hash = hex::encode(Sha256::digest(&wasm));
+        let _: Result<u64, MultisigIndexError> =
+            self.update(self.admin, "upload_wasm_chunk", (version.to_string(), wasm));
+        let result: Result<WasmData, MultisigIndexError> = self.update(
+            self.admin,
+            "finalize_wasm",
+            (version.to_string(), hash, "test".to_string()),
+        );
+        result.expect("Failed to finalize wasm")
+    }
+
+    // Every transaction on a single page, the tests never create more as a page holds
+    pub fn get_transactions(&self) -> Vec<TransactionData> {
+        self.get_transactions_page(GetTransactionsArgs::default())
+            .transactions
+    }
+
+    pub fn get_transactions_page(&self, args: GetTransactionsArgs) -> TransactionsPage {
+        self.query("get_transactions", (args,))
+    }
+
+    pub fn get_transaction(&self, block_index: u64) -> TransactionData {
+        self.get_transactions()
+            .into_iter()
+            .find(|t| t.icp_transfer_block_index == block_index)
+            .expect("Transaction not found")
+    }
+
+    pub fn get_multisigs(&self) -> Vec<MultisigData> {
+        self.get_multisigs_page(GetMultisigsArgs::default())
+            .multisigs
+    }
+
+    pub fn get_multisigs_page(&self, args: GetMultisigsArgs) -> MultisigsPage {
+        self.query("get_multisigs", (args,))
+    }
+
+    pub fn get_multisig_by_group_identifier(
+        &self,
+        group_identifier: Principal,
+    ) -> Option<MultisigData> {
+        self.query("get_multisig_by_group_identifier", (group_identifier,))
+    }
+
+    pub fn get_multisigs_by_creator(&self, created_by: Principal) -> Vec<MultisigData> {
+        self.query("get_multisigs_by_creator", (created_by,))
+    }
+
+    pub fn get_transactions_by_initializer(
+        &self,
+        initialized_by: Principal,
+    ) -> Vec<TransactionData> {
+        self.query("get_transactions_by_initializer", (initialized_by,))
+    }
+
+    pub fn get_local_balance(&self, principal: Principal) -> u64 {
+        self.query("get_principal_local_balance", (principal,))
+    }
+
+    pub fn get_balance_history(&self, principal: Principal) -> Vec<JournalEntry> {
+        self.query("get_balance_history", (principal,))
+    }
+
+    pub fn update_multisig_controllers(
+        &self,
+        caller: Principal,
+        canister_id: Principal,
+        update: ControllerUpdate,
+    ) -> Result<MultisigData, MultisigIndexError> {
+        self.update(caller, "update_multisig_controllers", (canister_id, update))
+    }
+
+    pub fn set_top_up_config(
+        &self,
+        top_up_threshold_cycles: Option<u64>,
+        top_up_cycles: Option<u64>,
+    ) -> Result<IndexConfig, MultisigIndexError> {
+        self.update(
+            self.admin,
+            "set_top_up_config",
+            (top_up_threshold_cycles, top_up_cycles),
+        )
+    }
+
+    pub fn set_batch_fee_payouts(
+        &self,
+        batch_fee_payouts: bool,
+    ) -> Result<IndexConfig, MultisigIndexError> {
+        self.update(self.admin, "set_batch_fee_payouts", (batch_fee_payouts,))
+    }
+
+    pub fn get_fee_payouts(&self, args: GetFeePayoutsArgs) -> FeePayoutsPage {
+        self.update(self.admin, "get_fee_payouts", (args,))
+    }
+
+    pub fn pay_out_fees(&self) -> Vec<FeePayout> {
+        self.update(self.admin, "pay_out_fees", ())
+    }
+
+    pub fn reconcile_transactions(&self) -> Vec<TransactionData> {
+        self.update(self.admin, "reconcile_transactions", ())
+    }
+
+    pub fn fund_multisig_icp(
+        &self,
+        caller: Principal,
+        canister_id: Principal,
+        e8s: u64,
+    ) -> Result<PrepaidBalance, MultisigIndexError> {
+        self.update(caller, "fund_multisig_icp", (canister_id, e8s))
+    }
+
+    pub fn monitor_multisigs(&self) -> Vec<(Principal, CanisterStatusEntry)> {
+        self.update(self.admin, "monitor_multisigs", ())
+    }
+
+    pub fn get_status_history(&self, canister_id: Principal) -> Vec<CanisterStatusEntry> {
+        self.query("get_status_history", (canister_id,))
+    }
+
+    pub fn get_prepaid_balance(&self, canister_id: Principal) -> PrepaidBalance {
+        self.query("get_prepaid_balance", (canister_id,))
+    }
+
+    pub fn resume_spawn(
+        &self,
+        caller: Principal,
+        group_identifier: Principal,
+    ) -> Result<Principal, MultisigIndexError> {
+        self.update(caller, "resume_spawn", (group_identifier,))
+    }
+
+    pub fn get_refund_claims(&self, claimant: Principal) -> Vec<RefundClaim> {
+        self.query("get_refund_claims", (claimant,))
+    }
+
+    pub fn claim_refund(
+        &self,
+        caller: Principal,
+        icp_block_index: u64,
+        method: RefundMethod,
+    ) -> Result<RefundClaim, MultisigIndexError> {
+        self.update(caller, "claim_refund", (icp_block_index, method))
+    }
+
+    pub fn get_refund_review_queue(&self) -> Vec<RefundClaim> {
+        self.update(self.admin, "get_refund_review_queue", ())
+    }
+
+    pub fn resolve_refund_claim(
+        &self,
+        icp_block_index: u64,
+        resolution: RefundResolution,
+    ) -> Result<RefundClaim, MultisigIndexError> {
+        self.update(
+            self.admin,
+            "resolve_refund_claim",
+            (icp_block_index, resolution),
+        )
+    }
+
+    pub fn get_spawn(&self, group_identifier: Principal) -> Option<SpawnData> {
+        self.query("get_spawn", (group_identifier,))
+    }
+
+    // Upgrade the index to the same wasm without arguments, this runs the migrations of `post_upgrade`
+    pub fn upgrade_index(&self) {
+        self.pic
+            .upgrade_canister(
+                self.index,
+                index_wasm(),
+                Encode!(&None::<InitArgs>).unwrap(),
+                None,
+            )
+            .expect("Index upgrade failed");
+    }
+
+    pub fn get_cycles_report(&self) -> Result<CyclesReport, MultisigIndexError> {
+        self.query("get_cycles_report", ())
+    }
+
+    pub fn get_storage_report(&self) -> StorageReport {
+        self.update(self.admin, "get_storage_report", ())
+    }
+
+    pub fn http_get(&self, url: &str) -> HttpResponse {
+        self.query(
+            "http_request",
+            (HttpRequest {
+                method: "GET".to_string(),
+                url: url.to_string(),
+                headers: vec![],
+                body: vec![],
+                certificate_version: None,
+            },),
+        )
+    }
+
+    pub fn get_events(
+        &self,
+        from: Option<u64>,
+        limit: Option<u64>,
+        filter: Option<EventFilter>,
+    ) -> EventsPage {
+        self.update(self.admin, "get_events", (from, limit, filter))
+    }
+
+    pub fn get_pending_transfers(&self) -> Vec<OutgoingTransfer> {
+        self.update(self.admin, "get_pending_transfers", ())
+    }
+
+    pub fn resolve_transfer(
+        &self,
+        id: &str,
+        resolution: TransferResolution,
+    ) -> Result<(), MultisigIndexError> {
+        self.update(self.admin, "resolve_transfer", (id.to_string(), resolution))
+    }
+
+    pub fn get_initialization_status(
+        &self,
+        group_identifier: Principal,
+    ) -> Option<InitializeStatus> {
+        self.query("get_initialization_status", (group_identifier,))
+    }
+}
+
+impl Default for TestEnv {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn group_identifier(seed: u8) -> Principal {
+    Principal::from_slice(&[100, seed])
+}
+
+fn index_wasm() -> Vec<u8> {
+    read_wasm(
+        "MULTISIG_INDEX_WASM",
+        "target/wasm32-unknown-unknown/release/multisig_index.wasm",
+    )
+}
+
+fn read_wasm(env_var: &str, default_path: &str) -> Vec<u8> {
+    let path = match env::var(env_var) {
+        Ok(path) => PathBuf::from(path),
+        Err(_) => PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join(default_path),
+    };
+    fs::read(&path).unwrap_or_else(|_| {
+        panic!(
+            "Could not read {}, set {} or run scripts/test.sh",
+            path.display(),
+            env_var
+        )
+    })
+}
@@ -0,0 +1,599 @@
+// Mirrors of the candid types of the index, the ledger and the mock CMC used by the tests
+use candid::{CandidType, Nat, Principal};
+use ic_ledger_types::{Subaccount, Tokens, TransferArgs, TransferError};
+use serde::Deserialize;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct MultisigData {
+    pub canister_id: Principal,
+    pub group_identifier: Option<Principal>,
+    pub created_by: Principal,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub wasm_version: Option<String>,
+    pub wasm_hash: Option<String>,
+    pub controllers: Option<Vec<Principal>>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum ControllerUpdate {
+    Add(Principal),
+    Remove(Principal),
+    HandOver(Vec<Principal>),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum InitializeStatus {
+    Initializing,
+    Done,
+    Error,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum TransactionStatus {
+    IcpToIndexFailed,
+    IcpToCmcFailed,
+    CyclesToIndexFailed,
+    InsufficientIcp,
+    Success,
+    Pending,
+    RecoveryFailed,
+    Reserved,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum JournalAccount {
+    Principal(Principal),
+    Ledger,
+    Cmc,
+    FeeRecipient,
+    Multisig(Principal),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum JournalEntryKind {
+    Deposit,
+    SpawnSpend,
+    ProtocolFee,
+    Refund,
+    Withdrawal,
+    OpeningBalance,
+    MultisigFunding,
+    MultisigTopUp,
+    FeePayout,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct JournalEntry {
+    pub id: u64,
+    pub kind: JournalEntryKind,
+    pub from: JournalAccount,
+    pub to: JournalAccount,
+    pub amount: Tokens,
+    pub block_index: Option<u64>,
+    pub created_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct DepositAccount {
+    pub owner: Principal,
+    pub subaccount: Subaccount,
+    pub account_identifier: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct TransactionData {
+    pub icp_transfer_block_index: u64,
+    pub cmc_transfer_block_index: Option<u64>,
+    pub icp_amount: Option<Tokens>,
+    pub cycles_amount: Option<Nat>,
+    pub initialized_by: Principal,
+    pub created_at: u64,
+    pub status: TransactionStatus,
+    pub error_message: Option<String>,
+    pub attempts: Option<u32>,
+    pub last_attempt_at: Option<u64>,
+    pub catalyze_fee: Option<Tokens>,
+    pub creation: Option<CanisterCreation>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum CanisterCreation {
+    TopUp,
+    Cmc { subnet_type: Option<String> },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum SpawnStep {
+    ToppedUp,
+    CanisterCreated,
+    CodeInstalled,
+    Registered,
+    FeeTransferred,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SpawnData {
+    pub group_identifier: Principal,
+    pub created_by: Principal,
+    pub icp_block_index: u64,
+    pub cycles: Option<Nat>,
+    pub canister_id: Option<Principal>,
+    pub completed_steps: Vec<SpawnStep>,
+    pub error_message: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub wasm_version: Option<String>,
+    pub creation: Option<CanisterCreation>,
+    pub price: Option<SpawnPrice>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum RefundStatus {
+    Open,
+    PendingReview,
+    Credited,
+    PaidOut,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum RefundMethod {
+    Cycles(Principal),
+    Icp,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum RefundResolution {
+    Approve(u64),
+    Reject(String),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct RefundClaim {
+    pub icp_block_index: u64,
+    pub group_identifier: Principal,
+    pub claimant: Principal,
+    pub icp_amount: Option<Tokens>,
+    pub cmc_amount: Option<Tokens>,
+    pub catalyze_fee: Option<Tokens>,
+    pub cycles: Option<Nat>,
+    pub canister_id: Option<Principal>,
+    pub status: RefundStatus,
+    pub method: Option<RefundMethod>,
+    pub error_message: Option<String>,
+    pub review_note: Option<String>,
+    pub paid_e8s: Option<Tokens>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum OutgoingTransferStatus {
+    Pending,
+    Completed(u64),
+    Failed(String),
+    NeedsReview(String),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum TransferResolution {
+    Completed(u64),
+    Failed,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct OutgoingTransfer {
+    pub id: String,
+    pub args: TransferArgs,
+    pub status: OutgoingTransferStatus,
+    pub attempts: u32,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum FeePayoutStatus {
+    Pending,
+    Paid(u64),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct FeePayout {
+    pub icp_block_index: u64,
+    pub amount: Tokens,
+    pub status: FeePayoutStatus,
+    pub transfer_id: Option<String>,
+    pub recipient: Option<Principal>,
+    pub attempts: u32,
+    pub error_message: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct GetFeePayoutsArgs {
+    pub start: Option<u64>,
+    pub limit: Option<u64>,
+    pub direction: Option<SortDirection>,
+    pub pending_only: Option<bool>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CyclesReport {
+    pub balance: Nat,
+    pub opening_balance: Nat,
+    pub opened_at: u64,
+    pub minted: Nat,
+    pub received: Nat,
+    pub attached: Nat,
+    pub deposited: Nat,
+    pub overhead: Nat,
+    pub created_canisters: u64,
+    pub spending_exceeds_recovery: bool,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct FeePayoutsPage {
+    pub payouts: Vec<FeePayout>,
+    pub next: Option<u64>,
+    pub total: u64,
+    pub owed: Tokens,
+    pub paid: Tokens,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct WasmData {
+    pub version: String,
+    pub hash: String,
+    pub release_notes: String,
+    pub size: u64,
+    pub uploaded_by: Principal,
+    pub created_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct InitArgs {
+    pub admins: Option<Vec<Principal>>,
+    pub fee_recipient: Option<Principal>,
+    pub catalyze_e8s_fee: Option<u64>,
+    pub min_e8s_for_spinup: Option<u64>,
+    pub ledger_canister_id: Option<Principal>,
+    pub cmc_canister_id: Option<Principal>,
+    pub multisig_cycles: Option<u64>,
+    pub catalyze_fee_percentage: Option<u64>,
+    pub top_up_threshold_cycles: Option<u64>,
+    pub top_up_cycles: Option<u64>,
+    pub batch_fee_payouts: Option<bool>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct IndexConfig {
+    pub fee_recipient: Principal,
+    pub catalyze_e8s_fee: Tokens,
+    pub min_e8s_for_spinup: Tokens,
+    pub ledger_canister_id: Option<Principal>,
+    pub cmc_canister_id: Option<Principal>,
+    pub multisig_cycles: Option<u64>,
+    pub catalyze_fee_percentage: Option<u64>,
+    pub top_up_threshold_cycles: Option<u64>,
+    pub top_up_cycles: Option<u64>,
+    pub batch_fee_payouts: Option<bool>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PrepaidBalance {
+    pub canister_id: Principal,
+    pub icp: Tokens,
+    pub cycles: Nat,
+    pub pending_top_up: Option<PendingTopUp>,
+    pub pending_transfer: Option<String>,
+    pub updated_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PendingTopUp {
+    pub cmc_block_index: u64,
+    pub amount: Tokens,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum CanisterStatusType {
+    #[serde(rename = "running")]
+    Running,
+    #[serde(rename = "stopping")]
+    Stopping,
+    #[serde(rename = "stopped")]
+    Stopped,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CanisterStatusEntry {
+    pub status: Option<CanisterStatusType>,
+    pub cycles: Option<Nat>,
+    pub memory_size: Option<Nat>,
+    pub topped_up_cycles: Option<Nat>,
+    pub error_message: Option<String>,
+    pub checked_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SpawnPrice {
+    pub total_e8s: Tokens,
+    pub catalyze_fee: Tokens,
+    pub multisig_cycles: Option<u64>,
+    pub xdr_permyriad_per_icp: Option<u64>,
+    pub expires_at: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum RejectionCode {
+    NoError,
+    SysFatal,
+    SysTransient,
+    DestinationInvalid,
+    CanisterReject,
+    CanisterError,
+    Unknown,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum NotifyError {
+    Refunded {
+        block_index: Option<u64>,
+        reason: String,
+    },
+    InvalidTransaction(String),
+    Other {
+        error_message: String,
+        error_code: u64,
+    },
+    Processing,
+    TransactionTooOld(u64),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum TransactionValidationError {
+    BlockNotFound(u64),
+    NoOperation,
+    NotATransfer,
+    InvalidSender,
+    InvalidReceiver,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum MultisigIndexError {
+    CallRejected {
+        code: RejectionCode,
+        message: String,
+    },
+    LedgerTransfer(TransferError),
+    CmcNotify(NotifyError),
+    InvalidTransaction(TransactionValidationError),
+    InsufficientIcp {
+        required: Tokens,
+        available: Tokens,
+    },
+    BlockAlreadyProcessed(u64),
+    InitializationInProgress(Principal),
+    NoBalance,
+    SpawnNotFound(Principal),
+    SpawnIncomplete(Principal),
+    Unauthorized,
+    WasmVersionNotFound(String),
+    WasmVersionExists(String),
+    WasmHashMismatch {
+        expected: String,
+        actual: String,
+    },
+    InvalidConfig(String),
+    LastAdmin,
+    PrincipalLocked(Principal),
+    BlockLocked(u64),
+    LedgerTransferFrom(TransferFromError),
+    PriceUnavailable,
+    MultisigNotFound(Principal),
+    NotController(Principal),
+    InvalidControllers(String),
+    CorruptRecord(String),
+    RefundClaimNotFound(u64),
+    RefundUnavailable(String),
+    TransferNeedsReview(String),
+    TransferNotInReview(String),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Account {
+    pub owner: Principal,
+    pub subaccount: Option<Vec<u8>>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ApproveArgs {
+    pub fee: Option<Nat>,
+    pub memo: Option<Vec<u8>>,
+    pub from_subaccount: Option<Vec<u8>>,
+    pub created_at_time: Option<u64>,
+    pub amount: Nat,
+    pub expected_allowance: Option<Nat>,
+    pub expires_at: Option<u64>,
+    pub spender: Account,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum ApproveError {
+    GenericError { message: String, error_code: Nat },
+    TemporarilyUnavailable,
+    Duplicate { duplicate_of: Nat },
+    BadFee { expected_fee: Nat },
+    AllowanceChanged { current_allowance: Nat },
+    CreatedInFuture { ledger_time: u64 },
+    TooOld,
+    Expired { ledger_time: u64 },
+    InsufficientFunds { balance: Nat },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum TransferFromError {
+    GenericError { message: String, error_code: Nat },
+    TemporarilyUnavailable,
+    InsufficientAllowance { allowance: Nat },
+    BadBurn { min_burn_amount: Nat },
+    Duplicate { duplicate_of: Nat },
+    BadFee { expected_fee: Nat },
+    CreatedInFuture { ledger_time: u64 },
+    TooOld,
+    InsufficientFunds { balance: Nat },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct LedgerInitArgs {
+    pub minting_account: String,
+    pub initial_values: Vec<(String, Tokens)>,
+    pub send_whitelist: Vec<Principal>,
+    pub transfer_fee: Option<Tokens>,
+    pub token_symbol: Option<String>,
+    pub token_name: Option<String>,
+    pub feature_flags: Option<LedgerFeatureFlags>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct LedgerFeatureFlags {
+    pub icrc2: bool,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum LedgerArg {
+    Init(LedgerInitArgs),
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct GetMultisigsArgs {
+    pub start: Option<Principal>,
+    pub limit: Option<u64>,
+    pub direction: Option<SortDirection>,
+    pub created_by: Option<Principal>,
+    pub created_after: Option<u64>,
+    pub created_before: Option<u64>,
+    pub has_group_identifier: Option<bool>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct MultisigsPage {
+    pub multisigs: Vec<MultisigData>,
+    pub next: Option<Principal>,
+    pub total: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct GetTransactionsArgs {
+    pub start: Option<u64>,
+    pub limit: Option<u64>,
+    pub direction: Option<SortDirection>,
+    pub initialized_by: Option<Principal>,
+    pub statuses: Option<Vec<TransactionStatus>>,
+    pub created_after: Option<u64>,
+    pub created_before: Option<u64>,
+    pub from_block: Option<u64>,
+    pub to_block: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct TransactionsPage {
+    pub transactions: Vec<TransactionData>,
+    pub next: Option<u64>,
+    pub total: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CorruptEntry {
+    pub map: String,
+    pub key: String,
+    pub version: Option<u8>,
+    pub error: String,
+    pub size: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    SpawnStarted,
+    SpawnResumed,
+    SpawnCompleted,
+    SpawnFailed,
+    PaymentAccepted,
+    PaymentPulled,
+    CyclesMinted,
+    CanisterCreated,
+    CodeInstalled,
+    MultisigRegistered,
+    Deposit,
+    Withdrawal,
+    WithdrawalFailed,
+    FeePaid,
+    RefundClaimOpened,
+    RefundPaidOut,
+    LedgerTransfer,
+    LedgerTransferFailed,
+    CmcNotified,
+    CmcNotifyFailed,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Event {
+    pub id: u64,
+    pub kind: EventKind,
+    pub principal: Option<Principal>,
+    pub group_identifier: Option<Principal>,
+    pub canister_id: Option<Principal>,
+    pub icp_block_index: Option<u64>,
+    pub block_index: Option<u64>,
+    pub amount: Option<Tokens>,
+    pub cycles: Option<Nat>,
+    pub error_message: Option<String>,
+    pub created_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct EventFilter {
+    pub kinds: Option<Vec<EventKind>>,
+    pub principal: Option<Principal>,
+    pub group_identifier: Option<Principal>,
+    pub canister_id: Option<Principal>,
+    pub icp_block_index: Option<u64>,
+    pub created_after: Option<u64>,
+    pub created_before: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct EventsPage {
+    pub events: Vec<Event>,
+    pub next: Option<u64>,
+    pub total: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub certificate_version: Option<u16>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct HttpResponse {
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct StorageReport {
+    pub schema_version: u64,
+    pub corrupt_entries: Vec<CorruptEntry>,
+}
@@ -0,0 +1,88 @@
+use candid::Principal;
+use integration_tests::{
+    group_identifier, test_env,
+    types::{ControllerUpdate, MultisigIndexError},
+    TestEnv, MIN_E8S_FOR_SPINUP,
+};
+
+fn spawn(env: &TestEnv) -> Principal {
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+    env.spawn_multisig(env.user, block_index, group_identifier(1), None)
+        .expect("Spawn failed")
+}
+
+#[test]
+fn add_and_remove_controller() {
+    let env = test_env!();
+    let canister_id = spawn(&env);
+    let co_controller = group_identifier(5);
+
+    let multisig = env
+        .update_multisig_controllers(env.user, canister_id, ControllerUpdate::Add(co_controller))
+        .expect("Adding the controller failed");
+    assert_eq!(multisig.controllers, Some(vec![env.index, co_controller]));
+    env.pic
+        .stop_canister(canister_id, Some(co_controller))
+        .expect("Co-controller can not control the multisig");
+    env.pic
+        .start_canister(canister_id, Some(co_controller))
+        .unwrap();
+
+    // The multisig itself can change its controllers as well
+    let multisig = env
+        .update_multisig_controllers(
+            canister_id,
+            canister_id,
+            ControllerUpdate::Remove(co_controller),
+        )
+        .expect("Removing the controller failed");
+    assert_eq!(multisig.controllers, Some(vec![env.index]));
+    assert!(env
+        .pic
+        .stop_canister(canister_id, Some(co_controller))
+        .is_err());
+
+    assert!(matches!(
+        env.update_multisig_controllers(env.user, canister_id, ControllerUpdate::Remove(env.index)),
+        Err(MultisigIndexError::InvalidControllers(_))
+    ));
+}
+
+#[test]
+fn only_creator_or_multisig_can_update() {
+    let env = test_env!();
+    let canister_id = spawn(&env);
+    let other = group_identifier(5);
+
+    assert_eq!(
+        env.update_multisig_controllers(other, canister_id, ControllerUpdate::Add(other))
+            .unwrap_err(),
+        MultisigIndexError::Unauthorized
+    );
+}
+
+#[test]
+fn hand_over_multisig() {
+    let env = test_env!();
+    let canister_id = spawn(&env);
+    let group_dao = group_identifier(5);
+
+    let multisig = env
+        .update_multisig_controllers(
+            env.user,
+            canister_id,
+            ControllerUpdate::HandOver(vec![group_dao, canister_id]),
+        )
+        .expect("Handover failed");
+    assert_eq!(multisig.controllers, Some(vec![group_dao, canister_id]));
+    env.pic
+        .stop_canister(canister_id, Some(group_dao))
+        .expect("The group can not control the multisig");
+
+    // The index gave up control, so further changes have to be made by the group itself
+    assert_eq!(
+        env.update_multisig_controllers(env.user, canister_id, ControllerUpdate::Add(env.user))
+            .unwrap_err(),
+        MultisigIndexError::NotController(canister_id)
+    );
+}
@@ -0,0 +1,46 @@
+use candid::Nat;
+use integration_tests::{group_identifier, test_env, MIN_E8S_FOR_SPINUP};
+
+#[test]
+fn cycles_report_tracks_spawns() {
+    let env = test_env!();
+    let opened = env.get_cycles_report().expect("Failed to get the report");
+    assert!(opened.opening_balance > 0u64);
+    assert_eq!(opened.minted, Nat::from(0));
+    assert_eq!(opened.created_canisters, 0);
+
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+    env.spawn_multisig(env.user, block_index, group_identifier(1), None)
+        .expect("Spawn failed");
+
+    let minted = env
+        .get_transaction(block_index)
+        .cycles_amount
+        .expect("No cycles minted");
+    let report = env.get_cycles_report().expect("Failed to get the report");
+    assert_eq!(report.opening_balance, opened.opening_balance);
+    assert_eq!(report.minted, minted);
+    assert_eq!(report.attached, minted);
+    assert_eq!(report.created_canisters, 1);
+
+    // Every minted cycle went to the new canister, so the calls of the spawn were paid from the balance
+    assert!(report.overhead > 0u64);
+    assert!(report.spending_exceeds_recovery);
+    assert_eq!(
+        report.balance + report.attached + report.overhead,
+        report.opening_balance + report.minted
+    );
+}
+
+#[test]
+fn cmc_spawns_do_not_count_as_minted() {
+    let env = test_env!();
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+    env.spawn_multisig_via_cmc(env.user, block_index, group_identifier(1), None)
+        .expect("Spawn failed");
+
+    let report = env.get_cycles_report().expect("Failed to get the report");
+    assert_eq!(report.minted, Nat::from(0));
+    assert_eq!(report.attached, Nat::from(0));
+    assert_eq!(report.created_canisters, 0);
+}
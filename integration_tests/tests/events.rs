@@ -0,0 +1,85 @@
+use integration_tests::{
+    group_identifier, test_env,
+    types::{EventFilter, EventKind},
+    MIN_E8S_FOR_SPINUP,
+};
+
+#[test]
+fn spawn_steps_are_logged() {
+    let env = test_env!();
+    let group = group_identifier(1);
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+    let canister_id = env
+        .spawn_multisig(env.user, block_index, group, None)
+        .expect("Spawn failed");
+
+    let page = env.get_events(
+        None,
+        None,
+        Some(EventFilter {
+            icp_block_index: Some(block_index),
+            ..Default::default()
+        }),
+    );
+    let kinds: Vec<EventKind> = page.events.iter().map(|event| event.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            EventKind::SpawnStarted,
+            EventKind::PaymentAccepted,
+            EventKind::CyclesMinted,
+            EventKind::CanisterCreated,
+            EventKind::CodeInstalled,
+            EventKind::MultisigRegistered,
+            EventKind::FeePaid,
+            EventKind::SpawnCompleted,
+        ]
+    );
+
+    let completed = page.events.last().unwrap();
+    assert_eq!(completed.principal, Some(env.user));
+    assert_eq!(completed.group_identifier, Some(group));
+    assert_eq!(completed.canister_id, Some(canister_id));
+
+    // The ledger and CMC calls are logged as well
+    let calls = env.get_events(
+        None,
+        None,
+        Some(EventFilter {
+            kinds: Some(vec![EventKind::LedgerTransfer, EventKind::CmcNotified]),
+            ..Default::default()
+        }),
+    );
+    assert!(calls.events.len() >= 3);
+}
+
+#[test]
+fn events_are_paginated() {
+    let env = test_env!();
+    let amount = MIN_E8S_FOR_SPINUP / 2;
+    let block_index = env.transfer_to_index(env.user, amount);
+    let _ = env.spawn_multisig(env.user, block_index, group_identifier(1), None);
+    env.withdraw_balance(env.user).expect("Withdraw failed");
+
+    let all = env.get_events(None, None, None);
+    assert!(all.total >= 3);
+    assert_eq!(all.events.len() as u64, all.total);
+    assert!(all.next.is_none());
+
+    let first = env.get_events(None, Some(2), None);
+    assert_eq!(first.events.len(), 2);
+    assert_eq!(first.next, Some(2));
+    let second = env.get_events(first.next, Some(2), None);
+    assert_eq!(second.events[0].id, 2);
+
+    let withdrawals = env.get_events(
+        None,
+        None,
+        Some(EventFilter {
+            kinds: Some(vec![EventKind::Withdrawal]),
+            principal: Some(env.user),
+            ..Default::default()
+        }),
+    );
+    assert_eq!(withdrawals.events.len(), 1);
+}
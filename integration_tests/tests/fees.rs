@@ -0,0 +1,63 @@
+use integration_tests::{
+    group_identifier, test_env,
+    types::{FeePayoutStatus, GetFeePayoutsArgs},
+    CATALYZE_E8S_FEE, ICP_TRANSACTION_FEE, MIN_E8S_FOR_SPINUP,
+};
+
+#[test]
+fn fee_is_paid_out_when_a_spawn_completes() {
+    let env = test_env!();
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+    env.spawn_multisig(env.user, block_index, group_identifier(1), None)
+        .expect("Spawn failed");
+
+    let page = env.get_fee_payouts(GetFeePayoutsArgs::default());
+    assert_eq!(page.total, 1);
+    assert_eq!(page.owed.e8s(), 0);
+    assert_eq!(page.paid.e8s(), CATALYZE_E8S_FEE);
+
+    let payout = &page.payouts[0];
+    assert_eq!(payout.icp_block_index, block_index);
+    assert!(matches!(payout.status, FeePayoutStatus::Paid(_)));
+    assert_eq!(payout.recipient, Some(env.treasury));
+    assert_eq!(
+        env.ledger_balance(env.treasury),
+        CATALYZE_E8S_FEE - ICP_TRANSACTION_FEE
+    );
+}
+
+#[test]
+fn batched_fees_are_paid_out_in_one_transfer() {
+    let env = test_env!();
+    env.set_batch_fee_payouts(true)
+        .expect("Failed to enable batched fee payouts");
+
+    for seed in 1..=2 {
+        let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+        env.spawn_multisig(env.user, block_index, group_identifier(seed), None)
+            .expect("Spawn failed");
+    }
+
+    let pending = env.get_fee_payouts(GetFeePayoutsArgs {
+        pending_only: Some(true),
+        ..Default::default()
+    });
+    assert_eq!(pending.total, 2);
+    assert_eq!(pending.owed.e8s(), 2 * CATALYZE_E8S_FEE);
+    assert_eq!(env.ledger_balance(env.treasury), 0);
+
+    let payouts = env.pay_out_fees();
+    assert_eq!(payouts.len(), 2);
+    assert_eq!(payouts[0].status, payouts[1].status);
+    assert!(matches!(payouts[0].status, FeePayoutStatus::Paid(_)));
+
+    // The sweep pays a single ledger fee for both payouts
+    assert_eq!(
+        env.ledger_balance(env.treasury),
+        2 * CATALYZE_E8S_FEE - ICP_TRANSACTION_FEE
+    );
+    assert_eq!(
+        env.get_fee_payouts(GetFeePayoutsArgs::default()).owed.e8s(),
+        0
+    );
+}
@@ -0,0 +1,39 @@
+use integration_tests::{group_identifier, test_env, MIN_E8S_FOR_SPINUP};
+use serde_json::Value;
+
+#[test]
+fn serve_metrics_and_listings() {
+    let env = test_env!();
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+    let canister_id = env
+        .spawn_multisig(env.user, block_index, group_identifier(1), None)
+        .expect("Spawn failed");
+
+    let response = env.http_get("/metrics");
+    assert_eq!(response.status_code, 200);
+    let metrics = String::from_utf8(response.body).expect("Metrics are not text");
+    assert!(metrics.contains("multisig_index_multisigs 1\n"));
+    assert!(metrics.contains("multisig_index_transactions{status=\"Success\"} 1\n"));
+    assert!(metrics.contains("multisig_index_transactions{status=\"IcpToCmcFailed\"} 0\n"));
+    assert!(metrics.contains("multisig_index_spawns{status=\"done\"} 1\n"));
+
+    let response = env.http_get("/multisigs?limit=10");
+    assert_eq!(response.status_code, 200);
+    let multisigs: Value = serde_json::from_slice(&response.body).expect("Invalid JSON");
+    assert_eq!(multisigs["total"], 1);
+    assert_eq!(
+        multisigs["multisigs"][0]["canister_id"],
+        canister_id.to_string()
+    );
+
+    let response = env.http_get(&format!("/transactions?initialized_by={}", env.user));
+    let transactions: Value = serde_json::from_slice(&response.body).expect("Invalid JSON");
+    assert_eq!(
+        transactions["transactions"][0]["icp_transfer_block_index"],
+        block_index
+    );
+    assert_eq!(transactions["transactions"][0]["status"], "Success");
+
+    assert_eq!(env.http_get("/transactions?limit=ten").status_code, 400);
+    assert_eq!(env.http_get("/unknown").status_code, 404);
+}
@@ -0,0 +1,86 @@
+use candid::{Nat, Principal};
+use ic_ledger_types::Tokens;
+use integration_tests::{
+    group_identifier, test_env,
+    types::{CanisterStatusType, MultisigIndexError},
+    TestEnv, ICP_TRANSACTION_FEE, MIN_E8S_FOR_SPINUP,
+};
+
+// Every check tops up the multisig, the mock CMC mints 1T cycles per top up
+static THRESHOLD_CYCLES: u64 = 1_000_000_000_000_000;
+static TOP_UP_CYCLES: u64 = 2_000_000_000_000;
+// 2T cycles at 10 XDR per ICP
+static TOP_UP_E8S: u64 = 20_000_000;
+
+fn spawn_with_extra(env: &TestEnv, extra: u64) -> Principal {
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP + extra);
+    env.spawn_multisig(env.user, block_index, group_identifier(1), None)
+        .expect("Spawn failed")
+}
+
+#[test]
+fn records_status_without_funds() {
+    let env = test_env!();
+    let canister_id = spawn_with_extra(&env, 0);
+    env.set_top_up_config(Some(THRESHOLD_CYCLES), Some(TOP_UP_CYCLES))
+        .expect("Setting the top up config failed");
+
+    let result = env.monitor_multisigs();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].0, canister_id);
+
+    let history = env.get_status_history(canister_id);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].status, Some(CanisterStatusType::Running));
+    assert!(history[0].cycles.is_some());
+    assert!(history[0].memory_size.is_some());
+    assert!(history[0].topped_up_cycles.is_none());
+}
+
+#[test]
+fn tops_up_from_prepaid_icp() {
+    let env = test_env!();
+    let extra = MIN_E8S_FOR_SPINUP;
+    let canister_id = spawn_with_extra(&env, extra);
+    env.set_top_up_config(Some(THRESHOLD_CYCLES), Some(TOP_UP_CYCLES))
+        .expect("Setting the top up config failed");
+
+    let balance = env
+        .fund_multisig_icp(env.user, canister_id, extra)
+        .expect("Funding failed");
+    assert_eq!(balance.icp, Tokens::from_e8s(extra));
+    assert_eq!(env.get_local_balance(env.user), 0);
+
+    let cycles_before = env.pic.cycle_balance(canister_id);
+    env.monitor_multisigs();
+
+    let entry = env.get_status_history(canister_id).pop().unwrap();
+    assert_eq!(entry.error_message, None);
+    assert_eq!(
+        entry.topped_up_cycles,
+        Some(Nat::from(1_000_000_000_000_u64))
+    );
+    assert!(env.pic.cycle_balance(canister_id) > cycles_before);
+
+    let balance = env.get_prepaid_balance(canister_id);
+    assert_eq!(
+        balance.icp,
+        Tokens::from_e8s(extra - TOP_UP_E8S - ICP_TRANSACTION_FEE)
+    );
+    assert!(balance.pending_top_up.is_none());
+}
+
+#[test]
+fn funding_needs_local_balance() {
+    let env = test_env!();
+    let canister_id = spawn_with_extra(&env, 0);
+
+    assert!(matches!(
+        env.fund_multisig_icp(env.user, canister_id, 1),
+        Err(MultisigIndexError::InsufficientIcp { .. })
+    ));
+    assert!(matches!(
+        env.fund_multisig_icp(env.user, group_identifier(7), 1),
+        Err(MultisigIndexError::MultisigNotFound(_))
+    ));
+}
@@ -0,0 +1,109 @@
+use integration_tests::{
+    group_identifier, test_env,
+    types::{GetMultisigsArgs, GetTransactionsArgs, SortDirection, TransactionStatus},
+    MIN_E8S_FOR_SPINUP,
+};
+
+#[test]
+fn paginate_multisigs() {
+    let env = test_env!();
+    for seed in 1..=3 {
+        let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+        env.spawn_multisig(env.user, block_index, group_identifier(seed), None)
+            .expect("Spawn failed");
+    }
+    let all = env.get_multisigs();
+    assert_eq!(all.len(), 3);
+
+    let first = env.get_multisigs_page(GetMultisigsArgs {
+        limit: Some(2),
+        ..Default::default()
+    });
+    assert_eq!(first.total, 3);
+    assert_eq!(first.multisigs.len(), 2);
+    assert_eq!(first.next, Some(all[2].canister_id));
+
+    let second = env.get_multisigs_page(GetMultisigsArgs {
+        start: first.next,
+        limit: Some(2),
+        ..Default::default()
+    });
+    assert_eq!(second.multisigs.len(), 1);
+    assert_eq!(second.next, None);
+
+    let descending = env.get_multisigs_page(GetMultisigsArgs {
+        direction: Some(SortDirection::Desc),
+        ..Default::default()
+    });
+    let ids: Vec<_> = descending.multisigs.iter().map(|m| m.canister_id).collect();
+    let mut expected: Vec<_> = all.iter().map(|m| m.canister_id).collect();
+    expected.reverse();
+    assert_eq!(ids, expected);
+
+    let filtered = env.get_multisigs_page(GetMultisigsArgs {
+        created_by: Some(group_identifier(9)),
+        ..Default::default()
+    });
+    assert_eq!(filtered.total, 0);
+    assert!(filtered.multisigs.is_empty());
+}
+
+#[test]
+fn filter_transactions() {
+    let env = test_env!();
+    let spawned = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+    env.spawn_multisig(env.user, spawned, group_identifier(1), None)
+        .expect("Spawn failed");
+    let insufficient = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP / 2);
+    let _ = env.spawn_multisig(env.user, insufficient, group_identifier(2), None);
+
+    let page = env.get_transactions_page(GetTransactionsArgs {
+        statuses: Some(vec![TransactionStatus::InsufficientIcp]),
+        ..Default::default()
+    });
+    assert_eq!(page.total, 1);
+    assert_eq!(page.transactions[0].icp_transfer_block_index, insufficient);
+
+    let page = env.get_transactions_page(GetTransactionsArgs {
+        initialized_by: Some(env.user),
+        direction: Some(SortDirection::Desc),
+        limit: Some(1),
+        ..Default::default()
+    });
+    assert_eq!(page.total, 2);
+    assert_eq!(page.transactions[0].icp_transfer_block_index, insufficient);
+    assert_eq!(page.next, Some(spawned));
+
+    let page = env.get_transactions_page(GetTransactionsArgs {
+        from_block: Some(spawned + 1),
+        ..Default::default()
+    });
+    assert_eq!(page.total, 1);
+}
+
+#[test]
+fn lookup_by_secondary_indexes() {
+    let env = test_env!();
+    let group = group_identifier(1);
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+    let canister_id = env
+        .spawn_multisig(env.user, block_index, group, None)
+        .expect("Spawn failed");
+
+    let multisig = env
+        .get_multisig_by_group_identifier(group)
+        .expect("Multisig not found by group");
+    assert_eq!(multisig.canister_id, canister_id);
+    assert!(env
+        .get_multisig_by_group_identifier(group_identifier(2))
+        .is_none());
+
+    let created = env.get_multisigs_by_creator(env.user);
+    assert_eq!(created.len(), 1);
+    assert_eq!(created[0].canister_id, canister_id);
+    assert!(env.get_multisigs_by_creator(group).is_empty());
+
+    let transactions = env.get_transactions_by_initializer(env.user);
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].icp_transfer_block_index, block_index);
+}
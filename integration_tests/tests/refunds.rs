@@ -0,0 +1,102 @@
+use ic_ledger_types::Tokens;
+use integration_tests::{
+    group_identifier, test_env,
+    types::{MultisigIndexError, RefundMethod, RefundResolution, RefundStatus},
+    CATALYZE_E8S_FEE, ICP_TRANSACTION_FEE, MIN_E8S_FOR_SPINUP,
+};
+
+#[test]
+fn failed_spawn_opens_a_refund_claim() {
+    let env = test_env!();
+    let group = group_identifier(1);
+    env.upload_wasm("broken", b"not a wasm module".to_vec());
+
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+    assert!(env
+        .spawn_multisig(env.user, block_index, group, Some("broken".to_string()))
+        .is_err());
+
+    let claims = env.get_refund_claims(env.user);
+    assert_eq!(claims.len(), 1);
+    let claim = &claims[0];
+    assert_eq!(claim.icp_block_index, block_index);
+    assert_eq!(claim.group_identifier, group);
+    assert_eq!(claim.status, RefundStatus::Open);
+    assert!(claim.canister_id.is_some());
+    assert!(claim.error_message.is_some());
+    // The cycles went into the created canister
+    assert!(claim.cycles.is_none());
+    assert!(matches!(
+        env.claim_refund(env.user, block_index, RefundMethod::Cycles(env.index)),
+        Err(MultisigIndexError::RefundUnavailable(_))
+    ));
+
+    // Only the claimant can claim
+    assert!(matches!(
+        env.claim_refund(env.admin, block_index, RefundMethod::Icp),
+        Err(MultisigIndexError::Unauthorized)
+    ));
+}
+
+#[test]
+fn icp_refund_is_reviewed_by_an_admin() {
+    let env = test_env!();
+    let group = group_identifier(1);
+    env.upload_wasm("broken", b"not a wasm module".to_vec());
+
+    // The deposit is larger than the price, only the price is sent to the CMC
+    let excess_e8s = 100_000_000;
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP + excess_e8s);
+    assert!(env
+        .spawn_multisig(env.user, block_index, group, Some("broken".to_string()))
+        .is_err());
+    let balance = env.get_local_balance(env.user);
+    assert_eq!(balance, excess_e8s);
+
+    let claim = env
+        .claim_refund(env.user, block_index, RefundMethod::Icp)
+        .expect("Claim failed");
+    assert_eq!(claim.status, RefundStatus::PendingReview);
+    assert_eq!(env.get_refund_review_queue().len(), 1);
+    let cmc_e8s = MIN_E8S_FOR_SPINUP - CATALYZE_E8S_FEE - ICP_TRANSACTION_FEE;
+    assert_eq!(claim.cmc_amount, Some(Tokens::from_e8s(cmc_e8s)));
+
+    // The payment can not be used for a retry while it is being refunded
+    assert!(matches!(
+        env.resume_spawn(env.user, group),
+        Err(MultisigIndexError::RefundUnavailable(_))
+    ));
+
+    let claim = env
+        .resolve_refund_claim(
+            block_index,
+            RefundResolution::Reject("Wrong amount".to_string()),
+        )
+        .expect("Reject failed");
+    assert_eq!(claim.status, RefundStatus::Open);
+    assert_eq!(claim.review_note, Some("Wrong amount".to_string()));
+    assert!(env.get_refund_review_queue().is_empty());
+
+    env.claim_refund(env.user, block_index, RefundMethod::Icp)
+        .expect("Claim failed");
+    // The excess of the deposit is already on the local balance, so it can not be approved again
+    assert!(matches!(
+        env.resolve_refund_claim(block_index, RefundResolution::Approve(cmc_e8s + 1)),
+        Err(MultisigIndexError::RefundUnavailable(_))
+    ));
+    let refunded_e8s = 50_000_000;
+    let claim = env
+        .resolve_refund_claim(block_index, RefundResolution::Approve(refunded_e8s))
+        .expect("Approve failed");
+    assert_eq!(claim.status, RefundStatus::PaidOut);
+
+    // The Catalyze fee was never transferred, so it is given back as well
+    assert_eq!(
+        env.get_local_balance(env.user),
+        balance + refunded_e8s + CATALYZE_E8S_FEE
+    );
+    assert!(matches!(
+        env.resolve_refund_claim(block_index, RefundResolution::Approve(refunded_e8s)),
+        Err(MultisigIndexError::RefundUnavailable(_))
+    ));
+}
@@ -0,0 +1,454 @@
+use ic_ledger_types::{AccountIdentifier, Tokens};
+use integration_tests::{
+    group_identifier, test_env,
+    types::{
+        CanisterCreation, InitializeStatus, JournalEntryKind, MultisigIndexError, NotifyError,
+        OutgoingTransferStatus, RefundStatus, SpawnStep, TransactionStatus, TransferFromError,
+        TransferResolution,
+    },
+    ICP_TRANSACTION_FEE, INITIAL_USER_E8S, MIN_E8S_FOR_SPINUP,
+};
+
+#[test]
+fn spawn_multisig_happy_path() {
+    let env = test_env!();
+    let group = group_identifier(1);
+
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+    let canister_id = env
+        .spawn_multisig(env.user, block_index, group, None)
+        .expect("Spawn failed");
+
+    let multisigs = env.get_multisigs();
+    assert_eq!(multisigs.len(), 1);
+    assert_eq!(multisigs[0].canister_id, canister_id);
+    assert_eq!(multisigs[0].group_identifier, Some(group));
+    assert_eq!(multisigs[0].created_by, env.user);
+
+    let transaction = env.get_transaction(block_index);
+    assert_eq!(transaction.status, TransactionStatus::Success);
+    assert!(transaction.cmc_transfer_block_index.is_some());
+    assert!(transaction.cycles_amount.is_some());
+
+    assert_eq!(env.get_local_balance(env.user), 0);
+    assert_eq!(
+        env.get_initialization_status(group),
+        Some(InitializeStatus::Done)
+    );
+    let spawn = env.get_spawn(group).expect("Spawn not stored");
+    assert!(spawn.completed_steps.contains(&SpawnStep::FeeTransferred));
+}
+
+#[test]
+fn spawn_multisig_keeps_overpayment() {
+    let env = test_env!();
+    let extra = MIN_E8S_FOR_SPINUP / 2;
+
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP + extra);
+    env.spawn_multisig(env.user, block_index, group_identifier(1), None)
+        .expect("Spawn failed");
+
+    // Only the spin up price is charged, the rest stays on the local balance
+    assert_eq!(env.get_local_balance(env.user), extra);
+
+    let history = env.get_balance_history(env.user);
+    let kinds: Vec<JournalEntryKind> = history.iter().map(|entry| entry.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            JournalEntryKind::Deposit,
+            JournalEntryKind::SpawnSpend,
+            JournalEntryKind::ProtocolFee
+        ]
+    );
+    assert!(history
+        .iter()
+        .all(|entry| entry.block_index == Some(block_index)));
+}
+
+#[test]
+fn spawn_multisig_icrc2() {
+    let env = test_env!();
+    let group = group_identifier(1);
+    env.approve_index(env.user, MIN_E8S_FOR_SPINUP + ICP_TRANSACTION_FEE);
+
+    let canister_id = env
+        .spawn_multisig_icrc2(env.user, group)
+        .expect("Spawn failed");
+
+    assert_eq!(env.get_multisigs()[0].canister_id, canister_id);
+    assert_eq!(env.get_local_balance(env.user), 0);
+    assert_eq!(
+        env.ledger_balance(env.user),
+        INITIAL_USER_E8S - MIN_E8S_FOR_SPINUP - 2 * ICP_TRANSACTION_FEE
+    );
+    // The spawn is charged the price the payment was pulled for
+    let price = env.get_spawn(group).and_then(|spawn| spawn.price);
+    assert_eq!(
+        price.map(|price| price.total_e8s),
+        Some(Tokens::from_e8s(MIN_E8S_FOR_SPINUP))
+    );
+}
+
+#[test]
+fn spawn_multisig_icrc2_without_approval() {
+    let env = test_env!();
+
+    let result = env.spawn_multisig_icrc2(env.user, group_identifier(1));
+
+    assert!(matches!(
+        result,
+        Err(MultisigIndexError::LedgerTransferFrom(
+            TransferFromError::InsufficientAllowance { .. }
+        ))
+    ));
+    assert!(env.get_transactions().is_empty());
+}
+
+#[test]
+fn spawn_multisig_from_deposit_account() {
+    let env = test_env!();
+    let payer = env.user;
+    let owner = group_identifier(9);
+
+    // Anyone can pay into the deposit account of the owner
+    let deposit_account = env.get_deposit_account(owner);
+    env.transfer(
+        payer,
+        AccountIdentifier::from_hex(&deposit_account.account_identifier).unwrap(),
+        MIN_E8S_FOR_SPINUP + ICP_TRANSACTION_FEE,
+    );
+
+    let deposit = env.notify_deposit(owner).expect("Notify failed");
+    assert_eq!(
+        deposit.icp_amount,
+        Some(Tokens::from_e8s(MIN_E8S_FOR_SPINUP))
+    );
+    assert_eq!(env.get_local_balance(owner), MIN_E8S_FOR_SPINUP);
+    assert!(matches!(
+        env.notify_deposit(owner),
+        Err(MultisigIndexError::NoBalance)
+    ));
+
+    env.spawn_multisig(
+        owner,
+        deposit.icp_transfer_block_index,
+        group_identifier(1),
+        None,
+    )
+    .expect("Spawn failed");
+    assert_eq!(env.get_local_balance(owner), 0);
+}
+
+#[test]
+fn spawn_multisig_dynamic_price() {
+    let env = test_env!();
+    // 2T cycles at 10 XDR per ICP is 0.2 ICP, plus a 10% fee and two ledger fees
+    env.set_spawn_pricing(Some(2_000_000_000_000), Some(10))
+        .expect("Setting the pricing failed");
+    let expected_total = 20_000_000 + 2_000_000 + 2 * ICP_TRANSACTION_FEE;
+
+    // The first quote needs a conversion rate, which is fetched by a spawn or the timer
+    env.pic
+        .advance_time(std::time::Duration::from_secs(10 * 60));
+    env.pic.tick();
+    env.pic.tick();
+
+    let price = env.get_spawn_price().expect("No price available");
+    assert_eq!(price.total_e8s, Tokens::from_e8s(expected_total));
+    assert!(price.expires_at.is_some());
+
+    let block_index = env.transfer_to_index(env.user, expected_total);
+    env.spawn_multisig(env.user, block_index, group_identifier(1), None)
+        .expect("Spawn failed");
+    assert_eq!(env.get_local_balance(env.user), 0);
+}
+
+#[test]
+fn spawn_multisig_via_cmc() {
+    let env = test_env!();
+    let group = group_identifier(1);
+    let subnet_type = "fiduciary".to_string();
+    assert_eq!(env.get_subnet_types(), Ok(vec![subnet_type.clone()]));
+
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+    let canister_id = env
+        .spawn_multisig_via_cmc(env.user, block_index, group, Some(subnet_type.clone()))
+        .expect("Spawn failed");
+
+    assert_eq!(env.get_multisigs()[0].canister_id, canister_id);
+    let transaction = env.get_transaction(block_index);
+    assert_eq!(transaction.status, TransactionStatus::Success);
+    assert_eq!(
+        transaction.creation,
+        Some(CanisterCreation::Cmc {
+            subnet_type: Some(subnet_type)
+        })
+    );
+    assert!(transaction.cycles_amount.is_none());
+
+    // The canister is never created from the cycles of the index, so there is no top up step
+    let spawn = env.get_spawn(group).expect("Spawn not stored");
+    assert!(!spawn.completed_steps.contains(&SpawnStep::ToppedUp));
+    assert!(spawn.completed_steps.contains(&SpawnStep::FeeTransferred));
+    assert_eq!(env.get_local_balance(env.user), 0);
+}
+
+#[test]
+fn spawn_multisig_via_cmc_unknown_subnet_type() {
+    let env = test_env!();
+    let group = group_identifier(1);
+
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+    let result =
+        env.spawn_multisig_via_cmc(env.user, block_index, group, Some("unknown".to_string()));
+
+    assert!(matches!(
+        result,
+        Err(MultisigIndexError::CmcNotify(NotifyError::Refunded { .. }))
+    ));
+    assert_eq!(
+        env.get_transaction(block_index).status,
+        TransactionStatus::CyclesToIndexFailed
+    );
+    assert!(env.get_multisigs().is_empty());
+}
+
+#[test]
+fn spawn_multisig_insufficient_icp() {
+    let env = test_env!();
+    let group = group_identifier(1);
+    let amount = MIN_E8S_FOR_SPINUP / 2;
+
+    let block_index = env.transfer_to_index(env.user, amount);
+    let result = env.spawn_multisig(env.user, block_index, group, None);
+
+    assert!(matches!(
+        result,
+        Err(MultisigIndexError::InsufficientIcp { .. })
+    ));
+    assert_eq!(
+        env.get_transaction(block_index).status,
+        TransactionStatus::InsufficientIcp
+    );
+    assert!(env.get_multisigs().is_empty());
+    assert_eq!(env.get_local_balance(env.user), amount);
+}
+
+#[test]
+fn spawn_multisig_retry_insufficient_block() {
+    let env = test_env!();
+    let group = group_identifier(1);
+    let amount = MIN_E8S_FOR_SPINUP / 2;
+
+    let block_index = env.transfer_to_index(env.user, amount);
+    let _ = env.spawn_multisig(env.user, block_index, group, None);
+    let result = env.spawn_multisig(env.user, block_index, group, None);
+
+    // The block is only added to the local balance once
+    assert!(matches!(
+        result,
+        Err(MultisigIndexError::InsufficientIcp { .. })
+    ));
+    assert_eq!(env.get_local_balance(env.user), amount);
+}
+
+#[test]
+fn spawn_multisig_reused_block() {
+    let env = test_env!();
+
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+    env.spawn_multisig(env.user, block_index, group_identifier(1), None)
+        .expect("Spawn failed");
+
+    let result = env.spawn_multisig(env.user, block_index, group_identifier(2), None);
+
+    assert_eq!(
+        result,
+        Err(MultisigIndexError::BlockAlreadyProcessed(block_index))
+    );
+    assert_eq!(env.get_multisigs().len(), 1);
+    assert_eq!(env.get_transactions().len(), 1);
+    assert_eq!(env.get_local_balance(env.user), 0);
+}
+
+#[test]
+fn spawn_multisig_cmc_failure() {
+    let env = test_env!();
+    let group = group_identifier(1);
+    let cmc_error = NotifyError::Other {
+        error_message: "CMC unavailable".to_string(),
+        error_code: 1,
+    };
+    env.set_cmc_error(Some(cmc_error.clone()));
+
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+    let result = env.spawn_multisig(env.user, block_index, group, None);
+
+    assert_eq!(result, Err(MultisigIndexError::CmcNotify(cmc_error)));
+    let transaction = env.get_transaction(block_index);
+    assert_eq!(transaction.status, TransactionStatus::CyclesToIndexFailed);
+    assert!(transaction.cmc_transfer_block_index.is_some());
+    assert!(transaction.cycles_amount.is_none());
+
+    assert!(env.get_multisigs().is_empty());
+    assert_eq!(
+        env.get_initialization_status(group),
+        Some(InitializeStatus::Error)
+    );
+
+    // The ICP left the index, so a claim is opened that can only be paid out as ICP
+    let claims = env.get_refund_claims(env.user);
+    assert_eq!(claims.len(), 1);
+    assert_eq!(claims[0].icp_block_index, block_index);
+    assert_eq!(claims[0].status, RefundStatus::Open);
+    assert!(claims[0].cycles.is_none());
+
+    // The transaction belongs to the spawn, so the cycles are not minted without it
+    env.set_cmc_error(None);
+    assert!(env.reconcile_transactions().is_empty());
+    assert_eq!(
+        env.get_transaction(block_index).status,
+        TransactionStatus::CyclesToIndexFailed
+    );
+
+    // The ICP is already at the CMC, so resuming notifies the CMC again with the stored block
+    let canister_id = env.resume_spawn(env.user, group).expect("Resume failed");
+
+    assert_eq!(env.get_multisigs()[0].canister_id, canister_id);
+    let resumed = env.get_transaction(block_index);
+    assert_eq!(resumed.status, TransactionStatus::Success);
+    assert_eq!(
+        resumed.cmc_transfer_block_index,
+        transaction.cmc_transfer_block_index
+    );
+    assert!(resumed.cycles_amount.is_some());
+    assert!(env
+        .get_spawn(group)
+        .expect("Spawn not stored")
+        .completed_steps
+        .contains(&SpawnStep::ToppedUp));
+    assert_eq!(
+        env.get_initialization_status(group),
+        Some(InitializeStatus::Done)
+    );
+    assert_eq!(
+        env.get_refund_claims(env.user)[0].status,
+        RefundStatus::Credited
+    );
+}
+
+#[test]
+fn spawn_multisig_install_failure() {
+    let env = test_env!();
+    let group = group_identifier(1);
+    env.upload_wasm("broken", b"not a wasm module".to_vec());
+
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+    let result = env.spawn_multisig(env.user, block_index, group, Some("broken".to_string()));
+
+    assert!(matches!(
+        result,
+        Err(MultisigIndexError::CallRejected { .. })
+    ));
+    assert_eq!(
+        env.get_transaction(block_index).status,
+        TransactionStatus::Success
+    );
+    assert!(env.get_multisigs().is_empty());
+    assert_eq!(
+        env.get_initialization_status(group),
+        Some(InitializeStatus::Error)
+    );
+
+    // The created canister is kept so the spawn can be resumed
+    let spawn = env.get_spawn(group).expect("Spawn not stored");
+    assert!(spawn.canister_id.is_some());
+    assert_eq!(
+        spawn.completed_steps,
+        vec![SpawnStep::ToppedUp, SpawnStep::CanisterCreated]
+    );
+}
+
+#[test]
+fn withdraw_balance() {
+    let env = test_env!();
+    let amount = MIN_E8S_FOR_SPINUP / 2;
+
+    let block_index = env.transfer_to_index(env.user, amount);
+    let _ = env.spawn_multisig(env.user, block_index, group_identifier(1), None);
+    assert_eq!(env.get_local_balance(env.user), amount);
+
+    env.withdraw_balance(env.user).expect("Withdraw failed");
+
+    assert_eq!(env.get_local_balance(env.user), 0);
+    assert_eq!(
+        env.ledger_balance(env.user),
+        INITIAL_USER_E8S - 2 * ICP_TRANSACTION_FEE
+    );
+    assert_eq!(
+        env.withdraw_balance(env.user),
+        Err(MultisigIndexError::NoBalance)
+    );
+}
+
+#[test]
+fn withdrawal_needing_review_is_resolved_by_an_admin() {
+    let env = test_env!();
+    let amount = MIN_E8S_FOR_SPINUP / 2;
+
+    let block_index = env.transfer_to_index(env.user, amount);
+    let _ = env.spawn_multisig(env.user, block_index, group_identifier(1), None);
+
+    // The ledger is stopped, so the outcome of the withdrawal is unknown and it stays pending
+    env.pic.stop_canister(env.ledger, None).unwrap();
+    assert!(env.withdraw_balance(env.user).is_err());
+    env.pic.start_canister(env.ledger, None).unwrap();
+    let transfer_id = env.get_pending_transfers()[0].id.clone();
+
+    // The retry is outside of the deduplication window, the ledger can no longer compare it with the first attempt
+    env.pic
+        .advance_time(std::time::Duration::from_secs(25 * 60 * 60));
+    assert_eq!(
+        env.withdraw_balance(env.user),
+        Err(MultisigIndexError::TransferNeedsReview(transfer_id.clone()))
+    );
+    assert!(matches!(
+        env.get_pending_transfers()[0].status,
+        OutgoingTransferStatus::NeedsReview(_)
+    ));
+    assert_eq!(env.get_local_balance(env.user), 0);
+
+    // No attempt went through, so the withdrawn amount is given back
+    env.resolve_transfer(&transfer_id, TransferResolution::Failed)
+        .expect("Resolve failed");
+    assert_eq!(env.get_local_balance(env.user), amount);
+    assert!(env.get_pending_transfers().is_empty());
+    assert_eq!(
+        env.resolve_transfer(&transfer_id, TransferResolution::Failed),
+        Err(MultisigIndexError::TransferNotInReview(transfer_id))
+    );
+
+    env.withdraw_balance(env.user).expect("Withdraw failed");
+    assert_eq!(env.get_local_balance(env.user), 0);
+    assert_eq!(
+        env.ledger_balance(env.user),
+        INITIAL_USER_E8S - 2 * ICP_TRANSACTION_FEE
+    );
+}
+
+#[test]
+fn no_transfers_stay_pending() {
+    let env = test_env!();
+
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+    env.spawn_multisig(env.user, block_index, group_identifier(1), None)
+        .expect("Spawn failed");
+
+    let amount = MIN_E8S_FOR_SPINUP / 2;
+    let block_index = env.transfer_to_index(env.user, amount);
+    let _ = env.spawn_multisig(env.user, block_index, group_identifier(2), None);
+    env.withdraw_balance(env.user).expect("Withdraw failed");
+
+    assert!(env.get_pending_transfers().is_empty());
+}
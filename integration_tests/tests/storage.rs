@@ -0,0 +1,31 @@
+use integration_tests::{group_identifier, test_env, MIN_E8S_FOR_SPINUP};
+
+#[test]
+fn records_survive_an_upgrade() {
+    let env = test_env!();
+    let group = group_identifier(1);
+    let block_index = env.transfer_to_index(env.user, MIN_E8S_FOR_SPINUP);
+    let canister_id = env
+        .spawn_multisig(env.user, block_index, group, None)
+        .expect("Spawn failed");
+
+    let report = env.get_storage_report();
+    assert_eq!(report.schema_version, 1);
+    assert!(report.corrupt_entries.is_empty());
+
+    env.upgrade_index();
+
+    let multisig = env
+        .get_multisig_by_group_identifier(group)
+        .expect("Multisig not found after the upgrade");
+    assert_eq!(multisig.canister_id, canister_id);
+    assert_eq!(
+        env.get_transaction(block_index).icp_transfer_block_index,
+        block_index
+    );
+    assert!(!env.get_balance_history(env.user).is_empty());
+
+    let report = env.get_storage_report();
+    assert_eq!(report.schema_version, 1);
+    assert!(report.corrupt_entries.is_empty());
+}
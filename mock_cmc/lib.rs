@@ -0,0 +1,191 @@
+use std::{cell::RefCell, collections::BTreeMap};
+
+use candid::{CandidType, Deserialize, Nat, Principal};
+use ic_cdk::{
+    api::{
+        management_canister::{
+            main::{create_canister, deposit_cycles, CanisterIdRecord, CreateCanisterArgument},
+            provisional::CanisterSettings,
+        },
+        time,
+    },
+    query, update,
+};
+
+// Cycles minted for every notified block, the real CMC converts the transferred ICP
+pub static CYCLES_PER_TOP_UP: u128 = 1_000_000_000_000;
+// 10 XDR per ICP
+pub static XDR_PERMYRIAD_PER_ICP: u64 = 100_000;
+// The real CMC maps every subnet type to its subnets, the mock creates every canister on its own subnet
+pub static SUBNET_TYPES: &[&str] = &["fiduciary"];
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum NotifyError {
+    Refunded {
+        block_index: Option<u64>,
+        reason: String,
+    },
+    InvalidTransaction(String),
+    Other {
+        error_message: String,
+        error_code: u64,
+    },
+    Processing,
+    TransactionTooOld(u64),
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct IcpXdrConversionRate {
+    pub xdr_permyriad_per_icp: u64,
+    pub timestamp_seconds: u64,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct IcpXdrConversionRateResponse {
+    pub certificate: Vec<u8>,
+    pub data: IcpXdrConversionRate,
+    pub hash_tree: Vec<u8>,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct NotifyTopUpArg {
+    pub block_index: u64,
+    pub canister_id: Principal,
+}
+
+#[derive(CandidType, Deserialize)]
+pub enum NotifyTopUpResult {
+    Ok(Nat),
+    Err(NotifyError),
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct NotifyCreateCanisterArg {
+    pub controller: Principal,
+    pub block_index: u64,
+    pub subnet_type: Option<String>,
+}
+
+#[derive(CandidType, Deserialize)]
+pub enum NotifyCreateCanisterResult {
+    Ok(Principal),
+    Err(NotifyError),
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct SubnetTypesToSubnetsResponse {
+    pub data: Vec<(String, Vec<Principal>)>,
+}
+
+thread_local! {
+    static NOTIFY_ERROR: RefCell<Option<NotifyError>> = const { RefCell::new(None) };
+    static NOTIFIED_BLOCKS: RefCell<BTreeMap<u64, Nat>> = const { RefCell::new(BTreeMap::new()) };
+    static CREATED_CANISTERS: RefCell<BTreeMap<u64, Principal>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+// Make every following notify call fail with the given error, pass `None` to succeed again
+#[update]
+fn set_notify_error(error: Option<NotifyError>) {
+    NOTIFY_ERROR.with(|e| *e.borrow_mut() = error);
+}
+
+// The real CMC certifies the rate, the index does not verify the certificate so it is left empty
+#[query]
+fn get_icp_xdr_conversion_rate() -> IcpXdrConversionRateResponse {
+    IcpXdrConversionRateResponse {
+        certificate: vec![],
+        data: IcpXdrConversionRate {
+            xdr_permyriad_per_icp: XDR_PERMYRIAD_PER_ICP,
+            timestamp_seconds: time() / 1_000_000_000,
+        },
+        hash_tree: vec![],
+    }
+}
+
+// Unlike the real CMC the block is not checked on the ledger, but the call is idempotent per block
+#[update]
+async fn notify_top_up(arg: NotifyTopUpArg) -> NotifyTopUpResult {
+    if let Some(err) = NOTIFY_ERROR.with(|e| e.borrow().clone()) {
+        return NotifyTopUpResult::Err(err);
+    }
+
+    if let Some(cycles) = NOTIFIED_BLOCKS.with(|n| n.borrow().get(&arg.block_index).cloned()) {
+        return NotifyTopUpResult::Ok(cycles);
+    }
+
+    let result = deposit_cycles(
+        CanisterIdRecord {
+            canister_id: arg.canister_id,
+        },
+        CYCLES_PER_TOP_UP,
+    )
+    .await;
+
+    match result {
+        Ok(()) => {
+            let cycles = Nat::from(CYCLES_PER_TOP_UP);
+            NOTIFIED_BLOCKS.with(|n| n.borrow_mut().insert(arg.block_index, cycles.clone()));
+            NotifyTopUpResult::Ok(cycles)
+        }
+        Err((_, err)) => NotifyTopUpResult::Err(NotifyError::Other {
+            error_message: err,
+            error_code: 0,
+        }),
+    }
+}
+
+#[query]
+fn get_subnet_types_to_subnets() -> SubnetTypesToSubnetsResponse {
+    SubnetTypesToSubnetsResponse {
+        data: SUBNET_TYPES
+            .iter()
+            .map(|subnet_type| (subnet_type.to_string(), vec![]))
+            .collect(),
+    }
+}
+
+// Creates the canister from the cycles of the mock, idempotent per block like `notify_top_up`
+#[update]
+async fn notify_create_canister(arg: NotifyCreateCanisterArg) -> NotifyCreateCanisterResult {
+    if let Some(err) = NOTIFY_ERROR.with(|e| e.borrow().clone()) {
+        return NotifyCreateCanisterResult::Err(err);
+    }
+
+    if let Some(canister_id) = CREATED_CANISTERS.with(|c| c.borrow().get(&arg.block_index).cloned())
+    {
+        return NotifyCreateCanisterResult::Ok(canister_id);
+    }
+
+    if let Some(subnet_type) = &arg.subnet_type {
+        if !SUBNET_TYPES.contains(&subnet_type.as_str()) {
+            return NotifyCreateCanisterResult::Err(NotifyError::Refunded {
+                block_index: None,
+                reason: format!("Provided subnet type {} does not exist", subnet_type),
+            });
+        }
+    }
+
+    let result = create_canister(
+        CreateCanisterArgument {
+            settings: Some(CanisterSettings {
+                controllers: Some(vec![arg.controller]),
+                compute_allocation: None,
+                memory_allocation: None,
+                freezing_threshold: None,
+            }),
+        },
+        CYCLES_PER_TOP_UP,
+    )
+    .await;
+
+    match result {
+        Ok((record,)) => {
+            CREATED_CANISTERS.with(|c| c.borrow_mut().insert(arg.block_index, record.canister_id));
+            NotifyCreateCanisterResult::Ok(record.canister_id)
+        }
+        Err((_, err)) => NotifyCreateCanisterResult::Err(NotifyError::Other {
+            error_message: err,
+            error_code: 0,
+        }),
+    }
+}
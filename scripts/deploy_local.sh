@@ -0,0 +1,24 @@
+#!/bin/sh
+
+# Deploys the index against a locally installed ICP ledger and cycles minting canister,
+# see the "Local deployment" section in the README for the required network configuration
+
+LEDGER_CANISTER_ID=${LEDGER_CANISTER_ID:-"ryjl3-tyaaa-aaaaa-aaaba-cai"}
+CMC_CANISTER_ID=${CMC_CANISTER_ID:-"rkp4c-7iaaa-aaaaa-aaaca-cai"}
+ADMIN=$(dfx identity get-principal)
+
+echo "> Starting local replica.."
+dfx start --clean --background
+
+echo "> Installing the NNS canisters (ledger and cycles minting canister).."
+dfx nns install
+
+echo "> Deploying multisig_index.."
+dfx deploy multisig_index --argument "(opt record {
+    admins = opt vec { principal \"$ADMIN\" };
+    fee_recipient = opt principal \"$ADMIN\";
+    catalyze_e8s_fee = null;
+    min_e8s_for_spinup = null;
+    ledger_canister_id = opt principal \"$LEDGER_CANISTER_ID\";
+    cmc_canister_id = opt principal \"$CMC_CANISTER_ID\";
+})"
@@ -0,0 +1,22 @@
+#!/bin/sh
+set -e
+
+# Runs the PocketIC integration tests, requires:
+# - POCKET_IC_BIN: path to a PocketIC server binary matching the pocket-ic crate version
+# - ICP_LEDGER_WASM: path to the ICP ledger wasm (ledger-canister.wasm.gz from an IC release)
+
+if [ -z "$POCKET_IC_BIN" ]; then
+    echo "POCKET_IC_BIN is not set"
+    exit 1
+fi
+
+if [ -z "$ICP_LEDGER_WASM" ]; then
+    echo "ICP_LEDGER_WASM is not set"
+    exit 1
+fi
+
+echo "> Building canisters.."
+cargo build --release --target wasm32-unknown-unknown -p multisig_index -p mock_cmc
+
+echo "> Running integration tests.."
+cargo test -p integration_tests
@@ -0,0 +1,20 @@
+use ic_cdk::{api::is_controller as caller_is_controller, caller};
+
+use crate::logic::config::Config;
+
+pub fn is_controller() -> Result<(), String> {
+    if caller_is_controller(&caller()) {
+        Ok(())
+    } else {
+        Err("Caller is not a controller of this canister".to_string())
+    }
+}
+
+// Controllers of this canister are always allowed to act as admin
+pub fn is_admin() -> Result<(), String> {
+    if Config::is_admin(&caller()) || caller_is_controller(&caller()) {
+        Ok(())
+    } else {
+        Err("Caller is not an admin".to_string())
+    }
+}
@@ -1,11 +1,33 @@
+pub mod guards;
 pub mod methods;
 
 pub mod logic {
     pub mod cmc;
+    pub mod config;
+    pub mod controllers;
+    pub mod cycles;
+    pub mod deposit;
+    pub mod events;
+    pub mod fees;
+    pub mod http;
+    pub mod indexes;
+    pub mod journal;
     pub mod ledger;
+    pub mod lock;
+    pub mod monitor;
+    pub mod pagination;
+    pub mod pricing;
+    pub mod reconciliation;
+    pub mod refunds;
+    pub mod schema;
     pub mod store;
+    pub mod transfers;
+    pub mod upgrade;
+    pub mod wasm;
 }
 pub mod rust_declarations {
     pub mod cmc_service;
+    pub mod icp_ledger_service;
+    pub mod stored;
     pub mod types;
 }
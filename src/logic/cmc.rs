@@ -1,25 +1,106 @@
-use candid::Nat;
+use candid::{Nat, Principal};
 use ic_cdk::id;
-use ic_ledger_types::MAINNET_CYCLES_MINTING_CANISTER_ID;
 
-use crate::rust_declarations::cmc_service::{CmcService, NotifyTopUpArg, NotifyTopUpResult};
+use crate::rust_declarations::{
+    cmc_service::{
+        CmcService, NotifyCreateCanisterArg, NotifyCreateCanisterResult, NotifyTopUpArg,
+        NotifyTopUpResult,
+    },
+    types::{Event, EventKind, MultisigIndexError},
+};
+
+use super::{config::Config, events::Events};
 
 pub struct CMC {}
 
 impl CMC {
-    pub async fn top_up_self(block_index: u64) -> Result<Nat, String> {
-        match CmcService(MAINNET_CYCLES_MINTING_CANISTER_ID)
+    pub async fn top_up_self(block_index: u64) -> Result<Nat, MultisigIndexError> {
+        Self::top_up(block_index, id()).await
+    }
+
+    // The block should be a transfer to the subaccount of the canister that is topped up
+    pub async fn top_up(
+        block_index: u64,
+        canister_id: Principal,
+    ) -> Result<Nat, MultisigIndexError> {
+        let result = match CmcService(Config::cmc_canister_id())
             .notify_top_up(NotifyTopUpArg {
                 block_index,
-                canister_id: id(),
+                canister_id,
             })
             .await
         {
             Ok((result,)) => match result {
                 NotifyTopUpResult::Ok(cycles) => Ok(cycles),
-                NotifyTopUpResult::Err(err) => Err(format!("{:?}", err)),
+                NotifyTopUpResult::Err(err) => Err(MultisigIndexError::CmcNotify(err)),
+            },
+            Err(err) => Err(err.into()),
+        };
+
+        let event = match &result {
+            Ok(cycles) => Event {
+                cycles: Some(cycles.clone()),
+                ..Event::new(EventKind::CmcNotified)
             },
-            Err((_, err)) => Err(err),
+            Err(err) => Self::failed_event(err),
+        };
+        Events::record(Event {
+            canister_id: Some(canister_id),
+            block_index: Some(block_index),
+            ..event
+        });
+        result
+    }
+
+    // The created canister is controlled by this canister, notifying the same block again returns the same canister
+    pub async fn create_canister(
+        block_index: u64,
+        subnet_type: Option<String>,
+    ) -> Result<Principal, MultisigIndexError> {
+        let result = match CmcService(Config::cmc_canister_id())
+            .notify_create_canister(NotifyCreateCanisterArg {
+                controller: id(),
+                block_index,
+                subnet_type,
+            })
+            .await
+        {
+            Ok((result,)) => match result {
+                NotifyCreateCanisterResult::Ok(canister_id) => Ok(canister_id),
+                NotifyCreateCanisterResult::Err(err) => Err(MultisigIndexError::CmcNotify(err)),
+            },
+            Err(err) => Err(err.into()),
+        };
+
+        let event = match &result {
+            Ok(canister_id) => Event {
+                canister_id: Some(*canister_id),
+                ..Event::new(EventKind::CmcNotified)
+            },
+            Err(err) => Self::failed_event(err),
+        };
+        Events::record(Event {
+            block_index: Some(block_index),
+            ..event
+        });
+        result
+    }
+
+    fn failed_event(err: &MultisigIndexError) -> Event {
+        Event {
+            error_message: Some(err.to_string()),
+            ..Event::new(EventKind::CmcNotifyFailed)
         }
     }
+
+    pub async fn get_subnet_types() -> Result<Vec<String>, MultisigIndexError> {
+        let (response,) = CmcService(Config::cmc_canister_id())
+            .get_subnet_types_to_subnets()
+            .await?;
+        Ok(response
+            .data
+            .into_iter()
+            .map(|(subnet_type, _)| subnet_type)
+            .collect())
+    }
 }
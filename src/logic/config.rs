@@ -0,0 +1,222 @@
+use candid::Principal;
+use ic_cdk::api::time;
+use ic_ledger_types::{Tokens, MAINNET_CYCLES_MINTING_CANISTER_ID, MAINNET_LEDGER_CANISTER_ID};
+
+use crate::rust_declarations::types::{IndexConfig, InitArgs, MultisigIndexError};
+
+use super::monitor::{DEFAULT_TOP_UP_CYCLES, DEFAULT_TOP_UP_THRESHOLD_CYCLES};
+use super::store::{
+    ADMINS, CATALYZE_E8S_FEE, CATALYZE_MULTI_SIG, CONFIG, ICP_TRANSACTION_FEE, MIN_E8S_FOR_SPINUP,
+};
+
+impl Default for IndexConfig {
+    fn default() -> Self {
+        IndexConfig {
+            fee_recipient: Principal::from_text(CATALYZE_MULTI_SIG).unwrap(),
+            catalyze_e8s_fee: CATALYZE_E8S_FEE,
+            min_e8s_for_spinup: MIN_E8S_FOR_SPINUP,
+            ledger_canister_id: None,
+            cmc_canister_id: None,
+            multisig_cycles: None,
+            catalyze_fee_percentage: None,
+            top_up_threshold_cycles: None,
+            top_up_cycles: None,
+            batch_fee_payouts: None,
+        }
+    }
+}
+
+pub struct Config;
+
+impl Config {
+    pub fn get() -> IndexConfig {
+        CONFIG.with(|c| c.borrow().get().clone())
+    }
+
+    pub fn ledger_canister_id() -> Principal {
+        Self::get()
+            .ledger_canister_id
+            .unwrap_or(MAINNET_LEDGER_CANISTER_ID)
+    }
+
+    pub fn cmc_canister_id() -> Principal {
+        Self::get()
+            .cmc_canister_id
+            .unwrap_or(MAINNET_CYCLES_MINTING_CANISTER_ID)
+    }
+
+    // Apply the init or upgrade arguments, fields that are not passed keep their current value
+    pub fn apply_init_args(args: InitArgs) {
+        if let Some(admins) = args.admins {
+            for admin in admins {
+                Self::add_admin(admin);
+            }
+        }
+
+        let mut config = Self::get();
+        if let Some(fee_recipient) = args.fee_recipient {
+            config.fee_recipient = fee_recipient;
+        }
+        if let Some(catalyze_e8s_fee) = args.catalyze_e8s_fee {
+            config.catalyze_e8s_fee = Tokens::from_e8s(catalyze_e8s_fee);
+        }
+        if let Some(min_e8s_for_spinup) = args.min_e8s_for_spinup {
+            config.min_e8s_for_spinup = Tokens::from_e8s(min_e8s_for_spinup);
+        }
+        if let Some(ledger_canister_id) = args.ledger_canister_id {
+            config.ledger_canister_id = Some(ledger_canister_id);
+        }
+        if let Some(cmc_canister_id) = args.cmc_canister_id {
+            config.cmc_canister_id = Some(cmc_canister_id);
+        }
+        if let Some(multisig_cycles) = args.multisig_cycles {
+            config.multisig_cycles = Some(multisig_cycles);
+        }
+        if let Some(catalyze_fee_percentage) = args.catalyze_fee_percentage {
+            config.catalyze_fee_percentage = Some(catalyze_fee_percentage);
+        }
+        if let Some(top_up_threshold_cycles) = args.top_up_threshold_cycles {
+            config.top_up_threshold_cycles = Some(top_up_threshold_cycles);
+        }
+        if let Some(top_up_cycles) = args.top_up_cycles {
+            config.top_up_cycles = Some(top_up_cycles);
+        }
+        if let Some(batch_fee_payouts) = args.batch_fee_payouts {
+            config.batch_fee_payouts = Some(batch_fee_payouts);
+        }
+
+        // Trap on invalid arguments so the install or upgrade is rolled back
+        if let Err(err) = Self::set(config) {
+            ic_cdk::trap(&err.to_string());
+        }
+    }
+
+    pub fn set_fee_recipient(fee_recipient: Principal) -> Result<IndexConfig, MultisigIndexError> {
+        let mut config = Self::get();
+        config.fee_recipient = fee_recipient;
+        Self::set(config)
+    }
+
+    pub fn set_catalyze_e8s_fee(e8s: u64) -> Result<IndexConfig, MultisigIndexError> {
+        let mut config = Self::get();
+        config.catalyze_e8s_fee = Tokens::from_e8s(e8s);
+        Self::set(config)
+    }
+
+    pub fn set_min_e8s_for_spinup(e8s: u64) -> Result<IndexConfig, MultisigIndexError> {
+        let mut config = Self::get();
+        config.min_e8s_for_spinup = Tokens::from_e8s(e8s);
+        Self::set(config)
+    }
+
+    // Pass no cycles amount to go back to the fixed e8s price
+    pub fn set_spawn_pricing(
+        multisig_cycles: Option<u64>,
+        catalyze_fee_percentage: Option<u64>,
+    ) -> Result<IndexConfig, MultisigIndexError> {
+        let mut config = Self::get();
+        config.multisig_cycles = multisig_cycles;
+        config.catalyze_fee_percentage = catalyze_fee_percentage;
+        Self::set(config)
+    }
+
+    // Pass no values to go back to the defaults
+    pub fn set_top_up_config(
+        top_up_threshold_cycles: Option<u64>,
+        top_up_cycles: Option<u64>,
+    ) -> Result<IndexConfig, MultisigIndexError> {
+        let mut config = Self::get();
+        config.top_up_threshold_cycles = top_up_threshold_cycles;
+        config.top_up_cycles = top_up_cycles;
+        Self::set(config)
+    }
+
+    pub fn set_batch_fee_payouts(
+        batch_fee_payouts: bool,
+    ) -> Result<IndexConfig, MultisigIndexError> {
+        let mut config = Self::get();
+        config.batch_fee_payouts = Some(batch_fee_payouts);
+        Self::set(config)
+    }
+
+    pub fn batch_fee_payouts() -> bool {
+        Self::get().batch_fee_payouts.unwrap_or_default()
+    }
+
+    pub fn top_up_threshold_cycles() -> u64 {
+        Self::get()
+            .top_up_threshold_cycles
+            .unwrap_or(DEFAULT_TOP_UP_THRESHOLD_CYCLES)
+    }
+
+    pub fn top_up_cycles() -> u64 {
+        Self::get().top_up_cycles.unwrap_or(DEFAULT_TOP_UP_CYCLES)
+    }
+
+    fn set(config: IndexConfig) -> Result<IndexConfig, MultisigIndexError> {
+        // The fee transfer and the transfer to the CMC both need to cover the ledger fee
+        if config.catalyze_e8s_fee <= ICP_TRANSACTION_FEE {
+            return Err(MultisigIndexError::InvalidConfig(
+                "Catalyze fee should be higher as the transaction fee".to_string(),
+            ));
+        }
+        if config.min_e8s_for_spinup.e8s()
+            <= config.catalyze_e8s_fee.e8s() + ICP_TRANSACTION_FEE.e8s()
+        {
+            return Err(MultisigIndexError::InvalidConfig(
+                "Spin up amount should be higher as the Catalyze fee and the transaction fee"
+                    .to_string(),
+            ));
+        }
+
+        if config.multisig_cycles == Some(0) {
+            return Err(MultisigIndexError::InvalidConfig(
+                "Multisig cycles should be higher as zero".to_string(),
+            ));
+        }
+        if config.catalyze_fee_percentage.unwrap_or_default() > 100 {
+            return Err(MultisigIndexError::InvalidConfig(
+                "Catalyze fee percentage should not be higher as 100".to_string(),
+            ));
+        }
+
+        if config.top_up_cycles == Some(0) {
+            return Err(MultisigIndexError::InvalidConfig(
+                "Top up cycles should be higher as zero".to_string(),
+            ));
+        }
+
+        CONFIG
+            .with(|c| c.borrow_mut().set(config.clone()))
+            .expect("Failed to store the config");
+        Ok(config)
+    }
+
+    pub fn get_admins() -> Vec<Principal> {
+        ADMINS.with(|a| {
+            a.borrow()
+                .iter()
+                .filter_map(|(k, _)| Principal::from_text(k).ok())
+                .collect()
+        })
+    }
+
+    pub fn is_admin(principal: &Principal) -> bool {
+        ADMINS.with(|a| a.borrow().contains_key(&principal.to_string()))
+    }
+
+    pub fn add_admin(principal: Principal) {
+        ADMINS.with(|a| a.borrow_mut().insert(principal.to_string(), time()));
+    }
+
+    pub fn remove_admin(principal: Principal) -> Result<(), MultisigIndexError> {
+        ADMINS.with(|a| {
+            let mut admins = a.borrow_mut();
+            if admins.len() <= 1 && admins.contains_key(&principal.to_string()) {
+                return Err(MultisigIndexError::LastAdmin);
+            }
+            admins.remove(&principal.to_string());
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,223 @@
+use candid::Principal;
+use ic_cdk::{
+    api::{
+        management_canister::{
+            main::{update_settings, UpdateSettingsArgument},
+            provisional::CanisterSettings,
+        },
+        time,
+    },
+    id,
+};
+
+use crate::rust_declarations::{
+    stored::Stored,
+    types::{ControllerUpdate, MultisigData, MultisigIndexError},
+};
+
+use super::{
+    lock::PrincipalLock,
+    store::{Store, ENTRIES},
+};
+
+// The IC does not accept more controllers on a single canister
+pub static MAX_CONTROLLERS: usize = 10;
+
+pub struct Controllers;
+
+impl Controllers {
+    pub fn get_controllers(multisig: &MultisigData) -> Vec<Principal> {
+        multisig.controllers.clone().unwrap_or_else(|| vec![id()])
+    }
+
+    pub fn is_index_controller(multisig: &MultisigData) -> bool {
+        Self::get_controllers(multisig).contains(&id())
+    }
+
+    // The creator of the multisig or the multisig itself can change the controllers, as long as this canister
+    // is still one of them
+    pub async fn update_controllers(
+        caller: Principal,
+        canister_id: Principal,
+        update: ControllerUpdate,
+    ) -> Result<MultisigData, MultisigIndexError> {
+        // Only one change per multisig at a time, so a change is never based on an outdated controller set
+        let _lock = PrincipalLock::acquire(canister_id)?;
+
+        let mut multisig = ENTRIES
+            .with(|e| e.borrow().get(&canister_id.to_string()))
+            .and_then(Stored::valid)
+            .ok_or(MultisigIndexError::MultisigNotFound(canister_id))?;
+
+        if caller != multisig.created_by && caller != canister_id {
+            return Err(MultisigIndexError::Unauthorized);
+        }
+
+        if !Self::is_index_controller(&multisig) {
+            return Err(MultisigIndexError::NotController(canister_id));
+        }
+
+        let controllers = Self::apply_update(Self::get_controllers(&multisig), update, id())?;
+
+        update_settings(UpdateSettingsArgument {
+            canister_id,
+            settings: CanisterSettings {
+                controllers: Some(controllers.clone()),
+                compute_allocation: None,
+                memory_allocation: None,
+                freezing_threshold: None,
+            },
+        })
+        .await?;
+
+        multisig.controllers = Some(controllers);
+        multisig.updated_at = time();
+        Store::insert_multisig(multisig.clone());
+        Ok(multisig)
+    }
+
+    // The index is passed in as the id of this canister is only known inside the canister
+    fn apply_update(
+        mut controllers: Vec<Principal>,
+        update: ControllerUpdate,
+        index: Principal,
+    ) -> Result<Vec<Principal>, MultisigIndexError> {
+        match update {
+            ControllerUpdate::Add(controller) => {
+                if controllers.contains(&controller) {
+                    return Err(MultisigIndexError::InvalidControllers(
+                        "Principal is already a controller".to_string(),
+                    ));
+                }
+                controllers.push(controller);
+            }
+            ControllerUpdate::Remove(controller) => {
+                if controller == index {
+                    return Err(MultisigIndexError::InvalidControllers(
+                        "The index can only be removed with a handover".to_string(),
+                    ));
+                }
+                if !controllers.contains(&controller) {
+                    return Err(MultisigIndexError::InvalidControllers(
+                        "Principal is not a controller".to_string(),
+                    ));
+                }
+                controllers.retain(|c| c != &controller);
+            }
+            ControllerUpdate::HandOver(new_controllers) => {
+                if new_controllers.is_empty() {
+                    return Err(MultisigIndexError::InvalidControllers(
+                        "A handover needs at least one controller".to_string(),
+                    ));
+                }
+                if new_controllers.contains(&index) {
+                    return Err(MultisigIndexError::InvalidControllers(
+                        "The index can not stay a controller after a handover".to_string(),
+                    ));
+                }
+                controllers = vec![];
+                for controller in new_controllers {
+                    if !controllers.contains(&controller) {
+                        controllers.push(controller);
+                    }
+                }
+            }
+        }
+
+        if controllers.len() > MAX_CONTROLLERS {
+            return Err(MultisigIndexError::InvalidControllers(format!(
+                "A multisig can have at most {} controllers",
+                MAX_CONTROLLERS
+            )));
+        }
+        Ok(controllers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn principal(seed: u8) -> Principal {
+        Principal::from_slice(&[seed; 29])
+    }
+
+    fn is_invalid(result: Result<Vec<Principal>, MultisigIndexError>) -> bool {
+        matches!(result, Err(MultisigIndexError::InvalidControllers(_)))
+    }
+
+    #[test]
+    fn add_appends_a_new_controller() {
+        let index = principal(0);
+        let controllers =
+            Controllers::apply_update(vec![index], ControllerUpdate::Add(principal(1)), index);
+        assert_eq!(controllers, Ok(vec![index, principal(1)]));
+
+        assert!(is_invalid(Controllers::apply_update(
+            vec![index, principal(1)],
+            ControllerUpdate::Add(principal(1)),
+            index,
+        )));
+    }
+
+    #[test]
+    fn remove_keeps_the_index() {
+        let index = principal(0);
+        let controllers = Controllers::apply_update(
+            vec![index, principal(1)],
+            ControllerUpdate::Remove(principal(1)),
+            index,
+        );
+        assert_eq!(controllers, Ok(vec![index]));
+
+        assert!(is_invalid(Controllers::apply_update(
+            vec![index, principal(1)],
+            ControllerUpdate::Remove(index),
+            index,
+        )));
+        assert!(is_invalid(Controllers::apply_update(
+            vec![index],
+            ControllerUpdate::Remove(principal(1)),
+            index,
+        )));
+    }
+
+    #[test]
+    fn handover_replaces_every_controller() {
+        let index = principal(0);
+        let controllers = Controllers::apply_update(
+            vec![index, principal(1)],
+            ControllerUpdate::HandOver(vec![principal(2), principal(3), principal(2)]),
+            index,
+        );
+        assert_eq!(controllers, Ok(vec![principal(2), principal(3)]));
+
+        assert!(is_invalid(Controllers::apply_update(
+            vec![index],
+            ControllerUpdate::HandOver(vec![]),
+            index,
+        )));
+        assert!(is_invalid(Controllers::apply_update(
+            vec![index],
+            ControllerUpdate::HandOver(vec![principal(2), index]),
+            index,
+        )));
+    }
+
+    #[test]
+    fn controllers_are_limited() {
+        let index = principal(0);
+        let full: Vec<Principal> = (0..MAX_CONTROLLERS as u8).map(principal).collect();
+
+        assert!(is_invalid(Controllers::apply_update(
+            full,
+            ControllerUpdate::Add(principal(100)),
+            index,
+        )));
+        assert!(is_invalid(Controllers::apply_update(
+            vec![index],
+            ControllerUpdate::HandOver((1..=MAX_CONTROLLERS as u8 + 1).map(principal).collect()),
+            index,
+        )));
+    }
+}
@@ -0,0 +1,89 @@
+use candid::Nat;
+use ic_cdk::api::{canister_balance128, time};
+
+use crate::rust_declarations::{
+    stored::Stored,
+    types::{CycleTotals, CyclesReport, MultisigIndexError},
+};
+
+use super::store::CYCLE_TOTALS;
+
+// Accounting of the cycles of this canister. What comes in and what goes out is recorded where the cycles move,
+// the overhead that is burned in between is the part of the balance that none of the totals explain
+pub struct Cycles;
+
+impl Cycles {
+    // Called on install and upgrade, the totals start from the balance at the first call
+    pub fn open() {
+        Self::update(|totals| {
+            if totals.opened_at == 0 {
+                totals.opening_balance = Nat::from(canister_balance128());
+                totals.opened_at = time();
+            }
+        });
+    }
+
+    pub fn record_minted(cycles: &Nat) {
+        Self::update(|totals| totals.minted += cycles.clone());
+    }
+
+    pub fn record_received(cycles: u128) {
+        Self::update(|totals| totals.received += Nat::from(cycles));
+    }
+
+    pub fn record_attached(cycles: &Nat) {
+        Self::update(|totals| {
+            totals.attached += cycles.clone();
+            totals.created_canisters += 1;
+        });
+    }
+
+    pub fn record_deposited(cycles: u128) {
+        Self::update(|totals| totals.deposited += Nat::from(cycles));
+    }
+
+    pub fn get_report() -> Result<CyclesReport, MultisigIndexError> {
+        let totals = match CYCLE_TOTALS.with(|c| c.borrow().get().clone()) {
+            Stored::Valid(totals) => totals,
+            Stored::Corrupt { error, .. } => return Err(MultisigIndexError::CorruptRecord(error)),
+        };
+        let balance = Nat::from(canister_balance128());
+
+        let recovered = totals.minted.clone() + totals.received.clone();
+        let came_in = totals.opening_balance.clone() + recovered.clone();
+        let went_out = totals.attached.clone() + totals.deposited.clone() + balance.clone();
+        // Cycles that arrive outside of the recorded flows, like a deposit by a controller, lower the overhead
+        let overhead = if came_in > went_out {
+            came_in - went_out
+        } else {
+            Nat::from(0)
+        };
+        let spent = totals.attached.clone() + totals.deposited.clone() + overhead.clone();
+
+        Ok(CyclesReport {
+            balance,
+            opening_balance: totals.opening_balance,
+            opened_at: totals.opened_at,
+            minted: totals.minted,
+            received: totals.received,
+            attached: totals.attached,
+            deposited: totals.deposited,
+            overhead,
+            created_canisters: totals.created_canisters,
+            spending_exceeds_recovery: spent > recovered,
+        })
+    }
+
+    // A corrupt record is left as it is, the report returns its error
+    fn update(f: impl FnOnce(&mut CycleTotals)) {
+        CYCLE_TOTALS.with(|c| {
+            let mut cell = c.borrow_mut();
+            if let Stored::Valid(mut totals) = cell.get().clone() {
+                f(&mut totals);
+                totals.updated_at = time();
+                cell.set(totals.into())
+                    .expect("Failed to store the cycle totals");
+            }
+        });
+    }
+}
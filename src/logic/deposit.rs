@@ -0,0 +1,120 @@
+use candid::Principal;
+use ic_cdk::{api::time, id};
+use ic_ledger_types::{
+    account_balance, AccountBalanceArgs, AccountIdentifier, Subaccount, Tokens, TransferArgs,
+    DEFAULT_SUBACCOUNT,
+};
+
+use crate::rust_declarations::types::{
+    DepositAccount, Event, EventKind, JournalAccount, JournalEntryKind, MultisigIndexError,
+    TransactionData, TransactionStatus,
+};
+
+use super::{
+    config::Config,
+    events::Events,
+    journal::Journal,
+    lock::PrincipalLock,
+    store::{Store, ICP_TRANSACTION_FEE},
+    transfers::Transfers,
+};
+
+pub struct Deposit;
+
+impl Deposit {
+    pub fn get_deposit_account(principal: Principal) -> DepositAccount {
+        let subaccount = Subaccount::from(principal);
+        DepositAccount {
+            owner: id(),
+            subaccount,
+            account_identifier: AccountIdentifier::new(&id(), &subaccount).to_hex(),
+        }
+    }
+
+    // Move the balance of the deposit subaccount of the principal to the default account of this canister
+    // and add it to the local balance. The sweep block is stored as a validated transaction, so it can be
+    // passed to `spawn_multisig` like a block transferred by the principal
+    pub async fn notify_deposit(
+        principal: Principal,
+    ) -> Result<TransactionData, MultisigIndexError> {
+        let _principal_lock = PrincipalLock::acquire(principal)?;
+
+        // A sweep with an unknown outcome is finished first, when it went through the deposit is no longer
+        // in the subaccount
+        if let Some(pending) =
+            Transfers::get_pending_transfers(&Transfers::sweep_prefix(&principal))
+                .into_iter()
+                .next()
+        {
+            let amount = pending.args.amount;
+            let icp_block_index = Transfers::transfer(pending.id, pending.args).await?;
+            return Self::record_deposit(principal, amount, icp_block_index);
+        }
+
+        let subaccount = Subaccount::from(principal);
+        let balance = account_balance(
+            Config::ledger_canister_id(),
+            AccountBalanceArgs {
+                account: AccountIdentifier::new(&id(), &subaccount),
+            },
+        )
+        .await?;
+
+        if balance <= ICP_TRANSACTION_FEE {
+            return Err(MultisigIndexError::NoBalance);
+        }
+
+        // The ledger fee of the sweep is paid from the deposit
+        let amount = balance - ICP_TRANSACTION_FEE;
+        let transfer_id = Transfers::sweep_id(&principal, time());
+        let sweep_ledger_args = TransferArgs {
+            memo: Transfers::memo(&transfer_id),
+            amount,
+            fee: ICP_TRANSACTION_FEE,
+            from_subaccount: Some(subaccount),
+            to: AccountIdentifier::new(&id(), &DEFAULT_SUBACCOUNT),
+            created_at_time: None,
+        };
+        let icp_block_index = Transfers::transfer(transfer_id, sweep_ledger_args).await?;
+
+        Self::record_deposit(principal, amount, icp_block_index)
+    }
+
+    pub fn record_deposit(
+        principal: Principal,
+        amount: Tokens,
+        icp_block_index: u64,
+    ) -> Result<TransactionData, MultisigIndexError> {
+        Journal::record(
+            JournalEntryKind::Deposit,
+            JournalAccount::Ledger,
+            JournalAccount::Principal(principal),
+            amount,
+            Some(icp_block_index),
+        )?;
+
+        let transaction_data = TransactionData {
+            icp_transfer_block_index: icp_block_index,
+            cmc_transfer_block_index: None,
+            icp_amount: Some(amount),
+            cycles_amount: None,
+            initialized_by: principal,
+            created_at: time(),
+            status: TransactionStatus::Reserved,
+            error_message: None,
+            attempts: None,
+            last_attempt_at: None,
+            catalyze_fee: None,
+            creation: None,
+        };
+        Store::insert_transaction_data(icp_block_index, transaction_data.clone());
+        Events::record(Event {
+            principal: Some(principal),
+            icp_block_index: Some(icp_block_index),
+            amount: Some(amount),
+            ..Event::new(EventKind::Deposit)
+        });
+
+        Ok(transaction_data)
+    }
+}
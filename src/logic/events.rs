@@ -0,0 +1,115 @@
+use ic_cdk::api::time;
+
+use crate::rust_declarations::{
+    stored::Stored,
+    types::{Event, EventFilter, EventKind, EventsPage},
+};
+
+use super::{
+    pagination::{DEFAULT_PAGE_LIMIT, MAX_PAGE_LIMIT},
+    store::EVENTS,
+};
+
+// Events read by a single call, so a filter that matches few events stays within the instruction limit
+pub static MAX_SCANNED_EVENTS: u64 = 10_000;
+
+impl Event {
+    // The id and the time are set when the event is recorded
+    pub fn new(kind: EventKind) -> Self {
+        Event {
+            id: 0,
+            kind,
+            principal: None,
+            group_identifier: None,
+            canister_id: None,
+            icp_block_index: None,
+            block_index: None,
+            amount: None,
+            cycles: None,
+            error_message: None,
+            created_at: 0,
+        }
+    }
+}
+
+// Append-only log of what happened, events are never changed or removed. The id of an event is its position
+// in the log
+pub struct Events;
+
+impl Events {
+    pub fn record(mut event: Event) {
+        EVENTS.with(|e| {
+            let log = e.borrow();
+            event.id = log.len();
+            event.created_at = time();
+            log.append(&event.into())
+                .expect("Failed to append to the event log");
+        });
+    }
+
+    // Events from the given id in the order they happened
+    pub fn get_events(
+        from: Option<u64>,
+        limit: Option<u64>,
+        filter: Option<EventFilter>,
+    ) -> EventsPage {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT) as usize;
+        let filter = filter.unwrap_or_default();
+
+        EVENTS.with(|e| {
+            let log = e.borrow();
+            let total = log.len();
+            let start = from.unwrap_or_default();
+            let end = total.min(start.saturating_add(MAX_SCANNED_EVENTS));
+
+            let mut events = vec![];
+            let mut next = None;
+            for id in start..end {
+                if events.len() == limit {
+                    next = Some(id);
+                    break;
+                }
+                // Events that can not be decoded are skipped
+                if let Some(event) = log.get(id).and_then(Stored::valid) {
+                    if Self::matches(&filter, &event) {
+                        events.push(event);
+                    }
+                }
+            }
+            if next.is_none() && end < total {
+                next = Some(end);
+            }
+
+            EventsPage {
+                events,
+                next,
+                total,
+            }
+        })
+    }
+
+    fn matches(filter: &EventFilter, event: &Event) -> bool {
+        filter
+            .kinds
+            .as_ref()
+            .is_none_or(|kinds| kinds.contains(&event.kind))
+            && filter
+                .principal
+                .is_none_or(|principal| event.principal == Some(principal))
+            && filter
+                .group_identifier
+                .is_none_or(|group| event.group_identifier == Some(group))
+            && filter
+                .canister_id
+                .is_none_or(|canister_id| event.canister_id == Some(canister_id))
+            && filter
+                .icp_block_index
+                .is_none_or(|block| event.icp_block_index == Some(block))
+            && filter
+                .created_after
+                .is_none_or(|after| event.created_at >= after)
+            && filter
+                .created_before
+                .is_none_or(|before| event.created_at < before)
+    }
+}
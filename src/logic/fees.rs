@@ -0,0 +1,294 @@
+use std::{cell::RefCell, collections::BTreeMap, time::Duration};
+
+use ic_cdk::api::time;
+use ic_cdk_timers::set_timer_interval;
+use ic_ledger_types::{AccountIdentifier, Tokens, TransferArgs, DEFAULT_SUBACCOUNT};
+
+use crate::rust_declarations::{
+    stored::Stored,
+    types::{
+        Event, EventKind, FeePayout, FeePayoutStatus, FeePayoutsPage, GetFeePayoutsArgs,
+        JournalAccount, JournalEntryKind, MultisigIndexError,
+    },
+};
+
+use super::{
+    config::Config,
+    events::Events,
+    journal::Journal,
+    pagination::Pagination,
+    store::{FEE_PAYOUTS, ICP_TRANSACTION_FEE},
+    transfers::Transfers,
+};
+
+pub static FEE_PAYOUT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+thread_local! {
+    static IS_PAYING_OUT: RefCell<bool> = const { RefCell::new(false) };
+}
+
+// Resets the running flag when the payout run finishes or its future is dropped on a trap
+struct PayingOutFlag;
+
+impl Drop for PayingOutFlag {
+    fn drop(&mut self) {
+        IS_PAYING_OUT.with(|p| *p.borrow_mut() = false);
+    }
+}
+
+// The Catalyze fee of every completed spawn is recorded as a payout that stays pending until a transfer to the
+// fee recipient went through. Without batching the fee is sent when the spawn completes, with batching the
+// pending payouts are sent together by the timer
+pub struct Fees;
+
+impl Fees {
+    pub fn start_timer() {
+        set_timer_interval(FEE_PAYOUT_INTERVAL, || {
+            ic_cdk::spawn(async {
+                Self::pay_out_fees().await;
+            })
+        });
+    }
+
+    pub fn get_payout(icp_block_index: u64) -> Option<FeePayout> {
+        FEE_PAYOUTS
+            .with(|f| f.borrow().get(&icp_block_index))
+            .and_then(Stored::valid)
+    }
+
+    pub fn get_payouts_page(args: GetFeePayoutsArgs) -> FeePayoutsPage {
+        let pending_only = args.pending_only.unwrap_or_default();
+        let page = FEE_PAYOUTS.with(|f| {
+            Pagination::get_page(
+                &f.borrow(),
+                args.start,
+                args.limit,
+                args.direction,
+                |payout| !pending_only || payout.status == FeePayoutStatus::Pending,
+            )
+        });
+
+        let (owed, paid) = FEE_PAYOUTS.with(|f| {
+            f.borrow()
+                .iter()
+                .filter_map(|(_, payout)| payout.valid())
+                .fold(
+                    (Tokens::from_e8s(0), Tokens::from_e8s(0)),
+                    |(owed, paid), payout| match payout.status {
+                        FeePayoutStatus::Pending => (owed + payout.amount, paid),
+                        FeePayoutStatus::Paid(_) => (owed, paid + payout.amount),
+                    },
+                )
+        });
+
+        FeePayoutsPage {
+            payouts: page.items,
+            next: page.next,
+            total: page.total,
+            owed,
+            paid,
+        }
+    }
+
+    // Called by the last step of a spawn, the fee was moved to the fee recipient account when the spawn was charged
+    pub async fn record_payout(icp_block_index: u64, amount: Tokens) {
+        if amount == Tokens::from_e8s(0) {
+            return;
+        }
+
+        let payout = match Self::get_payout(icp_block_index) {
+            Some(payout) => payout,
+            None => {
+                let payout = FeePayout {
+                    icp_block_index,
+                    amount,
+                    status: FeePayoutStatus::Pending,
+                    transfer_id: None,
+                    recipient: None,
+                    attempts: 0,
+                    error_message: None,
+                    created_at: time(),
+                    updated_at: time(),
+                };
+                Self::insert_payout(payout.clone());
+                payout
+            }
+        };
+
+        // A payout that does not cover the ledger fee on its own is left for the timer, which sends it together
+        // with the other small payouts
+        if payout.status == FeePayoutStatus::Pending
+            && payout.transfer_id.is_none()
+            && payout.amount > ICP_TRANSACTION_FEE
+            && !Config::batch_fee_payouts()
+        {
+            // A payout that fails stays pending and is sent again by the timer
+            let _ = Self::send_new_payout(Transfers::fee_id(icp_block_index), vec![payout]).await;
+        }
+    }
+
+    // Send every pending payout, returns the payouts that were sent
+    pub async fn pay_out_fees() -> Vec<FeePayout> {
+        if IS_PAYING_OUT.with(|p| p.replace(true)) {
+            return vec![];
+        }
+        let _flag = PayingOutFlag;
+
+        let mut sent_transfers: BTreeMap<String, Vec<FeePayout>> = BTreeMap::new();
+        let mut unsent = vec![];
+        for payout in Self::get_pending_payouts() {
+            match payout.transfer_id.clone() {
+                Some(transfer_id) => sent_transfers.entry(transfer_id).or_default().push(payout),
+                None => unsent.push(payout),
+            }
+        }
+
+        let mut block_indexes = vec![];
+
+        // A transfer with an unknown outcome is sent again as it was, so the ledger recognizes a duplicate
+        for (transfer_id, payouts) in sent_transfers {
+            block_indexes.extend(payouts.iter().map(|payout| payout.icp_block_index));
+            match Transfers::get_transfer(&transfer_id) {
+                Some(transfer) => {
+                    let _ = Self::send_payout(transfer_id, transfer.args, payouts).await;
+                }
+                None => unsent.extend(payouts),
+            }
+        }
+
+        block_indexes.extend(unsent.iter().map(|payout| payout.icp_block_index));
+        if Config::batch_fee_payouts() {
+            if !unsent.is_empty() {
+                let _ = Self::send_new_payout(Transfers::fee_sweep_id(time()), unsent).await;
+            }
+        } else {
+            // Payouts that do not cover the ledger fee on their own are batched, they are sent once together
+            // they cover it
+            let (payouts, small_payouts): (Vec<FeePayout>, Vec<FeePayout>) = unsent
+                .into_iter()
+                .partition(|payout| payout.amount > ICP_TRANSACTION_FEE);
+            for payout in payouts {
+                let transfer_id = Transfers::fee_id(payout.icp_block_index);
+                let _ = Self::send_new_payout(transfer_id, vec![payout]).await;
+            }
+            if !small_payouts.is_empty() {
+                let _ = Self::send_new_payout(Transfers::fee_sweep_id(time()), small_payouts).await;
+            }
+        }
+
+        block_indexes.sort();
+        block_indexes.dedup();
+        block_indexes
+            .into_iter()
+            .filter_map(Self::get_payout)
+            .collect()
+    }
+
+    // Create one transfer that pays all the payouts, the ledger fee is paid from the payouts
+    async fn send_new_payout(
+        transfer_id: String,
+        payouts: Vec<FeePayout>,
+    ) -> Result<u64, MultisigIndexError> {
+        let amount = payouts
+            .iter()
+            .fold(Tokens::from_e8s(0), |total, payout| total + payout.amount);
+        // Left pending until there is enough to cover the ledger fee
+        if amount <= ICP_TRANSACTION_FEE {
+            return Err(MultisigIndexError::InsufficientIcp {
+                required: ICP_TRANSACTION_FEE,
+                available: amount,
+            });
+        }
+
+        let fee_recipient = Config::get().fee_recipient;
+        let args = TransferArgs {
+            memo: Transfers::memo(&transfer_id),
+            amount: amount - ICP_TRANSACTION_FEE,
+            fee: ICP_TRANSACTION_FEE,
+            from_subaccount: None,
+            to: AccountIdentifier::new(&fee_recipient, &DEFAULT_SUBACCOUNT),
+            created_at_time: None,
+        };
+
+        // Linked before the call, so a run that starts while the transfer is ongoing sends the same transfer
+        let payouts: Vec<FeePayout> = payouts
+            .into_iter()
+            .map(|mut payout| {
+                payout.transfer_id = Some(transfer_id.clone());
+                payout.recipient = Some(fee_recipient);
+                Self::insert_payout(payout.clone());
+                payout
+            })
+            .collect();
+
+        Self::send_payout(transfer_id, args, payouts).await
+    }
+
+    async fn send_payout(
+        transfer_id: String,
+        args: TransferArgs,
+        payouts: Vec<FeePayout>,
+    ) -> Result<u64, MultisigIndexError> {
+        let result = Transfers::transfer(transfer_id.clone(), args).await;
+        let is_unresolved = Transfers::is_unresolved(&transfer_id);
+
+        for mut payout in payouts {
+            payout.attempts += 1;
+            match &result {
+                Ok(block_index) => {
+                    payout.status = FeePayoutStatus::Paid(*block_index);
+                    payout.error_message = None;
+                    // Neither account has a local balance, so this never fails
+                    let _ = Journal::record(
+                        JournalEntryKind::FeePayout,
+                        JournalAccount::FeeRecipient,
+                        JournalAccount::Ledger,
+                        payout.amount,
+                        Some(payout.icp_block_index),
+                    );
+                    Events::record(Event {
+                        icp_block_index: Some(payout.icp_block_index),
+                        block_index: Some(*block_index),
+                        amount: Some(payout.amount),
+                        ..Event::new(EventKind::FeePaid)
+                    });
+                }
+                Err(err) => {
+                    payout.error_message = Some(err.to_string());
+                    // Rejected by the ledger, the next run creates a new transfer
+                    if !is_unresolved {
+                        payout.transfer_id = None;
+                    }
+                }
+            }
+            Self::insert_payout(payout);
+        }
+
+        result
+    }
+
+    // The transfer of the payouts did not go through, the next run sends them with a new transfer
+    pub fn unlink_transfer(transfer_id: &str) {
+        for mut payout in Self::get_pending_payouts() {
+            if payout.transfer_id.as_deref() == Some(transfer_id) {
+                payout.transfer_id = None;
+                Self::insert_payout(payout);
+            }
+        }
+    }
+
+    fn get_pending_payouts() -> Vec<FeePayout> {
+        FEE_PAYOUTS.with(|f| {
+            f.borrow()
+                .iter()
+                .filter_map(|(_, payout)| payout.valid())
+                .filter(|payout| payout.status == FeePayoutStatus::Pending)
+                .collect()
+        })
+    }
+
+    fn insert_payout(mut payout: FeePayout) {
+        payout.updated_at = time();
+        FEE_PAYOUTS.with(|f| f.borrow_mut().insert(payout.icp_block_index, payout.into()));
+    }
+}
@@ -0,0 +1,377 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use candid::Principal;
+use serde_json::{json, Value};
+
+use crate::rust_declarations::types::{
+    CanisterCreation, GetMultisigsArgs, GetTransactionsArgs, HttpRequest, HttpResponse,
+    InitializeStatus, MultisigData, SortDirection, TransactionData, TransactionStatus,
+};
+
+use super::store::{Store, CALLER_ICP_BALANCE, ENTRIES, INITIALIZING, TRANSACTIONS};
+
+const WASM_PAGE_SIZE: u64 = 64 * 1024;
+
+// Plain HTTP endpoints for monitoring, so the index can be scraped without a candid client. The responses are
+// not certified, they are served through the raw domain of the canister
+pub struct Http;
+
+impl Http {
+    pub fn handle(request: HttpRequest) -> HttpResponse {
+        if request.method != "GET" {
+            return Self::response(405, "text/plain", "Method not allowed".into());
+        }
+
+        let (path, query) = match request.url.split_once('?') {
+            Some((path, query)) => (path, query),
+            None => (request.url.as_str(), ""),
+        };
+        let params = Self::parse_query(query);
+
+        let result = match path {
+            "/metrics" => Ok(Self::response(
+                200,
+                "text/plain; version=0.0.4",
+                Self::metrics().into_bytes(),
+            )),
+            "/multisigs" => Self::multisigs(&params).map(Self::json_response),
+            "/transactions" => Self::transactions(&params).map(Self::json_response),
+            _ => Ok(Self::response(404, "text/plain", "Not found".into())),
+        };
+
+        result.unwrap_or_else(|err| Self::response(400, "text/plain", err.into_bytes()))
+    }
+
+    fn metrics() -> String {
+        let mut metrics = String::new();
+
+        Self::write_metric(
+            &mut metrics,
+            "multisig_index_multisigs",
+            "Number of registered multisigs",
+            &[(String::new(), ENTRIES.with(|e| e.borrow().len()))],
+        );
+
+        // Every status is listed, so a status that drops to zero is still scraped
+        let mut transactions: BTreeMap<String, u64> = [
+            TransactionStatus::IcpToIndexFailed,
+            TransactionStatus::IcpToCmcFailed,
+            TransactionStatus::CyclesToIndexFailed,
+            TransactionStatus::InsufficientIcp,
+            TransactionStatus::Success,
+            TransactionStatus::Pending,
+            TransactionStatus::RecoveryFailed,
+            TransactionStatus::Reserved,
+        ]
+        .iter()
+        .map(|status| (format!("{:?}", status), 0))
+        .collect();
+        TRANSACTIONS.with(|t| {
+            for transaction in t.borrow().iter().filter_map(|(_, t)| t.valid()) {
+                *transactions
+                    .entry(format!("{:?}", transaction.status))
+                    .or_default() += 1;
+            }
+        });
+        Self::write_metric(
+            &mut metrics,
+            "multisig_index_transactions",
+            "Number of spin up transactions by status",
+            &transactions
+                .into_iter()
+                .map(|(status, count)| (format!("status=\"{}\"", status), count))
+                .collect::<Vec<_>>(),
+        );
+
+        let (accounts, balance) = CALLER_ICP_BALANCE.with(|c| {
+            c.borrow()
+                .iter()
+                .filter(|(_, balance)| *balance > 0)
+                .fold((0, 0), |(accounts, total), (_, balance)| {
+                    (accounts + 1, total + balance)
+                })
+        });
+        Self::write_metric(
+            &mut metrics,
+            "multisig_index_local_icp_balance_e8s",
+            "Sum of the local ICP balances of all principals",
+            &[(String::new(), balance)],
+        );
+        Self::write_metric(
+            &mut metrics,
+            "multisig_index_local_icp_balance_accounts",
+            "Number of principals with a local ICP balance",
+            &[(String::new(), accounts)],
+        );
+
+        let mut spawns: BTreeMap<&str, u64> = ["in_progress", "failed", "done"]
+            .iter()
+            .map(|status| (*status, 0))
+            .collect();
+        INITIALIZING.with(|i| {
+            for status in i.borrow().iter().filter_map(|(_, status)| status.valid()) {
+                let label = match status {
+                    InitializeStatus::Initializing => "in_progress",
+                    InitializeStatus::Error => "failed",
+                    InitializeStatus::Done => "done",
+                };
+                *spawns.entry(label).or_default() += 1;
+            }
+        });
+        Self::write_metric(
+            &mut metrics,
+            "multisig_index_spawns",
+            "Number of spawns by status",
+            &spawns
+                .into_iter()
+                .map(|(status, count)| (format!("status=\"{}\"", status), count))
+                .collect::<Vec<_>>(),
+        );
+
+        Self::write_metric(
+            &mut metrics,
+            "multisig_index_cycles",
+            "Cycles balance of the index",
+            &[(String::new(), Store::get_cycles())],
+        );
+        Self::write_metric(
+            &mut metrics,
+            "multisig_index_stable_memory_bytes",
+            "Size of the stable memory of the index",
+            &[(
+                String::new(),
+                ic_cdk::api::stable::stable64_size() * WASM_PAGE_SIZE,
+            )],
+        );
+
+        metrics
+    }
+
+    // Every metric is a gauge, the samples are the labels without braces and the value
+    fn write_metric(metrics: &mut String, name: &str, help: &str, samples: &[(String, u64)]) {
+        metrics.push_str(&format!(
+            "# HELP {} {}\n# TYPE {} gauge\n",
+            name, help, name
+        ));
+        for (labels, value) in samples {
+            if labels.is_empty() {
+                metrics.push_str(&format!("{} {}\n", name, value));
+            } else {
+                metrics.push_str(&format!("{}{{{}}} {}\n", name, labels, value));
+            }
+        }
+    }
+
+    fn multisigs(params: &BTreeMap<String, String>) -> Result<Value, String> {
+        let page = Store::get_multisigs(GetMultisigsArgs {
+            start: Self::param(params, "start")?,
+            limit: Self::param(params, "limit")?,
+            direction: Self::direction(params)?,
+            created_by: Self::param(params, "created_by")?,
+            ..Default::default()
+        });
+
+        Ok(json!({
+            "multisigs": page.multisigs.iter().map(Self::multisig_json).collect::<Vec<_>>(),
+            "next": page.next.map(|next| next.to_string()),
+            "total": page.total,
+        }))
+    }
+
+    fn transactions(params: &BTreeMap<String, String>) -> Result<Value, String> {
+        let page = Store::get_transactions_page(GetTransactionsArgs {
+            start: Self::param(params, "start")?,
+            limit: Self::param(params, "limit")?,
+            direction: Self::direction(params)?,
+            initialized_by: Self::param(params, "initialized_by")?,
+            ..Default::default()
+        });
+
+        Ok(json!({
+            "transactions": page.transactions.iter().map(Self::transaction_json).collect::<Vec<_>>(),
+            "next": page.next,
+            "total": page.total,
+        }))
+    }
+
+    fn multisig_json(multisig: &MultisigData) -> Value {
+        json!({
+            "canister_id": multisig.canister_id.to_string(),
+            "group_identifier": multisig.group_identifier.map(|group| group.to_string()),
+            "created_by": multisig.created_by.to_string(),
+            "created_at": multisig.created_at,
+            "updated_at": multisig.updated_at,
+            "wasm_version": multisig.wasm_version,
+            "wasm_hash": multisig.wasm_hash,
+            "controllers": multisig.controllers.as_ref().map(|controllers| {
+                controllers.iter().map(Principal::to_string).collect::<Vec<_>>()
+            }),
+        })
+    }
+
+    // Cycles can be larger as a JSON number can safely hold, so they are passed as a string
+    fn transaction_json(transaction: &TransactionData) -> Value {
+        let (creation, subnet_type) = match &transaction.creation {
+            Some(CanisterCreation::TopUp) => (Some("TopUp"), None),
+            Some(CanisterCreation::Cmc { subnet_type }) => (Some("Cmc"), subnet_type.clone()),
+            None => (None, None),
+        };
+
+        json!({
+            "icp_transfer_block_index": transaction.icp_transfer_block_index,
+            "cmc_transfer_block_index": transaction.cmc_transfer_block_index,
+            "icp_amount_e8s": transaction.icp_amount.map(|amount| amount.e8s()),
+            "cycles_amount": transaction.cycles_amount.as_ref().map(|cycles| cycles.0.to_string()),
+            "initialized_by": transaction.initialized_by.to_string(),
+            "created_at": transaction.created_at,
+            "status": format!("{:?}", transaction.status),
+            "error_message": transaction.error_message,
+            "attempts": transaction.attempts,
+            "last_attempt_at": transaction.last_attempt_at,
+            "catalyze_fee_e8s": transaction.catalyze_fee.map(|fee| fee.e8s()),
+            "creation": creation,
+            "subnet_type": subnet_type,
+        })
+    }
+
+    fn parse_query(query: &str) -> BTreeMap<String, String> {
+        query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| match pair.split_once('=') {
+                Some((key, value)) => (key.to_string(), value.to_string()),
+                None => (pair.to_string(), String::new()),
+            })
+            .collect()
+    }
+
+    fn param<T>(params: &BTreeMap<String, String>, name: &str) -> Result<Option<T>, String>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        params
+            .get(name)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|err| format!("Invalid {}: {}", name, err))
+            })
+            .transpose()
+    }
+
+    fn direction(params: &BTreeMap<String, String>) -> Result<Option<SortDirection>, String> {
+        match params.get("direction").map(String::as_str) {
+            None => Ok(None),
+            Some("asc") => Ok(Some(SortDirection::Asc)),
+            Some("desc") => Ok(Some(SortDirection::Desc)),
+            Some(direction) => Err(format!("Invalid direction: {}", direction)),
+        }
+    }
+
+    fn json_response(value: Value) -> HttpResponse {
+        Self::response(200, "application/json", value.to_string().into_bytes())
+    }
+
+    fn response(status_code: u16, content_type: &str, body: Vec<u8>) -> HttpResponse {
+        HttpResponse {
+            status_code,
+            headers: vec![
+                ("Content-Type".to_string(), content_type.to_string()),
+                ("Content-Length".to_string(), body.len().to_string()),
+            ],
+            body,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get(url: &str) -> HttpRequest {
+        HttpRequest {
+            method: "GET".to_string(),
+            url: url.to_string(),
+            headers: vec![],
+            body: vec![],
+            certificate_version: None,
+        }
+    }
+
+    #[test]
+    fn parse_query_splits_pairs() {
+        let params = Http::parse_query("start=5&&desc&filter=a=b");
+
+        assert_eq!(params.len(), 3);
+        assert_eq!(params["start"], "5");
+        assert_eq!(params["desc"], "");
+        assert_eq!(params["filter"], "a=b");
+        assert!(Http::parse_query("").is_empty());
+    }
+
+    #[test]
+    fn params_are_parsed_or_rejected() {
+        let params = Http::parse_query("limit=10&start=abc&direction=up");
+
+        assert_eq!(Http::param::<u64>(&params, "limit"), Ok(Some(10)));
+        assert_eq!(Http::param::<u64>(&params, "missing"), Ok(None));
+        assert!(Http::param::<u64>(&params, "start").is_err());
+        assert_eq!(
+            Http::direction(&params),
+            Err("Invalid direction: up".to_string())
+        );
+        assert_eq!(
+            Http::direction(&Http::parse_query("direction=desc")),
+            Ok(Some(SortDirection::Desc))
+        );
+    }
+
+    #[test]
+    fn write_metric_writes_help_type_and_samples() {
+        let mut metrics = String::new();
+        Http::write_metric(
+            &mut metrics,
+            "index_spawns",
+            "Number of spawns",
+            &[
+                ("status=\"done\"".to_string(), 2),
+                ("status=\"failed\"".to_string(), 0),
+            ],
+        );
+        Http::write_metric(
+            &mut metrics,
+            "index_cycles",
+            "Cycles",
+            &[(String::new(), 5)],
+        );
+
+        assert_eq!(
+            metrics,
+            "# HELP index_spawns Number of spawns\n\
+             # TYPE index_spawns gauge\n\
+             index_spawns{status=\"done\"} 2\n\
+             index_spawns{status=\"failed\"} 0\n\
+             # HELP index_cycles Cycles\n\
+             # TYPE index_cycles gauge\n\
+             index_cycles 5\n"
+        );
+    }
+
+    #[test]
+    fn unknown_requests_are_rejected() {
+        let mut post = get("/metrics");
+        post.method = "POST".to_string();
+        assert_eq!(Http::handle(post).status_code, 405);
+
+        let not_found = Http::handle(get("/unknown?limit=1"));
+        assert_eq!(not_found.status_code, 404);
+        assert!(not_found.headers.contains(&(
+            "Content-Length".to_string(),
+            not_found.body.len().to_string()
+        )));
+
+        let bad_request = Http::handle(get("/multisigs?limit=abc"));
+        assert_eq!(bad_request.status_code, 400);
+    }
+}
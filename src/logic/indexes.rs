@@ -0,0 +1,168 @@
+use candid::Principal;
+
+use crate::rust_declarations::{
+    stored::Stored,
+    types::{MultisigData, TransactionData},
+};
+
+use super::store::{
+    CREATOR_INDEX, ENTRIES, GROUP_INDEX, INDEXES_BACKFILLED, INITIALIZER_INDEX, TRANSACTIONS,
+};
+
+// Secondary indexes on the multisigs and transactions, they are only changed together with the primary map
+// in the same message so they can not drift apart.
+// The creator and initializer indexes use `<principal>:<primary key>` keys, so all keys of a principal are a range
+pub struct Indexes;
+
+impl Indexes {
+    pub fn insert_multisig(multisig: MultisigData) {
+        let key = multisig.canister_id.to_string();
+        // The keys of a previous record that could not be decoded are left, lookups read the primary map
+        // again so they never return outdated data
+        if let Some(Stored::Valid(previous)) =
+            ENTRIES.with(|e| e.borrow_mut().insert(key, multisig.clone().into()))
+        {
+            Self::remove_multisig_keys(&previous);
+        }
+        Self::insert_multisig_keys(&multisig);
+    }
+
+    pub fn insert_transaction(icp_block_index: u64, transaction: TransactionData) {
+        if let Some(Stored::Valid(previous)) = TRANSACTIONS.with(|t| {
+            t.borrow_mut()
+                .insert(icp_block_index, transaction.clone().into())
+        }) {
+            Self::remove_transaction_key(&previous);
+        }
+        Self::insert_transaction_key(&transaction);
+    }
+
+    pub fn remove_transaction(icp_block_index: u64) {
+        if let Some(Stored::Valid(previous)) =
+            TRANSACTIONS.with(|t| t.borrow_mut().remove(&icp_block_index))
+        {
+            Self::remove_transaction_key(&previous);
+        }
+    }
+
+    pub fn get_canister_by_group_identifier(group_identifier: Principal) -> Option<Principal> {
+        GROUP_INDEX
+            .with(|g| g.borrow().get(&group_identifier.to_string()))
+            .and_then(|canister_id| Principal::from_text(canister_id).ok())
+    }
+
+    pub fn get_canisters_by_creator(created_by: Principal) -> Vec<Principal> {
+        let prefix = Self::prefix(&created_by);
+        CREATOR_INDEX.with(|c| {
+            c.borrow()
+                .range(prefix.clone()..)
+                .take_while(|(key, _)| key.starts_with(&prefix))
+                .filter_map(|(_, canister_id)| Principal::from_text(canister_id).ok())
+                .collect()
+        })
+    }
+
+    pub fn get_blocks_by_initializer(initialized_by: Principal) -> Vec<u64> {
+        let prefix = Self::prefix(&initialized_by);
+        INITIALIZER_INDEX.with(|i| {
+            i.borrow()
+                .range(prefix.clone()..)
+                .take_while(|(key, _)| key.starts_with(&prefix))
+                .map(|(_, block_index)| block_index)
+                .collect()
+        })
+    }
+
+    // Fill the indexes from the primary maps once, for data that was stored before the indexes existed
+    pub fn backfill() {
+        if INDEXES_BACKFILLED.with(|b| *b.borrow().get()) == 1 {
+            return;
+        }
+
+        ENTRIES.with(|e| {
+            for multisig in e
+                .borrow()
+                .iter()
+                .filter_map(|(_, multisig)| multisig.valid())
+            {
+                Self::insert_multisig_keys(&multisig);
+            }
+        });
+        TRANSACTIONS.with(|t| {
+            for transaction in t
+                .borrow()
+                .iter()
+                .filter_map(|(_, transaction)| transaction.valid())
+            {
+                Self::insert_transaction_key(&transaction);
+            }
+        });
+
+        INDEXES_BACKFILLED
+            .with(|b| b.borrow_mut().set(1))
+            .expect("Failed to store the backfill flag");
+    }
+
+    fn insert_multisig_keys(multisig: &MultisigData) {
+        let canister_id = multisig.canister_id.to_string();
+        if let Some(group_identifier) = multisig.group_identifier {
+            GROUP_INDEX.with(|g| {
+                g.borrow_mut()
+                    .insert(group_identifier.to_string(), canister_id.clone())
+            });
+        }
+        CREATOR_INDEX.with(|c| {
+            c.borrow_mut().insert(
+                Self::key(&multisig.created_by, &canister_id),
+                canister_id.clone(),
+            )
+        });
+    }
+
+    fn remove_multisig_keys(multisig: &MultisigData) {
+        let canister_id = multisig.canister_id.to_string();
+        if let Some(group_identifier) = multisig.group_identifier {
+            GROUP_INDEX.with(|g| {
+                let mut index = g.borrow_mut();
+                // Only remove the entry when it still points to this multisig
+                if index.get(&group_identifier.to_string()) == Some(canister_id.clone()) {
+                    index.remove(&group_identifier.to_string());
+                }
+            });
+        }
+        CREATOR_INDEX.with(|c| {
+            c.borrow_mut()
+                .remove(&Self::key(&multisig.created_by, &canister_id))
+        });
+    }
+
+    fn insert_transaction_key(transaction: &TransactionData) {
+        INITIALIZER_INDEX.with(|i| {
+            i.borrow_mut().insert(
+                Self::block_key(transaction),
+                transaction.icp_transfer_block_index,
+            )
+        });
+    }
+
+    fn remove_transaction_key(transaction: &TransactionData) {
+        INITIALIZER_INDEX.with(|i| i.borrow_mut().remove(&Self::block_key(transaction)));
+    }
+
+    fn block_key(transaction: &TransactionData) -> String {
+        // Padded so the keys of a principal are sorted by block index
+        Self::key(
+            &transaction.initialized_by,
+            &format!("{:020}", transaction.icp_transfer_block_index),
+        )
+    }
+
+    fn key(principal: &Principal, primary_key: &str) -> String {
+        format!("{}{}", Self::prefix(principal), primary_key)
+    }
+
+    // The text of a principal never contains a colon, so a prefix never matches another principal
+    fn prefix(principal: &Principal) -> String {
+        format!("{}:", principal)
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+
+use candid::Principal;
+use ic_cdk::api::time;
+use ic_ledger_types::Tokens;
+
+use crate::rust_declarations::types::{
+    BalanceMismatch, JournalAccount, JournalEntry, JournalEntryKind, MultisigIndexError,
+    UpdateIcpBalanceArgs,
+};
+
+use super::store::{Store, CALLER_ICP_BALANCE, JOURNAL};
+
+pub struct Journal;
+
+impl Journal {
+    // Move the amount between two accounts, a local balance is never allowed to go below zero.
+    // The journal entry and the local balances are updated in the same message so they cannot drift apart
+    pub fn record(
+        kind: JournalEntryKind,
+        from: JournalAccount,
+        to: JournalAccount,
+        amount: Tokens,
+        block_index: Option<u64>,
+    ) -> Result<JournalEntry, MultisigIndexError> {
+        if let JournalAccount::Principal(principal) = &from {
+            let available = Tokens::from_e8s(Store::get_caller_local_icp_balance(*principal));
+            if available < amount {
+                return Err(MultisigIndexError::InsufficientIcp {
+                    required: amount,
+                    available,
+                });
+            }
+            Self::update_balance(principal, UpdateIcpBalanceArgs::Subtract(amount));
+        }
+
+        if let JournalAccount::Principal(principal) = &to {
+            Self::update_balance(principal, UpdateIcpBalanceArgs::Add(amount));
+        }
+
+        Ok(Self::insert_entry(kind, from, to, amount, block_index))
+    }
+
+    pub fn get_balance_history(principal: Principal) -> Vec<JournalEntry> {
+        let account = JournalAccount::Principal(principal);
+        JOURNAL.with(|j| {
+            j.borrow()
+                .iter()
+                .filter_map(|(_, entry)| entry.valid())
+                .filter(|entry| entry.from == account || entry.to == account)
+                .collect()
+        })
+    }
+
+    // Compare every local balance with the balance that follows from the journal
+    pub fn get_balance_mismatches() -> Vec<BalanceMismatch> {
+        let journal_balances = Self::journal_balances();
+
+        let mut principals: Vec<Principal> = CALLER_ICP_BALANCE.with(|c| {
+            c.borrow()
+                .iter()
+                .filter_map(|(principal, _)| Principal::from_text(principal).ok())
+                .collect()
+        });
+        principals.extend(journal_balances.keys());
+        principals.sort();
+        principals.dedup();
+
+        principals
+            .into_iter()
+            .filter_map(|principal| {
+                let balance = Store::get_caller_local_icp_balance(principal);
+                let journal_balance = journal_balances
+                    .get(&principal)
+                    .copied()
+                    .unwrap_or_default();
+
+                if balance == journal_balance {
+                    None
+                } else {
+                    Some(BalanceMismatch {
+                        principal,
+                        balance,
+                        journal_balance,
+                    })
+                }
+            })
+            .collect()
+    }
+
+    // Balances that were stored before the journal existed get an opening entry, so they can be reconciled
+    pub fn open_balances() {
+        let journal_balances = Self::journal_balances();
+
+        let balances: Vec<(String, u64)> = CALLER_ICP_BALANCE.with(|c| c.borrow().iter().collect());
+
+        for (principal, balance) in balances {
+            let principal = match Principal::from_text(principal) {
+                Ok(principal) => principal,
+                Err(_) => continue,
+            };
+
+            if balance == 0 || journal_balances.contains_key(&principal) {
+                continue;
+            }
+
+            Self::insert_entry(
+                JournalEntryKind::OpeningBalance,
+                JournalAccount::Ledger,
+                JournalAccount::Principal(principal),
+                Tokens::from_e8s(balance),
+                None,
+            );
+        }
+    }
+
+    fn insert_entry(
+        kind: JournalEntryKind,
+        from: JournalAccount,
+        to: JournalAccount,
+        amount: Tokens,
+        block_index: Option<u64>,
+    ) -> JournalEntry {
+        JOURNAL.with(|j| {
+            let mut journal = j.borrow_mut();
+            let id = journal.last_key_value().map(|(id, _)| id + 1).unwrap_or(0);
+            let entry = JournalEntry {
+                id,
+                kind,
+                from,
+                to,
+                amount,
+                block_index,
+                created_at: time(),
+            };
+            journal.insert(id, entry.clone().into());
+            entry
+        })
+    }
+
+    fn journal_balances() -> BTreeMap<Principal, u64> {
+        let mut balances: BTreeMap<Principal, i128> = BTreeMap::new();
+
+        JOURNAL.with(|j| {
+            for entry in j.borrow().iter().filter_map(|(_, entry)| entry.valid()) {
+                if let JournalAccount::Principal(principal) = entry.from {
+                    *balances.entry(principal).or_default() -= entry.amount.e8s() as i128;
+                }
+                if let JournalAccount::Principal(principal) = entry.to {
+                    *balances.entry(principal).or_default() += entry.amount.e8s() as i128;
+                }
+            }
+        });
+
+        balances
+            .into_iter()
+            .map(|(principal, balance)| (principal, balance.max(0) as u64))
+            .collect()
+    }
+
+    fn update_balance(principal: &Principal, args: UpdateIcpBalanceArgs) {
+        CALLER_ICP_BALANCE.with(|c| {
+            let mut balance = c.borrow_mut();
+            let current_balance = balance.get(&principal.to_string()).unwrap_or(0);
+            let new_balance = match args {
+                UpdateIcpBalanceArgs::Add(amount) => current_balance.saturating_add(amount.e8s()),
+                UpdateIcpBalanceArgs::Subtract(amount) => {
+                    current_balance.saturating_sub(amount.e8s())
+                }
+            };
+            balance.insert(principal.to_string(), new_balance);
+        });
+    }
+}
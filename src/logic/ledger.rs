@@ -1,92 +1,171 @@
-use candid::Principal;
+use std::convert::TryFrom;
+
+use candid::{Nat, Principal};
 use ic_cdk::id;
 use ic_ledger_types::{
     query_archived_blocks, query_blocks, transfer, AccountIdentifier, Block, BlockIndex,
-    GetBlocksArgs, Tokens, TransferArgs, DEFAULT_SUBACCOUNT, MAINNET_LEDGER_CANISTER_ID,
+    GetBlocksArgs, Memo, Tokens, TransferArgs, DEFAULT_SUBACCOUNT,
+};
+
+use crate::rust_declarations::{
+    icp_ledger_service::{Account, IcpLedgerService, Result3, TransferFromArgs},
+    types::{Event, EventKind, MultisigIndexError, TransactionValidationError},
 };
 
+use super::{config::Config, events::Events};
+
 pub struct Ledger {}
 
 impl Ledger {
-    pub async fn transfer_icp(args: TransferArgs) -> Result<u64, String> {
-        match transfer(MAINNET_LEDGER_CANISTER_ID, args).await {
+    pub async fn transfer_icp(args: TransferArgs) -> Result<u64, MultisigIndexError> {
+        let amount = args.amount;
+        let result = match transfer(Config::ledger_canister_id(), args).await {
             Ok(result) => match result {
                 Ok(block_index) => Ok(block_index),
-                Err(err) => Err(err.to_string()),
+                Err(err) => Err(MultisigIndexError::LedgerTransfer(err)),
             },
-            Err((_, err)) => Err(err),
-        }
+            Err(err) => Err(err.into()),
+        };
+
+        Self::record_transfer(&result, None, amount);
+        result
+    }
+
+    // Pull the amount from the account of the principal into this canister, the principal needs to have
+    // approved this canister for the amount plus the ledger fee
+    pub async fn transfer_from(
+        principal: Principal,
+        amount: Tokens,
+        memo: Memo,
+        created_at_time: u64,
+    ) -> Result<u64, MultisigIndexError> {
+        let args = TransferFromArgs {
+            from: Account {
+                owner: principal,
+                subaccount: None,
+            },
+            to: Account {
+                owner: id(),
+                subaccount: None,
+            },
+            amount: Nat::from(amount.e8s()),
+            fee: None,
+            memo: Some(memo.0.to_be_bytes().to_vec()),
+            created_at_time: Some(created_at_time),
+            spender_subaccount: None,
+        };
+
+        let result = match IcpLedgerService(Config::ledger_canister_id())
+            .icrc2_transfer_from(args)
+            .await
+        {
+            Ok((Result3::Ok(block_index),)) => Ok(Self::nat_to_u64(block_index)),
+            Ok((Result3::Err(err),)) => Err(MultisigIndexError::LedgerTransferFrom(err)),
+            Err(err) => Err(err.into()),
+        };
+
+        Self::record_transfer(&result, Some(principal), amount);
+        result
+    }
+
+    fn record_transfer(
+        result: &Result<u64, MultisigIndexError>,
+        principal: Option<Principal>,
+        amount: Tokens,
+    ) {
+        let event = match result {
+            Ok(block_index) => Event {
+                block_index: Some(*block_index),
+                ..Event::new(EventKind::LedgerTransfer)
+            },
+            Err(err) => Event {
+                error_message: Some(err.to_string()),
+                ..Event::new(EventKind::LedgerTransferFailed)
+            },
+        };
+        Events::record(Event {
+            principal,
+            amount: Some(amount),
+            ..event
+        });
     }
 
     // This method checks if the transaction is send and received from the given principal
     pub async fn validate_transaction(
         principal: Principal,
         block_index: BlockIndex,
-    ) -> Result<Tokens, String> {
+    ) -> Result<Tokens, MultisigIndexError> {
         // Get the block
-        let block = Self::get_block(block_index).await;
-        match block {
-            Some(block) => {
-                // Check if the block has a transaction
-                if let Some(operation) = block.transaction.operation {
-                    if let ic_ledger_types::Operation::Transfer {
-                        from,
-                        to,
-                        amount,
-                        fee: _, // Ignore fee
-                    } = operation
-                    {
-                        if from != Self::principal_to_account_identifier(principal) {
-                            return Err("Transaction not from the given principal".to_string());
-                        }
-                        if to != Self::principal_to_account_identifier(id()) {
-                            return Err("Transaction not to the given principal".to_string());
-                        }
-                        return Ok(amount);
-                    } else {
-                        // Not a transfer
-                        return Err("Not a transfer".to_string());
-                    }
-                } else {
-                    // No operation
-                    return Err("No operation".to_string());
-                }
+        let block = Self::get_block(block_index).await?;
+
+        // Check if the block has a transaction
+        let operation =
+            block
+                .transaction
+                .operation
+                .ok_or(MultisigIndexError::InvalidTransaction(
+                    TransactionValidationError::NoOperation,
+                ))?;
+
+        if let ic_ledger_types::Operation::Transfer {
+            from,
+            to,
+            amount,
+            fee: _, // Ignore fee
+        } = operation
+        {
+            if from != Self::principal_to_account_identifier(principal) {
+                return Err(MultisigIndexError::InvalidTransaction(
+                    TransactionValidationError::InvalidSender,
+                ));
             }
-            // No block
-            None => return Err("No block".to_string()),
+            if to != Self::principal_to_account_identifier(id()) {
+                return Err(MultisigIndexError::InvalidTransaction(
+                    TransactionValidationError::InvalidReceiver,
+                ));
+            }
+            Ok(amount)
+        } else {
+            // Not a transfer
+            Err(MultisigIndexError::InvalidTransaction(
+                TransactionValidationError::NotATransfer,
+            ))
         }
     }
 
-    async fn get_block(block_index: BlockIndex) -> Option<Block> {
+    async fn get_block(block_index: BlockIndex) -> Result<Block, MultisigIndexError> {
         let args = GetBlocksArgs {
             start: block_index,
             length: 1,
         };
 
-        match query_blocks(MAINNET_LEDGER_CANISTER_ID, args.clone()).await {
-            Ok(blocks_result) => {
-                if blocks_result.blocks.len() >= 1 {
-                    debug_assert_eq!(blocks_result.first_block_index, block_index);
-                    return blocks_result.blocks.into_iter().next();
-                }
-
-                if let Some(func) = blocks_result.archived_blocks.into_iter().find_map(|b| {
-                    (b.start <= block_index && (block_index - b.start) < b.length)
-                        .then(|| b.callback)
-                }) {
-                    match query_archived_blocks(&func, args).await {
-                        Ok(range) => match range {
-                            Ok(_range) => return _range.blocks.into_iter().next(),
-                            Err(_) => return None,
-                        },
-                        _ => (),
-                    }
-                }
+        let blocks_result = query_blocks(Config::ledger_canister_id(), args.clone()).await?;
+
+        if !blocks_result.blocks.is_empty() {
+            debug_assert_eq!(blocks_result.first_block_index, block_index);
+            if let Some(block) = blocks_result.blocks.into_iter().next() {
+                return Ok(block);
             }
-            Err(_) => (),
         }
 
-        None
+        if let Some(func) = blocks_result.archived_blocks.into_iter().find_map(|b| {
+            (b.start <= block_index && (block_index - b.start) < b.length).then_some(b.callback)
+        }) {
+            if let Ok(Some(block)) = query_archived_blocks(&func, args)
+                .await?
+                .map(|range| range.blocks.into_iter().next())
+            {
+                return Ok(block);
+            }
+        }
+
+        Err(MultisigIndexError::InvalidTransaction(
+            TransactionValidationError::BlockNotFound(block_index),
+        ))
+    }
+
+    pub fn nat_to_u64(value: Nat) -> u64 {
+        TryFrom::try_from(value.0).unwrap()
     }
 
     fn principal_to_account_identifier(principal: Principal) -> AccountIdentifier {
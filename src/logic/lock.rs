@@ -0,0 +1,58 @@
+use std::{cell::RefCell, collections::BTreeSet};
+
+use candid::Principal;
+
+use crate::rust_declarations::types::MultisigIndexError;
+
+// The locks live on the heap, when a call traps its future is dropped and the lock is released,
+// an upgrade requires the canister to be stopped so no lock can be held across it
+thread_local! {
+    static PRINCIPAL_LOCKS: RefCell<BTreeSet<Principal>> = const { RefCell::new(BTreeSet::new()) };
+    static BLOCK_LOCKS: RefCell<BTreeSet<u64>> = const { RefCell::new(BTreeSet::new()) };
+}
+
+// Held while a call changes the local balance of a principal, released on drop
+pub struct PrincipalLock {
+    principal: Principal,
+}
+
+impl PrincipalLock {
+    pub fn acquire(principal: Principal) -> Result<Self, MultisigIndexError> {
+        PRINCIPAL_LOCKS.with(|l| {
+            if l.borrow_mut().insert(principal) {
+                Ok(Self { principal })
+            } else {
+                Err(MultisigIndexError::PrincipalLocked(principal))
+            }
+        })
+    }
+}
+
+impl Drop for PrincipalLock {
+    fn drop(&mut self) {
+        PRINCIPAL_LOCKS.with(|l| l.borrow_mut().remove(&self.principal));
+    }
+}
+
+// Held while a call processes the transaction of a block index, released on drop
+pub struct BlockLock {
+    block_index: u64,
+}
+
+impl BlockLock {
+    pub fn acquire(block_index: u64) -> Result<Self, MultisigIndexError> {
+        BLOCK_LOCKS.with(|l| {
+            if l.borrow_mut().insert(block_index) {
+                Ok(Self { block_index })
+            } else {
+                Err(MultisigIndexError::BlockLocked(block_index))
+            }
+        })
+    }
+}
+
+impl Drop for BlockLock {
+    fn drop(&mut self) {
+        BLOCK_LOCKS.with(|l| l.borrow_mut().remove(&self.block_index));
+    }
+}
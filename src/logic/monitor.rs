@@ -0,0 +1,418 @@
+use std::{cell::RefCell, convert::TryFrom, time::Duration};
+
+use candid::{Nat, Principal};
+use ic_cdk::api::{
+    call::{msg_cycles_accept128, msg_cycles_available128},
+    management_canister::main::{canister_status, deposit_cycles, CanisterIdRecord},
+    time,
+};
+use ic_cdk_timers::set_timer_interval;
+use ic_ledger_types::{AccountIdentifier, Subaccount, Tokens, TransferArgs};
+
+use crate::rust_declarations::{
+    cmc_service::NotifyError,
+    stored::Stored,
+    types::{
+        CanisterStatusEntry, CanisterStatusHistory, JournalAccount, JournalEntryKind,
+        MultisigIndexError, PendingTopUp, PrepaidBalance,
+    },
+};
+
+use super::{
+    cmc::CMC,
+    config::Config,
+    controllers::Controllers,
+    cycles::Cycles,
+    journal::Journal,
+    lock::PrincipalLock,
+    pricing::Pricing,
+    reconciliation::Reconciliation,
+    store::{ENTRIES, ICP_TRANSACTION_FEE, MEMO_TOP_UP_CANISTER, PREPAID_BALANCES, STATUS_HISTORY},
+    transfers::Transfers,
+};
+
+pub static MONITOR_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+// A week of checks is kept for every multisig
+pub static MAX_STATUS_HISTORY: usize = 28;
+pub static DEFAULT_TOP_UP_THRESHOLD_CYCLES: u64 = 1_000_000_000_000;
+pub static DEFAULT_TOP_UP_CYCLES: u64 = 2_000_000_000_000;
+
+thread_local! {
+    static IS_MONITORING: RefCell<bool> = const { RefCell::new(false) };
+}
+
+// Resets the running flag when the monitor finishes or its future is dropped on a trap
+struct MonitoringFlag;
+
+impl Drop for MonitoringFlag {
+    fn drop(&mut self) {
+        IS_MONITORING.with(|m| *m.borrow_mut() = false);
+    }
+}
+
+pub struct Monitor;
+
+impl Monitor {
+    pub fn start_timer() {
+        set_timer_interval(MONITOR_INTERVAL, || {
+            ic_cdk::spawn(async {
+                Self::monitor_multisigs().await;
+            })
+        });
+    }
+
+    // Check every multisig this canister still controls and top up the ones that run low on cycles,
+    // returns the new status entry of every checked multisig
+    pub async fn monitor_multisigs() -> Vec<(Principal, CanisterStatusEntry)> {
+        if IS_MONITORING.with(|m| m.replace(true)) {
+            return vec![];
+        }
+        let _flag = MonitoringFlag;
+
+        let canister_ids: Vec<Principal> = ENTRIES.with(|e| {
+            e.borrow()
+                .iter()
+                .filter_map(|(_, multisig)| multisig.valid())
+                .filter(Controllers::is_index_controller)
+                .map(|multisig| multisig.canister_id)
+                .collect()
+        });
+
+        let mut result = vec![];
+        for canister_id in canister_ids {
+            if let Some(entry) = Self::monitor_multisig(canister_id).await {
+                result.push((canister_id, entry));
+            }
+        }
+        result
+    }
+
+    pub fn get_status_history(canister_id: Principal) -> Vec<CanisterStatusEntry> {
+        STATUS_HISTORY
+            .with(|s| s.borrow().get(&canister_id.to_string()))
+            .and_then(Stored::valid)
+            .map(|history| history.entries)
+            .unwrap_or_default()
+    }
+
+    pub fn get_prepaid_balance(canister_id: Principal) -> PrepaidBalance {
+        PREPAID_BALANCES
+            .with(|p| p.borrow().get(&canister_id.to_string()))
+            .and_then(Stored::valid)
+            .unwrap_or(PrepaidBalance {
+                canister_id,
+                icp: Tokens::from_e8s(0),
+                cycles: Nat::from(0),
+                pending_top_up: None,
+                pending_transfer: None,
+                updated_at: time(),
+            })
+    }
+
+    // Move ICP from the local balance of the caller to the prepaid balance of the multisig
+    pub fn fund_with_icp(
+        caller: Principal,
+        canister_id: Principal,
+        e8s: u64,
+    ) -> Result<PrepaidBalance, MultisigIndexError> {
+        Self::check_multisig(canister_id)?;
+        let _principal_lock = PrincipalLock::acquire(caller)?;
+
+        let amount = Tokens::from_e8s(e8s);
+        Journal::record(
+            JournalEntryKind::MultisigFunding,
+            JournalAccount::Principal(caller),
+            JournalAccount::Multisig(canister_id),
+            amount,
+            None,
+        )?;
+        Ok(Self::update_prepaid_balance(canister_id, |balance| {
+            balance.icp += amount
+        }))
+    }
+
+    // Accept the cycles attached to the call as prepaid balance of the multisig
+    pub fn fund_with_cycles(canister_id: Principal) -> Result<PrepaidBalance, MultisigIndexError> {
+        Self::check_multisig(canister_id)?;
+
+        let available = msg_cycles_available128();
+        if available == 0 {
+            return Err(MultisigIndexError::NoBalance);
+        }
+        let accepted = msg_cycles_accept128(available);
+        Cycles::record_received(accepted);
+
+        Ok(Self::update_prepaid_balance(canister_id, |balance| {
+            balance.cycles += Nat::from(accepted)
+        }))
+    }
+
+    async fn monitor_multisig(canister_id: Principal) -> Option<CanisterStatusEntry> {
+        // A multisig that is being topped up by another call is checked on the next run
+        let _lock = PrincipalLock::acquire(canister_id).ok()?;
+
+        let mut entry = CanisterStatusEntry {
+            status: None,
+            cycles: None,
+            memory_size: None,
+            topped_up_cycles: None,
+            error_message: None,
+            checked_at: time(),
+        };
+
+        let mut topped_up = Nat::from(0);
+        let mut errors = vec![];
+
+        // A top up that was sent to or reached the CMC on a previous run is finished before a new one is started
+        if let Err(err) = Self::send_pending_transfer(canister_id).await {
+            errors.push(err.to_string());
+        }
+        match Self::notify_pending_top_up(canister_id).await {
+            Ok(cycles) => topped_up += cycles,
+            Err(err) => errors.push(err.to_string()),
+        }
+
+        match canister_status(CanisterIdRecord { canister_id }).await {
+            Ok((status,)) => {
+                let below_threshold =
+                    status.cycles.clone() + topped_up.clone() < Config::top_up_threshold_cycles();
+                entry.status = Some(status.status);
+                entry.cycles = Some(status.cycles);
+                entry.memory_size = Some(status.memory_size);
+
+                if below_threshold {
+                    match Self::top_up(canister_id, Config::top_up_cycles()).await {
+                        Ok(cycles) => topped_up += cycles,
+                        Err(err) => errors.push(err.to_string()),
+                    }
+                }
+            }
+            Err(err) => errors.push(MultisigIndexError::from(err).to_string()),
+        }
+
+        if topped_up > 0_u128 {
+            entry.topped_up_cycles = Some(topped_up);
+        }
+        if !errors.is_empty() {
+            entry.error_message = Some(errors.join(", "));
+        }
+
+        Self::insert_status_entry(canister_id, entry.clone());
+        Some(entry)
+    }
+
+    // Returns the amount of cycles the multisig was topped up with, zero when there are no funds left
+    async fn top_up(canister_id: Principal, cycles: u64) -> Result<Nat, MultisigIndexError> {
+        let balance = Self::get_prepaid_balance(canister_id);
+
+        // Prepaid cycles are used first, they are already held by this canister
+        let prepaid_cycles = u128::try_from(balance.cycles.0).unwrap_or(u128::MAX);
+        if prepaid_cycles > 0 {
+            let amount = prepaid_cycles.min(cycles as u128);
+            // Taken from the balance before the call so it cannot be spent twice
+            Self::update_prepaid_balance(canister_id, |balance| {
+                balance.cycles -= Nat::from(amount)
+            });
+
+            return match deposit_cycles(CanisterIdRecord { canister_id }, amount).await {
+                Ok(()) => {
+                    Cycles::record_deposited(amount);
+                    Ok(Nat::from(amount))
+                }
+                Err(err) => {
+                    Self::update_prepaid_balance(canister_id, |balance| {
+                        balance.cycles += Nat::from(amount)
+                    });
+                    Err(err.into())
+                }
+            };
+        }
+
+        // The funds of a transfer with an unknown outcome are not known yet
+        if balance.icp <= ICP_TRANSACTION_FEE || balance.pending_transfer.is_some() {
+            return Ok(Nat::from(0));
+        }
+
+        let price = Pricing::get_cycles_price(cycles).await?;
+        // Re-read the balance, it could have changed while the conversion rate was fetched
+        let available = Self::get_prepaid_balance(canister_id).icp;
+        if available <= ICP_TRANSACTION_FEE {
+            return Ok(Nat::from(0));
+        }
+        let amount = price.min(available - ICP_TRANSACTION_FEE);
+        let charged = amount + ICP_TRANSACTION_FEE;
+
+        Self::update_prepaid_balance(canister_id, |balance| balance.icp -= charged);
+        Journal::record(
+            JournalEntryKind::MultisigTopUp,
+            JournalAccount::Multisig(canister_id),
+            JournalAccount::Cmc,
+            charged,
+            None,
+        )?;
+
+        let transfer_id = Transfers::top_up_id(&canister_id, time());
+        let args = TransferArgs {
+            memo: MEMO_TOP_UP_CANISTER,
+            amount,
+            fee: ICP_TRANSACTION_FEE,
+            from_subaccount: None,
+            to: AccountIdentifier::new(&Config::cmc_canister_id(), &Subaccount::from(canister_id)),
+            created_at_time: None,
+        };
+        Self::update_prepaid_balance(canister_id, |balance| {
+            balance.pending_transfer = Some(transfer_id.clone())
+        });
+
+        Self::send_top_up_transfer(canister_id, transfer_id, args).await?;
+        Self::notify_pending_top_up(canister_id).await
+    }
+
+    // Send a top up transfer again when its outcome was not known on the previous run
+    async fn send_pending_transfer(canister_id: Principal) -> Result<(), MultisigIndexError> {
+        let transfer = match Self::get_prepaid_balance(canister_id)
+            .pending_transfer
+            .and_then(|transfer_id| Transfers::get_transfer(&transfer_id))
+        {
+            Some(transfer) => transfer,
+            None => return Ok(()),
+        };
+
+        Self::send_top_up_transfer(canister_id, transfer.id, transfer.args).await
+    }
+
+    async fn send_top_up_transfer(
+        canister_id: Principal,
+        transfer_id: String,
+        args: TransferArgs,
+    ) -> Result<(), MultisigIndexError> {
+        let amount = args.amount;
+        let charged = args.amount + args.fee;
+
+        match Transfers::transfer(transfer_id.clone(), args).await {
+            Ok(cmc_block_index) => {
+                Self::update_prepaid_balance(canister_id, |balance| {
+                    balance.pending_transfer = None;
+                    balance.pending_top_up = Some(PendingTopUp {
+                        cmc_block_index,
+                        amount,
+                    })
+                });
+                Ok(())
+            }
+            // The outcome is unknown, the transfer is sent again on the next run
+            Err(err) if Transfers::is_unresolved(&transfer_id) => Err(err),
+            Err(err) => {
+                // the ICP never left this canister, so it is given back
+                Self::update_prepaid_balance(canister_id, |balance| {
+                    balance.pending_transfer = None;
+                    balance.icp += charged
+                });
+                Journal::record(
+                    JournalEntryKind::Refund,
+                    JournalAccount::Cmc,
+                    JournalAccount::Multisig(canister_id),
+                    charged,
+                    None,
+                )?;
+                Err(err)
+            }
+        }
+    }
+
+    async fn notify_pending_top_up(canister_id: Principal) -> Result<Nat, MultisigIndexError> {
+        let pending = match Self::get_prepaid_balance(canister_id).pending_top_up {
+            Some(pending) => pending,
+            None => return Ok(Nat::from(0)),
+        };
+
+        match CMC::top_up(pending.cmc_block_index, canister_id).await {
+            Ok(cycles) => {
+                Self::update_prepaid_balance(canister_id, |balance| balance.pending_top_up = None);
+                Ok(cycles)
+            }
+            Err(err) => {
+                if Reconciliation::is_final_notify_error(&err) {
+                    // The CMC sends refunded ICP back minus the ledger fee
+                    let refunded = match &err {
+                        MultisigIndexError::CmcNotify(NotifyError::Refunded { .. })
+                            if pending.amount > ICP_TRANSACTION_FEE =>
+                        {
+                            Some(pending.amount - ICP_TRANSACTION_FEE)
+                        }
+                        _ => None,
+                    };
+                    Self::update_prepaid_balance(canister_id, |balance| {
+                        balance.pending_top_up = None;
+                        if let Some(refunded) = refunded {
+                            balance.icp += refunded;
+                        }
+                    });
+                    if let Some(refunded) = refunded {
+                        Journal::record(
+                            JournalEntryKind::Refund,
+                            JournalAccount::Cmc,
+                            JournalAccount::Multisig(canister_id),
+                            refunded,
+                            Some(pending.cmc_block_index),
+                        )?;
+                    }
+                }
+                Err(err)
+            }
+        }
+    }
+
+    fn check_multisig(canister_id: Principal) -> Result<(), MultisigIndexError> {
+        if !ENTRIES.with(|e| e.borrow().contains_key(&canister_id.to_string())) {
+            return Err(MultisigIndexError::MultisigNotFound(canister_id));
+        }
+        // Funding would replace a balance that can not be read, it has to be repaired first
+        if let Some(Stored::Corrupt { error, .. }) =
+            PREPAID_BALANCES.with(|p| p.borrow().get(&canister_id.to_string()))
+        {
+            return Err(MultisigIndexError::CorruptRecord(error));
+        }
+        Ok(())
+    }
+
+    // Always changes the latest stored balance, so changes made during an open call are never overwritten
+    fn update_prepaid_balance(
+        canister_id: Principal,
+        update: impl FnOnce(&mut PrepaidBalance),
+    ) -> PrepaidBalance {
+        let mut balance = Self::get_prepaid_balance(canister_id);
+        update(&mut balance);
+        balance.updated_at = time();
+        PREPAID_BALANCES.with(|p| {
+            p.borrow_mut()
+                .insert(canister_id.to_string(), balance.clone().into())
+        });
+        balance
+    }
+
+    fn insert_status_entry(canister_id: Principal, entry: CanisterStatusEntry) {
+        STATUS_HISTORY.with(|s| {
+            let mut history = s.borrow_mut();
+            let mut entries = history
+                .get(&canister_id.to_string())
+                .and_then(Stored::valid)
+                .map(|history| history.entries)
+                .unwrap_or_default();
+
+            entries.push(entry);
+            if entries.len() > MAX_STATUS_HISTORY {
+                entries.remove(0);
+            }
+
+            history.insert(
+                canister_id.to_string(),
+                CanisterStatusHistory {
+                    canister_id,
+                    entries,
+                }
+                .into(),
+            );
+        });
+    }
+}
@@ -0,0 +1,187 @@
+use ic_stable_structures::{Memory, StableBTreeMap, Storable};
+
+use crate::rust_declarations::{
+    stored::{Stored, Versioned},
+    types::SortDirection,
+};
+
+pub static DEFAULT_PAGE_LIMIT: u64 = 100;
+// Keeps a page of the largest records well below the response size limit of a query
+pub static MAX_PAGE_LIMIT: u64 = 500;
+
+pub struct Page<K, V> {
+    pub items: Vec<V>,
+    pub next: Option<K>,
+    pub total: u64,
+}
+
+pub struct Pagination;
+
+impl Pagination {
+    // Walk the map from the start key in the given direction and return up to `limit` values that match,
+    // the total counts the matching values of the whole map. Records that can not be decoded are skipped
+    pub fn get_page<K, V, M>(
+        map: &StableBTreeMap<K, Stored<V>, M>,
+        start: Option<K>,
+        limit: Option<u64>,
+        direction: Option<SortDirection>,
+        matches: impl Fn(&V) -> bool,
+    ) -> Page<K, V>
+    where
+        K: Storable + Ord + Clone,
+        V: Versioned,
+        M: Memory,
+    {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT) as usize;
+
+        let entries: Box<dyn Iterator<Item = (K, Stored<V>)>> =
+            match direction.unwrap_or(SortDirection::Asc) {
+                SortDirection::Asc => match start {
+                    Some(start) => Box::new(map.range(start..)),
+                    None => Box::new(map.iter()),
+                },
+                // The map can only be iterated forward, so every step looks up the next lower key
+                SortDirection::Desc => {
+                    let first = match start {
+                        Some(start) => match map.get(&start) {
+                            Some(value) => Some((start, value)),
+                            None => map.iter_upper_bound(&start).next(),
+                        },
+                        None => map.last_key_value(),
+                    };
+                    Box::new(std::iter::successors(first, move |(key, _)| {
+                        map.iter_upper_bound(key).next()
+                    }))
+                }
+            };
+
+        let mut items = vec![];
+        let mut next = None;
+        let matching = entries
+            .filter_map(|(key, value)| value.valid().map(|value| (key, value)))
+            .filter(|(_, value)| matches(value));
+        for (key, value) in matching {
+            if items.len() == limit {
+                next = Some(key);
+                break;
+            }
+            items.push(value);
+        }
+
+        let total = map
+            .iter()
+            .filter(|(_, value)| value.as_valid().is_some_and(&matches))
+            .count() as u64;
+
+        Page { items, next, total }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use candid::CandidType;
+    use ic_stable_structures::DefaultMemoryImpl;
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+    struct Record {
+        value: u64,
+    }
+
+    impl Versioned for Record {
+        const VERSION: u8 = 1;
+    }
+
+    fn map(keys: &[u64]) -> StableBTreeMap<u64, Stored<Record>, DefaultMemoryImpl> {
+        let mut map = StableBTreeMap::init(DefaultMemoryImpl::default());
+        for key in keys {
+            map.insert(*key, Record { value: *key }.into());
+        }
+        map
+    }
+
+    fn values(page: &Page<u64, Record>) -> Vec<u64> {
+        page.items.iter().map(|record| record.value).collect()
+    }
+
+    #[test]
+    fn ascending_pages_continue_from_next() {
+        let map = map(&[1, 2, 3, 4, 5]);
+
+        let first = Pagination::get_page(&map, None, Some(2), None, |_| true);
+        assert_eq!(values(&first), vec![1, 2]);
+        assert_eq!(first.next, Some(3));
+        assert_eq!(first.total, 5);
+
+        let last = Pagination::get_page(&map, Some(5), Some(2), None, |_| true);
+        assert_eq!(values(&last), vec![5]);
+        assert_eq!(last.next, None);
+    }
+
+    #[test]
+    fn descending_pages_continue_from_next() {
+        let map = map(&[1, 2, 3, 4, 5]);
+
+        let first = Pagination::get_page(&map, None, Some(2), Some(SortDirection::Desc), |_| true);
+        assert_eq!(values(&first), vec![5, 4]);
+        assert_eq!(first.next, Some(3));
+
+        let second =
+            Pagination::get_page(&map, first.next, Some(2), Some(SortDirection::Desc), |_| {
+                true
+            });
+        assert_eq!(values(&second), vec![3, 2]);
+        assert_eq!(second.next, Some(1));
+    }
+
+    #[test]
+    fn descending_start_between_keys_uses_the_next_lower_key() {
+        let map = map(&[10, 20, 30]);
+
+        let page = Pagination::get_page(&map, Some(25), None, Some(SortDirection::Desc), |_| true);
+        assert_eq!(values(&page), vec![20, 10]);
+        assert_eq!(page.next, None);
+    }
+
+    #[test]
+    fn filter_applies_to_the_items_and_the_total() {
+        let map = map(&[1, 2, 3, 4, 5, 6]);
+
+        let page = Pagination::get_page(&map, None, Some(2), None, |record| record.value % 2 == 0);
+        assert_eq!(values(&page), vec![2, 4]);
+        assert_eq!(page.next, Some(6));
+        assert_eq!(page.total, 3);
+    }
+
+    #[test]
+    fn corrupt_records_are_skipped() {
+        let mut map = map(&[1, 3]);
+        map.insert(
+            2,
+            Stored::Corrupt {
+                version: None,
+                error: "Unknown record format".to_string(),
+                bytes: vec![1, 2, 3],
+            },
+        );
+
+        let page = Pagination::get_page(&map, None, None, None, |_| true);
+        assert_eq!(values(&page), vec![1, 3]);
+        assert_eq!(page.total, 2);
+    }
+
+    #[test]
+    fn limit_is_clamped() {
+        let keys: Vec<u64> = (0..MAX_PAGE_LIMIT + 10).collect();
+        let map = map(&keys);
+
+        let empty_limit = Pagination::get_page(&map, None, Some(0), None, |_| true);
+        assert_eq!(empty_limit.items.len(), 1);
+
+        let large_limit = Pagination::get_page(&map, None, Some(u64::MAX), None, |_| true);
+        assert_eq!(large_limit.items.len() as u64, MAX_PAGE_LIMIT);
+        assert_eq!(large_limit.next, Some(MAX_PAGE_LIMIT));
+    }
+}
@@ -0,0 +1,191 @@
+use std::{cell::RefCell, time::Duration};
+
+use ic_cdk::api::time;
+use ic_cdk_timers::{set_timer, set_timer_interval};
+use ic_ledger_types::Tokens;
+
+use crate::rust_declarations::{
+    cmc_service::CmcService,
+    types::{MultisigIndexError, SpawnPrice},
+};
+
+use super::{config::Config, store::ICP_TRANSACTION_FEE};
+
+pub static CONVERSION_RATE_REFRESH_INTERVAL: Duration = Duration::from_secs(10 * 60);
+// A quote stays valid a bit longer as the refresh interval so a failed refresh does not block spawns right away
+pub static PRICE_QUOTE_VALIDITY_NANOS: u64 = 15 * 60 * 1_000_000_000;
+
+#[derive(Clone, Copy)]
+struct ConversionRate {
+    xdr_permyriad_per_icp: u64,
+    fetched_at: u64,
+}
+
+thread_local! {
+    static CONVERSION_RATE: RefCell<Option<ConversionRate>> = const { RefCell::new(None) };
+}
+
+pub struct Pricing;
+
+impl Pricing {
+    pub fn start_timer() {
+        // The cache lives on the heap, so it is filled right after an install or upgrade
+        set_timer(Duration::ZERO, || {
+            ic_cdk::spawn(async {
+                let _ = Self::refresh_conversion_rate().await;
+            })
+        });
+        set_timer_interval(CONVERSION_RATE_REFRESH_INTERVAL, || {
+            ic_cdk::spawn(async {
+                let _ = Self::refresh_conversion_rate().await;
+            })
+        });
+    }
+
+    // The price based on the cached conversion rate, or the fixed price when no cycles amount is configured
+    pub fn get_spawn_price() -> Result<SpawnPrice, MultisigIndexError> {
+        let config = Config::get();
+
+        let multisig_cycles = match config.multisig_cycles {
+            Some(multisig_cycles) => multisig_cycles,
+            None => {
+                return Ok(SpawnPrice {
+                    total_e8s: config.min_e8s_for_spinup,
+                    catalyze_fee: config.catalyze_e8s_fee,
+                    multisig_cycles: None,
+                    xdr_permyriad_per_icp: None,
+                    expires_at: None,
+                })
+            }
+        };
+
+        let rate = match Self::get_valid_conversion_rate() {
+            Some(rate) => rate,
+            None => return Err(MultisigIndexError::PriceUnavailable),
+        };
+
+        let (total_e8s, catalyze_fee) = Self::calculate_price(
+            multisig_cycles,
+            rate.xdr_permyriad_per_icp,
+            config.catalyze_fee_percentage.unwrap_or_default(),
+        );
+
+        Ok(SpawnPrice {
+            total_e8s,
+            catalyze_fee,
+            multisig_cycles: Some(multisig_cycles),
+            xdr_permyriad_per_icp: Some(rate.xdr_permyriad_per_icp),
+            expires_at: Some(rate.fetched_at + PRICE_QUOTE_VALIDITY_NANOS),
+        })
+    }
+
+    // Same as `get_spawn_price` but fetches a new conversion rate when the cached one expired
+    pub async fn get_current_spawn_price() -> Result<SpawnPrice, MultisigIndexError> {
+        if Config::get().multisig_cycles.is_some() && Self::get_valid_conversion_rate().is_none() {
+            Self::refresh_conversion_rate().await?;
+        }
+        Self::get_spawn_price()
+    }
+
+    // The e8s that mint the given amount of cycles, fetches a new conversion rate when the cached one expired
+    pub async fn get_cycles_price(cycles: u64) -> Result<Tokens, MultisigIndexError> {
+        if Self::get_valid_conversion_rate().is_none() {
+            Self::refresh_conversion_rate().await?;
+        }
+        let rate = Self::get_valid_conversion_rate().ok_or(MultisigIndexError::PriceUnavailable)?;
+        Ok(Tokens::from_e8s(Self::cycles_to_e8s(
+            cycles,
+            rate.xdr_permyriad_per_icp,
+        )))
+    }
+
+    // Returns the total price of a spawn and the Catalyze fee that is part of it
+    fn calculate_price(
+        multisig_cycles: u64,
+        xdr_permyriad_per_icp: u64,
+        catalyze_fee_percentage: u64,
+    ) -> (Tokens, Tokens) {
+        let multisig_e8s = Self::cycles_to_e8s(multisig_cycles, xdr_permyriad_per_icp);
+        let percentage_fee = multisig_e8s * catalyze_fee_percentage / 100;
+
+        // Both the transfer to the CMC and the transfer to the fee recipient cost a ledger fee
+        let catalyze_fee = Tokens::from_e8s(percentage_fee) + ICP_TRANSACTION_FEE;
+        let total_e8s = Tokens::from_e8s(multisig_e8s) + ICP_TRANSACTION_FEE + catalyze_fee;
+        (total_e8s, catalyze_fee)
+    }
+
+    // 1 XDR mints 1T cycles, so a single e8 mints `xdr_permyriad_per_icp` cycles. Rounded up so the
+    // cycles are always covered
+    fn cycles_to_e8s(cycles: u64, xdr_permyriad_per_icp: u64) -> u64 {
+        cycles.div_ceil(xdr_permyriad_per_icp)
+    }
+
+    pub async fn refresh_conversion_rate() -> Result<u64, MultisigIndexError> {
+        let (response,) = CmcService(Config::cmc_canister_id())
+            .get_icp_xdr_conversion_rate()
+            .await?;
+
+        let xdr_permyriad_per_icp = response.data.xdr_permyriad_per_icp;
+        if xdr_permyriad_per_icp == 0 {
+            return Err(MultisigIndexError::PriceUnavailable);
+        }
+
+        CONVERSION_RATE.with(|c| {
+            *c.borrow_mut() = Some(ConversionRate {
+                xdr_permyriad_per_icp,
+                fetched_at: time(),
+            })
+        });
+        Ok(xdr_permyriad_per_icp)
+    }
+
+    fn get_valid_conversion_rate() -> Option<ConversionRate> {
+        CONVERSION_RATE
+            .with(|c| *c.borrow())
+            .filter(|rate| rate.fetched_at + PRICE_QUOTE_VALIDITY_NANOS > time())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 10 XDR per ICP
+    const RATE: u64 = 100_000;
+
+    #[test]
+    fn cycles_are_rounded_up_to_whole_e8s() {
+        assert_eq!(Pricing::cycles_to_e8s(2_000_000_000_000, RATE), 20_000_000);
+        assert_eq!(Pricing::cycles_to_e8s(2_000_000_000_001, RATE), 20_000_001);
+        assert_eq!(Pricing::cycles_to_e8s(0, RATE), 0);
+    }
+
+    #[test]
+    fn price_includes_the_fee_and_two_ledger_fees() {
+        let (total, catalyze_fee) = Pricing::calculate_price(2_000_000_000_000, RATE, 10);
+
+        assert_eq!(
+            catalyze_fee,
+            Tokens::from_e8s(2_000_000) + ICP_TRANSACTION_FEE
+        );
+        assert_eq!(
+            total,
+            Tokens::from_e8s(20_000_000) + ICP_TRANSACTION_FEE + catalyze_fee
+        );
+    }
+
+    #[test]
+    fn price_without_percentage_fee_still_pays_the_ledger_fees() {
+        let (total, catalyze_fee) = Pricing::calculate_price(2_000_000_000_000, RATE, 0);
+
+        assert_eq!(catalyze_fee, ICP_TRANSACTION_FEE);
+        assert_eq!(total.e8s(), 20_000_000 + 2 * ICP_TRANSACTION_FEE.e8s());
+    }
+
+    #[test]
+    fn higher_rate_lowers_the_price() {
+        let (cheap, _) = Pricing::calculate_price(2_000_000_000_000, 2 * RATE, 10);
+        let (expensive, _) = Pricing::calculate_price(2_000_000_000_000, RATE, 10);
+        assert!(cheap < expensive);
+    }
+}
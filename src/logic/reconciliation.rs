@@ -0,0 +1,152 @@
+use std::{cell::RefCell, time::Duration};
+
+use ic_cdk::api::time;
+use ic_cdk_timers::set_timer_interval;
+
+use crate::rust_declarations::{
+    cmc_service::NotifyError,
+    types::{CanisterCreation, MultisigIndexError, TransactionData, TransactionStatus},
+};
+
+use super::{cmc::CMC, cycles::Cycles, lock::BlockLock, store::Store, transfers::Transfers};
+
+pub static RECONCILIATION_INTERVAL: Duration = Duration::from_secs(10 * 60);
+pub static MAX_RECONCILIATION_ATTEMPTS: u32 = 10;
+// Pending transactions younger as this could still be handled by an ongoing call
+pub static PENDING_TIMEOUT_NANOS: u64 = 60 * 60 * 1_000_000_000;
+
+thread_local! {
+    static IS_RECONCILING: RefCell<bool> = const { RefCell::new(false) };
+}
+
+// Resets the running flag when the reconciliation finishes or its future is dropped on a trap
+struct ReconcilingFlag;
+
+impl Drop for ReconcilingFlag {
+    fn drop(&mut self) {
+        IS_RECONCILING.with(|r| *r.borrow_mut() = false);
+    }
+}
+
+pub struct Reconciliation;
+
+impl Reconciliation {
+    pub fn start_timer() {
+        set_timer_interval(RECONCILIATION_INTERVAL, || {
+            ic_cdk::spawn(async {
+                Self::reconcile_transactions().await;
+            })
+        });
+    }
+
+    // Walk all transactions that are stuck in a non-terminal state and try to move them forward,
+    // returns the updated transactions
+    pub async fn reconcile_transactions() -> Vec<TransactionData> {
+        if IS_RECONCILING.with(|r| r.replace(true)) {
+            return vec![];
+        }
+        let _flag = ReconcilingFlag;
+
+        let mut reconciled = vec![];
+        for transaction in Self::get_stuck_transactions() {
+            let icp_block_index = transaction.icp_transfer_block_index;
+            Self::reconcile_transaction(transaction).await;
+            if let Some(transaction) = Store::get_transaction(icp_block_index) {
+                reconciled.push(transaction);
+            }
+        }
+        reconciled
+    }
+
+    fn get_stuck_transactions() -> Vec<TransactionData> {
+        let now = time();
+        let spawn_block_indexes = Store::get_spawn_block_indexes();
+        Store::get_transactions(None)
+            .into_iter()
+            // The transaction of a spawn is recovered by resuming the spawn, the cycles minted here would not
+            // be linked to it. A canister created by the CMC always belongs to a spawn
+            .filter(|t| !spawn_block_indexes.contains(&t.icp_transfer_block_index))
+            .filter(|t| !matches!(t.creation, Some(CanisterCreation::Cmc { .. })))
+            .filter(|t| match t.status {
+                TransactionStatus::CyclesToIndexFailed | TransactionStatus::IcpToCmcFailed => true,
+                TransactionStatus::Pending => t.created_at + PENDING_TIMEOUT_NANOS < now,
+                _ => false,
+            })
+            .collect()
+    }
+
+    async fn reconcile_transaction(mut transaction: TransactionData) {
+        // A call that is still processing this block is left alone
+        let _block_lock = match BlockLock::acquire(transaction.icp_transfer_block_index) {
+            Ok(lock) => lock,
+            Err(_) => return,
+        };
+
+        let attempts = transaction.attempts.unwrap_or_default() + 1;
+        transaction.attempts = Some(attempts);
+        transaction.last_attempt_at = Some(time());
+
+        match transaction.cmc_transfer_block_index {
+            // The ICP already reached the CMC, notifying the CMC again is idempotent
+            Some(cmc_block_index) => {
+                Self::retry_top_up(transaction, cmc_block_index, attempts).await
+            }
+            None => Self::close_without_transfer(transaction),
+        }
+    }
+
+    async fn retry_top_up(mut transaction: TransactionData, cmc_block_index: u64, attempts: u32) {
+        match CMC::top_up_self(cmc_block_index).await {
+            Ok(cycles) => {
+                Cycles::record_minted(&cycles);
+                transaction.cycles_amount = Some(cycles);
+                transaction.status = TransactionStatus::Success;
+                transaction.error_message = None;
+            }
+            Err(err) => {
+                transaction.status = if Self::is_final_notify_error(&err)
+                    || attempts >= MAX_RECONCILIATION_ATTEMPTS
+                {
+                    TransactionStatus::RecoveryFailed
+                } else {
+                    TransactionStatus::CyclesToIndexFailed
+                };
+                transaction.error_message = Some(err.to_string());
+            }
+        }
+        Store::insert_transaction_data(transaction.icp_transfer_block_index, transaction);
+    }
+
+    // Without a CMC block there is nothing to notify, the ICP of the block is on the local balance of the
+    // initializer. Spending it here would mint cycles that no spawn asked for, so the transaction is closed
+    fn close_without_transfer(transaction: TransactionData) {
+        // A transfer with an unknown outcome is resolved by the call that sent it
+        if Transfers::is_unresolved(&Transfers::spawn_payment_id(
+            transaction.icp_transfer_block_index,
+        )) {
+            return;
+        }
+        Self::set_recovery_failed(
+            transaction,
+            "No ICP was sent to the CMC, the amount stays on the local balance of the initializer",
+        );
+    }
+
+    fn set_recovery_failed(mut transaction: TransactionData, error_message: &str) {
+        transaction.status = TransactionStatus::RecoveryFailed;
+        transaction.error_message = Some(error_message.to_string());
+        Store::insert_transaction_data(transaction.icp_transfer_block_index, transaction);
+    }
+
+    // Errors where notifying the CMC again will never succeed
+    pub fn is_final_notify_error(err: &MultisigIndexError) -> bool {
+        matches!(
+            err,
+            MultisigIndexError::CmcNotify(
+                NotifyError::Refunded { .. }
+                    | NotifyError::InvalidTransaction(_)
+                    | NotifyError::TransactionTooOld(_)
+            )
+        )
+    }
+}
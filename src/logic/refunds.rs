@@ -0,0 +1,360 @@
+use std::convert::TryFrom;
+
+use candid::Principal;
+use ic_cdk::api::{
+    management_canister::main::{deposit_cycles, CanisterIdRecord},
+    time,
+};
+use ic_ledger_types::Tokens;
+
+use crate::rust_declarations::{
+    stored::Stored,
+    types::{
+        Event, EventKind, InitializeStatus, JournalAccount, JournalEntryKind, MultisigIndexError,
+        OutgoingTransferStatus, RefundClaim, RefundMethod, RefundResolution, RefundStatus,
+        SpawnData, SpawnStep, TransactionStatus,
+    },
+};
+
+use super::{
+    cycles::Cycles,
+    events::Events,
+    journal::Journal,
+    lock::PrincipalLock,
+    store::{Store, REFUND_CLAIMS},
+    transfers::Transfers,
+};
+
+// A spawn that fails after the ICP of the user was converted to cycles gets a refund claim. The claimant can
+// retry the spawn with it, have the cycles deposited to a canister while this canister still holds them, or
+// request an ICP payout that an admin reviews
+pub struct Refunds;
+
+impl Refunds {
+    pub fn get_claim(icp_block_index: u64) -> Option<RefundClaim> {
+        REFUND_CLAIMS
+            .with(|r| r.borrow().get(&icp_block_index))
+            .and_then(Stored::valid)
+    }
+
+    pub fn get_claims_by_claimant(claimant: Principal) -> Vec<RefundClaim> {
+        Self::filter_claims(|claim| claim.claimant == claimant)
+    }
+
+    pub fn get_review_queue() -> Vec<RefundClaim> {
+        Self::filter_claims(|claim| claim.status == RefundStatus::PendingReview)
+    }
+
+    // Called when a spawn fails, only a spawn that already sent the payment to the CMC gets a claim. When the
+    // CMC did not return cycles or a canister for it, the claim can only be paid out as ICP
+    pub fn open_claim(spawn: &SpawnData, err: &MultisigIndexError) {
+        let canister_created = spawn.completed_steps.contains(&SpawnStep::CanisterCreated);
+        let transaction = Store::get_transaction(spawn.icp_block_index);
+        // A successful transaction that this spawn did not top up with was used by another spawn
+        let sent_to_cmc = transaction.as_ref().is_some_and(|transaction| {
+            transaction.cmc_transfer_block_index.is_some()
+                && transaction.status != TransactionStatus::Success
+                && transaction.initialized_by == spawn.created_by
+        }) && !Store::is_topped_up_by_other_spawn(spawn);
+        if !canister_created
+            && !spawn.completed_steps.contains(&SpawnStep::ToppedUp)
+            && !sent_to_cmc
+        {
+            return;
+        }
+
+        let mut claim = match Self::get_claim(spawn.icp_block_index) {
+            Some(claim) if claim.status == RefundStatus::Open => claim,
+            Some(_) => return,
+            None => RefundClaim {
+                icp_block_index: spawn.icp_block_index,
+                group_identifier: spawn.group_identifier,
+                claimant: spawn.created_by,
+                icp_amount: transaction.as_ref().and_then(|t| t.icp_amount),
+                cmc_amount: None,
+                catalyze_fee: transaction.and_then(|t| t.catalyze_fee),
+                cycles: None,
+                canister_id: None,
+                status: RefundStatus::Open,
+                method: None,
+                error_message: None,
+                review_note: None,
+                paid_e8s: None,
+                created_at: time(),
+                updated_at: time(),
+            },
+        };
+
+        // Once the canister is created the cycles are held by the canister and no longer by this canister
+        claim.cycles = if canister_created {
+            None
+        } else {
+            spawn.cycles.clone()
+        };
+        claim.canister_id = spawn.canister_id;
+        claim.cmc_amount = Self::get_cmc_amount(claim.icp_block_index);
+        claim.error_message = Some(err.to_string());
+        Events::record(Event {
+            principal: Some(claim.claimant),
+            group_identifier: Some(claim.group_identifier),
+            canister_id: claim.canister_id,
+            icp_block_index: Some(claim.icp_block_index),
+            cycles: claim.cycles.clone(),
+            error_message: claim.error_message.clone(),
+            ..Event::new(EventKind::RefundClaimOpened)
+        });
+        Self::insert_claim(claim);
+    }
+
+    // A retry of the spawn succeeded, so the payment was used after all
+    pub fn credit_claim(icp_block_index: u64) {
+        if let Some(mut claim) = Self::get_claim(icp_block_index) {
+            if claim.status == RefundStatus::Open {
+                claim.status = RefundStatus::Credited;
+                claim.error_message = None;
+                Self::insert_claim(claim);
+            }
+        }
+    }
+
+    // A spawn can only be retried while its claim is open, otherwise the payment is used twice
+    pub fn check_can_resume(icp_block_index: u64) -> Result<(), MultisigIndexError> {
+        match Self::get_claim(icp_block_index) {
+            Some(claim) if claim.status != RefundStatus::Open => {
+                Err(MultisigIndexError::RefundUnavailable(
+                    "The payment of this spawn is refunded".to_string(),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn is_paid_out(icp_block_index: u64) -> bool {
+        Self::get_claim(icp_block_index).is_some_and(|claim| claim.status == RefundStatus::PaidOut)
+    }
+
+    pub async fn claim_refund(
+        caller: Principal,
+        icp_block_index: u64,
+        method: RefundMethod,
+    ) -> Result<RefundClaim, MultisigIndexError> {
+        let _principal_lock = PrincipalLock::acquire(caller)?;
+
+        let mut claim = Self::get_claim(icp_block_index)
+            .ok_or(MultisigIndexError::RefundClaimNotFound(icp_block_index))?;
+
+        if claim.claimant != caller {
+            return Err(MultisigIndexError::Unauthorized);
+        }
+        if claim.status != RefundStatus::Open {
+            return Err(MultisigIndexError::RefundUnavailable(
+                "The claim is not open".to_string(),
+            ));
+        }
+        // A running retry could still use the payment
+        if Store::get_initialization_status(claim.group_identifier)
+            == Some(InitializeStatus::Initializing)
+        {
+            return Err(MultisigIndexError::InitializationInProgress(
+                claim.group_identifier,
+            ));
+        }
+
+        claim.method = Some(method.clone());
+        match method {
+            RefundMethod::Cycles(canister_id) => {
+                let cycles = claim.cycles.clone().ok_or_else(|| {
+                    MultisigIndexError::RefundUnavailable(
+                        "This canister does not hold cycles for the spawn".to_string(),
+                    )
+                })?;
+                let amount = u128::try_from(cycles.0.clone()).unwrap_or(u128::MAX);
+
+                // Closed before the call so the cycles cannot be paid out twice or used by a retry
+                claim.status = RefundStatus::PaidOut;
+                Self::insert_claim(claim.clone());
+
+                match deposit_cycles(CanisterIdRecord { canister_id }, amount).await {
+                    Ok(()) => {
+                        Cycles::record_deposited(amount);
+                        Self::record_paid_out(
+                            &claim,
+                            Event {
+                                canister_id: Some(canister_id),
+                                cycles: Some(cycles),
+                                ..Event::new(EventKind::RefundPaidOut)
+                            },
+                        );
+                        Self::refund_fee(&claim);
+                        claim.error_message = None;
+                        Self::insert_claim(claim.clone());
+                        Ok(claim)
+                    }
+                    Err(err) => {
+                        let err = MultisigIndexError::from(err);
+                        claim.status = RefundStatus::Open;
+                        claim.error_message = Some(err.to_string());
+                        Self::insert_claim(claim);
+                        Err(err)
+                    }
+                }
+            }
+            // Cycles cannot be turned back into ICP, so the amount is decided by an admin
+            RefundMethod::Icp => {
+                claim.status = RefundStatus::PendingReview;
+                Self::insert_claim(claim.clone());
+                Ok(claim)
+            }
+        }
+    }
+
+    pub fn resolve_claim(
+        icp_block_index: u64,
+        resolution: RefundResolution,
+    ) -> Result<RefundClaim, MultisigIndexError> {
+        let mut claim = Self::get_claim(icp_block_index)
+            .ok_or(MultisigIndexError::RefundClaimNotFound(icp_block_index))?;
+
+        if claim.status != RefundStatus::PendingReview {
+            return Err(MultisigIndexError::RefundUnavailable(
+                "The claim is not pending review".to_string(),
+            ));
+        }
+
+        match resolution {
+            RefundResolution::Approve(e8s) => {
+                // Claims opened before the amount was recorded look it up now
+                if claim.cmc_amount.is_none() {
+                    claim.cmc_amount = Self::get_cmc_amount(icp_block_index);
+                }
+                Self::check_refundable(&claim, e8s)?;
+                let amount = Tokens::from_e8s(e8s);
+                Journal::record(
+                    JournalEntryKind::Refund,
+                    JournalAccount::Cmc,
+                    JournalAccount::Principal(claim.claimant),
+                    amount,
+                    Some(icp_block_index),
+                )?;
+                Self::refund_fee(&claim);
+                Self::record_paid_out(
+                    &claim,
+                    Event {
+                        amount: Some(amount),
+                        ..Event::new(EventKind::RefundPaidOut)
+                    },
+                );
+                claim.status = RefundStatus::PaidOut;
+                claim.paid_e8s = Some(amount);
+            }
+            RefundResolution::Reject(reason) => {
+                claim.status = RefundStatus::Open;
+                claim.review_note = Some(reason);
+            }
+        }
+
+        Self::insert_claim(claim.clone());
+        Ok(claim)
+    }
+
+    // The amount of the completed transfer of the spawn to the CMC
+    fn get_cmc_amount(icp_block_index: u64) -> Option<Tokens> {
+        Transfers::get_transfer(&Transfers::spawn_payment_id(icp_block_index))
+            .filter(|transfer| matches!(transfer.status, OutgoingTransferStatus::Completed(_)))
+            .map(|transfer| transfer.args.amount)
+    }
+
+    // At most what the spawn sent to the CMC minus what was given back already. The rest of the deposit stayed
+    // on the local balance and the Catalyze fee is given back separately
+    fn check_refundable(claim: &RefundClaim, e8s: u64) -> Result<(), MultisigIndexError> {
+        let paid = claim.paid_e8s.map_or(0, |paid| paid.e8s());
+        let refundable = claim
+            .cmc_amount
+            .map_or(0, |amount| amount.e8s().saturating_sub(paid));
+        if e8s > refundable {
+            return Err(MultisigIndexError::RefundUnavailable(format!(
+                "At most {} e8s can be refunded for this claim",
+                refundable
+            )));
+        }
+        Ok(())
+    }
+
+    // The fee is only transferred when a spawn completes, so a paid out spawn gives it back
+    fn refund_fee(claim: &RefundClaim) {
+        if let Some(catalyze_fee) = claim.catalyze_fee {
+            // Crediting a principal never fails
+            let _ = Journal::record(
+                JournalEntryKind::Refund,
+                JournalAccount::FeeRecipient,
+                JournalAccount::Principal(claim.claimant),
+                catalyze_fee,
+                Some(claim.icp_block_index),
+            );
+        }
+    }
+
+    fn record_paid_out(claim: &RefundClaim, event: Event) {
+        Events::record(Event {
+            principal: Some(claim.claimant),
+            group_identifier: Some(claim.group_identifier),
+            icp_block_index: Some(claim.icp_block_index),
+            ..event
+        });
+    }
+
+    fn filter_claims(matches: impl Fn(&RefundClaim) -> bool) -> Vec<RefundClaim> {
+        REFUND_CLAIMS.with(|r| {
+            r.borrow()
+                .iter()
+                .filter_map(|(_, claim)| claim.valid())
+                .filter(|claim| matches(claim))
+                .collect()
+        })
+    }
+
+    fn insert_claim(mut claim: RefundClaim) {
+        claim.updated_at = time();
+        REFUND_CLAIMS.with(|r| r.borrow_mut().insert(claim.icp_block_index, claim.into()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn approve_is_capped_by_what_was_sent_to_the_cmc() {
+        // A deposit of 5 ICP paid a spin up price of 1 ICP, the rest stayed on the local balance
+        let claim = RefundClaim {
+            icp_block_index: 1,
+            group_identifier: Principal::anonymous(),
+            claimant: Principal::anonymous(),
+            icp_amount: Some(Tokens::from_e8s(500_000_000)),
+            cmc_amount: Some(Tokens::from_e8s(89_990_000)),
+            catalyze_fee: Some(Tokens::from_e8s(10_000_000)),
+            cycles: None,
+            canister_id: None,
+            status: RefundStatus::PendingReview,
+            method: Some(RefundMethod::Icp),
+            error_message: None,
+            review_note: None,
+            paid_e8s: None,
+            created_at: 0,
+            updated_at: 0,
+        };
+
+        assert_eq!(Refunds::check_refundable(&claim, 89_990_000), Ok(()));
+        assert!(matches!(
+            Refunds::check_refundable(&claim, 89_990_001),
+            Err(MultisigIndexError::RefundUnavailable(_))
+        ));
+        assert!(Refunds::check_refundable(&claim, 500_000_000).is_err());
+
+        let unknown = RefundClaim {
+            cmc_amount: None,
+            ..claim
+        };
+        assert!(Refunds::check_refundable(&unknown, 1).is_err());
+    }
+}
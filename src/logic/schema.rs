@@ -0,0 +1,122 @@
+use ic_stable_structures::{Memory, StableBTreeMap, Storable};
+
+use crate::rust_declarations::{
+    stored::{Stored, Versioned},
+    types::{CorruptEntry, StorageReport},
+};
+
+use super::{
+    indexes::Indexes,
+    store::{
+        ENTRIES, EVENTS, FEE_PAYOUTS, INITIALIZING, JOURNAL, OUTGOING_TRANSFERS, PAYMENT_PULLS,
+        PREPAID_BALANCES, REFUND_CLAIMS, SCHEMA_VERSION, SPAWNS, STATUS_HISTORY, TRANSACTIONS,
+        UPGRADES, WASM_VERSIONS,
+    },
+};
+
+struct Migration {
+    version: u64,
+    run: fn(),
+}
+
+// Records are migrated lazily, an older version is decoded by `Versioned::decode_version` when it is read and
+// stored in the latest version on the next write. A migration is only added for data that has to change
+// at once, it runs a single time and new migrations are appended with the next version
+static MIGRATIONS: &[Migration] = &[
+    // Fill the secondary indexes, canisters that already did this before the migrations existed skip it
+    Migration {
+        version: 1,
+        run: Indexes::backfill,
+    },
+];
+
+pub struct Schema;
+
+impl Schema {
+    pub fn run_migrations() {
+        let current = Self::get_version();
+        for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+            (migration.run)();
+            SCHEMA_VERSION
+                .with(|s| s.borrow_mut().set(migration.version))
+                .expect("Failed to store the schema version");
+        }
+    }
+
+    pub fn get_version() -> u64 {
+        SCHEMA_VERSION.with(|s| *s.borrow().get())
+    }
+
+    pub fn get_storage_report() -> StorageReport {
+        let mut corrupt_entries = vec![];
+        ENTRIES.with(|e| corrupt_entries.extend(Self::corrupt_entries("multisigs", &e.borrow())));
+        TRANSACTIONS
+            .with(|t| corrupt_entries.extend(Self::corrupt_entries("transactions", &t.borrow())));
+        INITIALIZING
+            .with(|i| corrupt_entries.extend(Self::corrupt_entries("initializing", &i.borrow())));
+        SPAWNS.with(|s| corrupt_entries.extend(Self::corrupt_entries("spawns", &s.borrow())));
+        UPGRADES.with(|u| corrupt_entries.extend(Self::corrupt_entries("upgrades", &u.borrow())));
+        WASM_VERSIONS
+            .with(|w| corrupt_entries.extend(Self::corrupt_entries("wasm_versions", &w.borrow())));
+        JOURNAL.with(|j| corrupt_entries.extend(Self::corrupt_entries("journal", &j.borrow())));
+        STATUS_HISTORY
+            .with(|s| corrupt_entries.extend(Self::corrupt_entries("status_history", &s.borrow())));
+        PREPAID_BALANCES.with(|p| {
+            corrupt_entries.extend(Self::corrupt_entries("prepaid_balances", &p.borrow()))
+        });
+        REFUND_CLAIMS
+            .with(|r| corrupt_entries.extend(Self::corrupt_entries("refund_claims", &r.borrow())));
+        FEE_PAYOUTS
+            .with(|f| corrupt_entries.extend(Self::corrupt_entries("fee_payouts", &f.borrow())));
+        OUTGOING_TRANSFERS.with(|o| {
+            corrupt_entries.extend(Self::corrupt_entries("outgoing_transfers", &o.borrow()))
+        });
+        PAYMENT_PULLS
+            .with(|p| corrupt_entries.extend(Self::corrupt_entries("payment_pulls", &p.borrow())));
+
+        EVENTS.with(|e| {
+            corrupt_entries.extend(
+                e.borrow()
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(id, event)| Self::corrupt_entry("events", id.to_string(), event)),
+            )
+        });
+
+        StorageReport {
+            schema_version: Self::get_version(),
+            corrupt_entries,
+        }
+    }
+
+    fn corrupt_entries<K, V, M>(
+        map_name: &str,
+        map: &StableBTreeMap<K, Stored<V>, M>,
+    ) -> Vec<CorruptEntry>
+    where
+        K: Storable + Ord + Clone + ToString,
+        V: Versioned,
+        M: Memory,
+    {
+        map.iter()
+            .filter_map(|(key, value)| Self::corrupt_entry(map_name, key.to_string(), value))
+            .collect()
+    }
+
+    fn corrupt_entry<V>(map_name: &str, key: String, value: Stored<V>) -> Option<CorruptEntry> {
+        match value {
+            Stored::Valid(_) => None,
+            Stored::Corrupt {
+                version,
+                error,
+                bytes,
+            } => Some(CorruptEntry {
+                map: map_name.to_string(),
+                key,
+                version,
+                error,
+                size: bytes.len() as u64,
+            }),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, convert::TryFrom};
+use std::{cell::RefCell, collections::BTreeSet, convert::TryFrom};
 
 use candid::{Encode, Nat, Principal};
 use ic_cdk::{
@@ -16,40 +16,65 @@ use ic_cdk::{
 };
 use ic_ledger_types::{
     account_balance, AccountBalanceArgs, AccountIdentifier, Memo, Subaccount, Tokens, TransferArgs,
-    DEFAULT_SUBACCOUNT, MAINNET_CYCLES_MINTING_CANISTER_ID, MAINNET_LEDGER_CANISTER_ID,
+    DEFAULT_SUBACCOUNT,
 };
 use ic_stable_structures::{
     memory_manager::{MemoryId, MemoryManager, VirtualMemory},
-    {DefaultMemoryImpl, StableBTreeMap},
+    {DefaultMemoryImpl, StableBTreeMap, StableCell, StableLog},
 };
 
-use crate::rust_declarations::types::{
-    InitializeStatus, MultisigData, TransactionData, TransactionStatus, UpdateIcpBalanceArgs,
+use crate::rust_declarations::{
+    stored::Stored,
+    types::{
+        CanisterCreation, CanisterStatusHistory, CycleTotals, Event, EventKind, FeePayout,
+        GetMultisigsArgs, GetTransactionsArgs, IndexConfig, InitializeStatus, JournalAccount,
+        JournalEntry, JournalEntryKind, MultisigData, MultisigIndexError, MultisigUpgradeData,
+        MultisigsPage, OutgoingTransfer, OutgoingTransferStatus, PaymentPull, PrepaidBalance,
+        RefundClaim, SpawnData, SpawnPrice, SpawnStep, TransactionData, TransactionStatus,
+        TransactionValidationError, TransactionsPage, WasmData,
+    },
 };
 
-use super::{cmc::CMC, ledger::Ledger};
+use super::{
+    cmc::CMC,
+    config::Config,
+    cycles::Cycles,
+    events::Events,
+    fees::Fees,
+    indexes::Indexes,
+    journal::Journal,
+    ledger::Ledger,
+    lock::{BlockLock, PrincipalLock},
+    pagination::Pagination,
+    pricing::Pricing,
+    refunds::Refunds,
+    transfers::Transfers,
+    wasm::Wasm,
+};
 
 type Memory = VirtualMemory<DefaultMemoryImpl>;
 
 pub static MEMO_TOP_UP_CANISTER: Memo = Memo(1347768404_u64);
 pub static MEMO_CREATE_CANISTER: Memo = Memo(1095062083_u64);
 pub static ICP_TRANSACTION_FEE: Tokens = Tokens::from_e8s(10000);
+// Defaults used when no value is passed in the init arguments
 pub static MIN_E8S_FOR_SPINUP: Tokens = Tokens::from_e8s(110000000);
 pub static CATALYZE_E8S_FEE: Tokens = Tokens::from_e8s(10000000);
 pub static CATALYZE_MULTI_SIG: &str = "fcygz-gqaaa-aaaap-abpaa-cai";
+pub static MULTISIG_WASM: &[u8] = include_bytes!("../../wasm/multisig.wasm.gz");
 
 thread_local! {
     static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> =
         RefCell::new(MemoryManager::init(DefaultMemoryImpl::default()));
 
 
-    pub static ENTRIES: RefCell<StableBTreeMap<String, MultisigData, Memory>> = RefCell::new(
+    pub static ENTRIES: RefCell<StableBTreeMap<String, Stored<MultisigData>, Memory>> = RefCell::new(
         StableBTreeMap::init(
             MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(0))),
         )
     );
 
-    pub static TRANSACTIONS: RefCell<StableBTreeMap<u64, TransactionData, Memory>> = RefCell::new(
+    pub static TRANSACTIONS: RefCell<StableBTreeMap<u64, Stored<TransactionData>, Memory>> = RefCell::new(
         StableBTreeMap::init(
             MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(1))),
         )
@@ -61,42 +86,238 @@ thread_local! {
         )
     );
 
-    pub static INITIALIZING: RefCell<StableBTreeMap<String, InitializeStatus, Memory>> = RefCell::new(
+    pub static INITIALIZING: RefCell<StableBTreeMap<String, Stored<InitializeStatus>, Memory>> = RefCell::new(
         StableBTreeMap::init(
             MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(3))),
         )
     );
+
+    pub static SPAWNS: RefCell<StableBTreeMap<String, Stored<SpawnData>, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(4))),
+        )
+    );
+
+    pub static UPGRADES: RefCell<StableBTreeMap<String, Stored<MultisigUpgradeData>, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(5))),
+        )
+    );
+
+    pub static WASM_VERSIONS: RefCell<StableBTreeMap<String, Stored<WasmData>, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(6))),
+        )
+    );
+
+    pub static WASM_MODULES: RefCell<StableBTreeMap<String, Vec<u8>, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(7))),
+        )
+    );
+
+    pub static WASM_CHUNKS: RefCell<StableBTreeMap<String, Vec<u8>, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(8))),
+        )
+    );
+
+    pub static DEFAULT_WASM_VERSION: RefCell<StableCell<String, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(9))),
+            String::new(),
+        ).expect("Failed to initialize the default wasm version")
+    );
+
+    pub static CONFIG: RefCell<StableCell<IndexConfig, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(10))),
+            IndexConfig::default(),
+        ).expect("Failed to initialize the config")
+    );
+
+    pub static ADMINS: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(11))),
+        )
+    );
+
+    pub static JOURNAL: RefCell<StableBTreeMap<u64, Stored<JournalEntry>, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(12))),
+        )
+    );
+
+    pub static STATUS_HISTORY: RefCell<StableBTreeMap<String, Stored<CanisterStatusHistory>, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(13))),
+        )
+    );
+
+    pub static PREPAID_BALANCES: RefCell<StableBTreeMap<String, Stored<PrepaidBalance>, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(14))),
+        )
+    );
+
+    // group identifier -> canister id
+    pub static GROUP_INDEX: RefCell<StableBTreeMap<String, String, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(15))),
+        )
+    );
+
+    // created by:canister id -> canister id
+    pub static CREATOR_INDEX: RefCell<StableBTreeMap<String, String, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(16))),
+        )
+    );
+
+    // initialized by:block index -> block index
+    pub static INITIALIZER_INDEX: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(17))),
+        )
+    );
+
+    pub static INDEXES_BACKFILLED: RefCell<StableCell<u64, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(18))),
+            0,
+        ).expect("Failed to initialize the backfill flag")
+    );
+
+    // icp block index -> refund claim of the failed spawn paid with it
+    pub static REFUND_CLAIMS: RefCell<StableBTreeMap<u64, Stored<RefundClaim>, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(20))),
+        )
+    );
+
+    // icp block index -> Catalyze fee payout of the spawn paid with it, see `Fees`
+    pub static FEE_PAYOUTS: RefCell<StableBTreeMap<u64, Stored<FeePayout>, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(22))),
+        )
+    );
+
+    // transfer id -> outgoing ledger transfer, see `Transfers`
+    pub static OUTGOING_TRANSFERS: RefCell<StableBTreeMap<String, Stored<OutgoingTransfer>, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(21))),
+        )
+    );
+
+    // pull id -> ICRC-2 pull of a spin up price, see `Transfers`
+    pub static PAYMENT_PULLS: RefCell<StableBTreeMap<String, Stored<PaymentPull>, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(26))),
+        )
+    );
+
+    // Append-only log of every state change, see `Events`
+    pub static EVENTS: RefCell<StableLog<Stored<Event>, Memory, Memory>> = RefCell::new(
+        StableLog::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(23))),
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(24))),
+        ).expect("Failed to initialize the event log")
+    );
+
+    // Cycles that moved in and out of this canister, see `Cycles`
+    pub static CYCLE_TOTALS: RefCell<StableCell<Stored<CycleTotals>, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(25))),
+            CycleTotals::default().into(),
+        ).expect("Failed to initialize the cycle totals")
+    );
+
+    // The last migration that ran, see `Schema`
+    pub static SCHEMA_VERSION: RefCell<StableCell<u64, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(19))),
+            0,
+        ).expect("Failed to initialize the schema version")
+    );
 }
 
 pub struct Store;
 
 impl Store {
+    // Saturates at `u64::MAX`, see `Cycles::get_report` for the full balance
     pub fn get_cycles() -> u64 {
-        ic_cdk::api::canister_balance()
+        u64::try_from(ic_cdk::api::canister_balance128()).unwrap_or(u64::MAX)
     }
 
-    pub fn get_multisigs() -> Vec<MultisigData> {
-        ENTRIES.with(|e| e.borrow().iter().map(|(_, v)| v.clone()).collect())
+    pub fn get_multisigs(args: GetMultisigsArgs) -> MultisigsPage {
+        let page = ENTRIES.with(|e| {
+            Pagination::get_page(
+                &e.borrow(),
+                args.start.map(|start| start.to_string()),
+                args.limit,
+                args.direction,
+                |multisig| {
+                    args.created_by
+                        .is_none_or(|created_by| multisig.created_by == created_by)
+                        && args
+                            .created_after
+                            .is_none_or(|after| multisig.created_at >= after)
+                        && args
+                            .created_before
+                            .is_none_or(|before| multisig.created_at < before)
+                        && args
+                            .has_group_identifier
+                            .is_none_or(|has_group_identifier| {
+                                multisig.group_identifier.is_some() == has_group_identifier
+                            })
+                },
+            )
+        });
+
+        MultisigsPage {
+            multisigs: page.items,
+            next: page.next.and_then(|key| Principal::from_text(key).ok()),
+            total: page.total,
+        }
     }
 
     pub fn get_multisig_by_group_identifier(group_identifier: Principal) -> Option<MultisigData> {
+        let canister_id = Indexes::get_canister_by_group_identifier(group_identifier)?;
+        ENTRIES
+            .with(|e| e.borrow().get(&canister_id.to_string()))
+            .and_then(Stored::valid)
+    }
+
+    pub fn get_multisigs_by_creator(created_by: Principal) -> Vec<MultisigData> {
         ENTRIES.with(|e| {
-            e.borrow()
-                .iter()
-                .find(|(_, v)| {
-                    if let Some(_group_identifier) = v.group_identifier.clone() {
-                        _group_identifier == group_identifier
-                    } else {
-                        false
-                    }
-                })
-                .map(|(_, v)| v.clone())
+            let entries = e.borrow();
+            Indexes::get_canisters_by_creator(created_by)
+                .into_iter()
+                .filter_map(|canister_id| entries.get(&canister_id.to_string()))
+                .filter_map(Stored::valid)
+                .collect()
         })
     }
 
-    pub async fn get_icp_balance(caller: Principal) -> Result<u64, String> {
+    pub fn get_transactions_by_initializer(initialized_by: Principal) -> Vec<TransactionData> {
+        TRANSACTIONS.with(|t| {
+            let transactions = t.borrow();
+            Indexes::get_blocks_by_initializer(initialized_by)
+                .into_iter()
+                .filter_map(|block_index| transactions.get(&block_index))
+                .filter_map(Stored::valid)
+                .collect()
+        })
+    }
+
+    // Every change to a multisig goes through here so the indexes stay in sync
+    pub fn insert_multisig(multisig: MultisigData) {
+        Indexes::insert_multisig(multisig);
+    }
+
+    pub async fn get_icp_balance(caller: Principal) -> Result<u64, MultisigIndexError> {
         let result = account_balance(
-            MAINNET_LEDGER_CANISTER_ID,
+            Config::ledger_canister_id(),
             AccountBalanceArgs {
                 account: AccountIdentifier::new(&caller, &DEFAULT_SUBACCOUNT),
             },
@@ -105,7 +326,7 @@ impl Store {
 
         match result {
             Ok(balance) => Ok(balance.e8s()),
-            Err((_, err)) => Err(err),
+            Err(err) => Err(err.into()),
         }
     }
 
@@ -120,39 +341,254 @@ impl Store {
         TRANSACTIONS.with(|t| {
             t.borrow()
                 .iter()
-                .filter(|(_, v)| {
-                    if let Some(_status) = status.clone() {
-                        v.status == _status
-                    } else {
-                        true
-                    }
+                .filter_map(|(_, v)| v.valid())
+                .filter(|v| match &status {
+                    Some(status) => &v.status == status,
+                    None => true,
                 })
-                .map(|(_, v)| v.clone())
                 .collect()
         })
     }
 
+    pub fn get_transactions_page(args: GetTransactionsArgs) -> TransactionsPage {
+        let page = TRANSACTIONS.with(|t| {
+            Pagination::get_page(
+                &t.borrow(),
+                args.start,
+                args.limit,
+                args.direction,
+                |transaction| {
+                    args.initialized_by
+                        .is_none_or(|initialized_by| transaction.initialized_by == initialized_by)
+                        && args
+                            .statuses
+                            .as_ref()
+                            .is_none_or(|statuses| statuses.contains(&transaction.status))
+                        && args
+                            .created_after
+                            .is_none_or(|after| transaction.created_at >= after)
+                        && args
+                            .created_before
+                            .is_none_or(|before| transaction.created_at < before)
+                        && args
+                            .from_block
+                            .is_none_or(|from| transaction.icp_transfer_block_index >= from)
+                        && args
+                            .to_block
+                            .is_none_or(|to| transaction.icp_transfer_block_index <= to)
+                },
+            )
+        });
+
+        TransactionsPage {
+            transactions: page.items,
+            next: page.next,
+            total: page.total,
+        }
+    }
+
+    pub fn get_transaction(icp_block_index: u64) -> Option<TransactionData> {
+        TRANSACTIONS
+            .with(|t| t.borrow().get(&icp_block_index))
+            .and_then(Stored::valid)
+    }
+
+    // Only call this while holding the `BlockLock` of the block, a reserved block that is not locked
+    // was left behind by a call that trapped and can be processed again
     pub fn is_valid_block(block_index: u64) -> bool {
-        TRANSACTIONS.with(|t| {
-            if let Some(transaction) = t.borrow().get(&block_index) {
-                return match transaction.status {
-                    TransactionStatus::IcpToCmcFailed => true,
-                    TransactionStatus::InsufficientIcp => true,
-                    _ => false,
-                };
-            } else {
-                return true;
+        TRANSACTIONS.with(|t| match t.borrow().get(&block_index) {
+            Some(Stored::Valid(transaction)) => matches!(
+                transaction.status,
+                TransactionStatus::IcpToCmcFailed
+                    | TransactionStatus::InsufficientIcp
+                    | TransactionStatus::Reserved
+            ),
+            // The block was used before, it can not be trusted until the record is repaired
+            Some(Stored::Corrupt { .. }) => false,
+            None => true,
+        })
+    }
+
+    // Mint the cycles of a spawn that creates its canister from the cycles of this canister
+    pub async fn top_up_self(spawn: &SpawnData) -> Result<Nat, MultisigIndexError> {
+        let caller = spawn.created_by;
+        let icp_block_index = spawn.icp_block_index;
+
+        // Both locks are held until the transaction reached a final state for this call
+        let _block_lock = BlockLock::acquire(icp_block_index)?;
+        let _principal_lock = PrincipalLock::acquire(caller)?;
+
+        // The ICP already reached the CMC when only the notify call failed, so only the notify call is retried.
+        // Cycles that were minted by an earlier attempt are used as they are
+        if let Some((transaction, cmc_block_index)) = Self::get_unused_top_up(spawn)? {
+            return match (
+                transaction.status.clone(),
+                transaction.cycles_amount.clone(),
+            ) {
+                (TransactionStatus::Success, Some(cycles)) => Ok(cycles),
+                _ => Self::notify_top_up(caller, transaction, cmc_block_index).await,
+            };
+        }
+
+        let (transaction_data, price) =
+            Self::accept_payment(caller, icp_block_index, spawn.price.clone()).await?;
+        Self::icp_to_cycles(caller, transaction_data, price).await
+    }
+
+    // The transaction of the spawn when its ICP was sent to the CMC for a top up of this canister and the
+    // cycles were not used yet. A block can be given to more than one spawn, so the cycles are not available
+    // when another spawn of the block got them or the payment was refunded
+    fn get_unused_top_up(
+        spawn: &SpawnData,
+    ) -> Result<Option<(TransactionData, u64)>, MultisigIndexError> {
+        let transaction = match Self::get_transaction(spawn.icp_block_index) {
+            Some(transaction) => transaction,
+            None => return Ok(None),
+        };
+        let cmc_block_index = match transaction.cmc_transfer_block_index {
+            Some(cmc_block_index) => cmc_block_index,
+            None => return Ok(None),
+        };
+
+        let unused = match transaction.status {
+            TransactionStatus::CyclesToIndexFailed => {
+                !matches!(transaction.creation, Some(CanisterCreation::Cmc { .. }))
             }
+            // Transactions stored before the creation was recorded were used by a spawn without steps
+            TransactionStatus::Success => {
+                transaction.creation == Some(CanisterCreation::TopUp)
+                    && transaction.cycles_amount.is_some()
+            }
+            _ => false,
+        };
+        if !unused {
+            return Ok(None);
+        }
+
+        if transaction.initialized_by != spawn.created_by {
+            return Err(MultisigIndexError::InvalidTransaction(
+                TransactionValidationError::InvalidSender,
+            ));
+        }
+        Refunds::check_can_resume(spawn.icp_block_index)?;
+
+        if Self::is_topped_up_by_other_spawn(spawn) {
+            return Err(MultisigIndexError::BlockAlreadyProcessed(
+                spawn.icp_block_index,
+            ));
+        }
+
+        Ok(Some((transaction, cmc_block_index)))
+    }
+
+    // Another spawn of the same block got the cycles of the payment
+    pub fn is_topped_up_by_other_spawn(spawn: &SpawnData) -> bool {
+        SPAWNS.with(|s| {
+            s.borrow()
+                .iter()
+                .filter_map(|(_, other)| other.valid())
+                .any(|other| {
+                    other.icp_block_index == spawn.icp_block_index
+                        && other.group_identifier != spawn.group_identifier
+                        && other.completed_steps.contains(&SpawnStep::ToppedUp)
+                })
         })
     }
 
-    pub async fn top_up_self(caller: Principal, icp_block_index: u64) -> Result<Nat, String> {
+    // Create the canister of a spawn through the CMC with the ICP of the block, the created canister is
+    // controlled by this canister
+    pub async fn create_canister_with_payment(
+        caller: Principal,
+        icp_block_index: u64,
+        subnet_type: Option<String>,
+    ) -> Result<Principal, MultisigIndexError> {
+        let _block_lock = BlockLock::acquire(icp_block_index)?;
+        let _principal_lock = PrincipalLock::acquire(caller)?;
+
+        // The ICP already reached the CMC when only the notify call failed, so only the notify call is retried
+        if let Some(transaction) = Self::get_transaction(icp_block_index) {
+            if let (
+                TransactionStatus::CyclesToIndexFailed,
+                Some(cmc_block_index),
+                Some(CanisterCreation::Cmc { subnet_type }),
+            ) = (
+                transaction.status.clone(),
+                transaction.cmc_transfer_block_index,
+                transaction.creation.clone(),
+            ) {
+                if transaction.initialized_by != caller {
+                    return Err(MultisigIndexError::InvalidTransaction(
+                        TransactionValidationError::InvalidSender,
+                    ));
+                }
+                return Self::notify_create_canister(transaction, cmc_block_index, subnet_type)
+                    .await;
+            }
+        }
+
+        let (transaction_data, price) = Self::accept_payment(caller, icp_block_index, None).await?;
+        Self::icp_to_canister(caller, transaction_data, price, subnet_type).await
+    }
+
+    // Add the ICP of the block to the callers balance when it was not added before and check that the balance
+    // covers the spin up price, the caller has to hold the locks of the block and the caller. A pulled payment
+    // passes the price it was quoted for, otherwise the current price is used
+    async fn accept_payment(
+        caller: Principal,
+        icp_block_index: u64,
+        quoted_price: Option<SpawnPrice>,
+    ) -> Result<(TransactionData, SpawnPrice), MultisigIndexError> {
         // check if the block is already used
         if !Self::is_valid_block(icp_block_index) {
-            return Err("Transaction already processed".to_string());
+            return Err(MultisigIndexError::BlockAlreadyProcessed(icp_block_index));
+        }
+
+        let validated = Self::get_transaction(icp_block_index)
+            .filter(|transaction| transaction.icp_amount.is_some());
+
+        let mut transaction_data = match validated {
+            // A block that was validated before is already added to the callers balance
+            Some(transaction) => {
+                if transaction.initialized_by != caller {
+                    return Err(MultisigIndexError::InvalidTransaction(
+                        TransactionValidationError::InvalidSender,
+                    ));
+                }
+                transaction
+            }
+            None => Self::claim_block(caller, icp_block_index).await?,
+        };
+
+        let price = match quoted_price {
+            Some(price) => price,
+            None => Pricing::get_current_spawn_price().await?,
+        };
+
+        // The transferred amount is already part of the local balance, together with any previous balance
+        // it should cover the price of a spin up. A transfer to the CMC with an unknown outcome was already charged
+        let available = Tokens::from_e8s(Self::get_caller_local_icp_balance(caller));
+        if available < price.total_e8s
+            && !Transfers::is_unresolved(&Transfers::spawn_payment_id(icp_block_index))
+        {
+            let err = MultisigIndexError::InsufficientIcp {
+                required: price.total_e8s,
+                available,
+            };
+            transaction_data.status = TransactionStatus::InsufficientIcp;
+            transaction_data.error_message = Some(err.to_string());
+            Self::insert_transaction_data(icp_block_index, transaction_data);
+            return Err(err);
         }
 
-        // initialize a base transaction data object where the field are set per case
+        Ok((transaction_data, price))
+    }
+
+    // Reserve the block before the ledger is called so a concurrent call cannot use it, then validate the
+    // transaction done from the user to this canister and add the amount to the callers balance
+    async fn claim_block(
+        caller: Principal,
+        icp_block_index: u64,
+    ) -> Result<TransactionData, MultisigIndexError> {
         let mut transaction_data = TransactionData {
             icp_transfer_block_index: icp_block_index,
             cmc_transfer_block_index: None,
@@ -160,179 +596,604 @@ impl Store {
             cycles_amount: None,
             initialized_by: caller,
             created_at: time(),
-            status: TransactionStatus::Pending,
+            status: TransactionStatus::Reserved,
             error_message: None,
+            attempts: None,
+            last_attempt_at: None,
+            catalyze_fee: None,
+            creation: None,
         };
+        Self::insert_transaction_data(icp_block_index, transaction_data.clone());
 
-        // validate the transaction done from the user to this canister and return the amount
         match Ledger::validate_transaction(caller, icp_block_index).await {
-            // If the transaction from the user to this canister is valid, return the amount
             Ok(amount) => {
-                // add the amount to the callers balance
-                Self::update_caller_icp_balance(&caller, UpdateIcpBalanceArgs::Add(amount));
-
-                // Check if the transfer amount is lower as the minimum amount needed to spin up a canister
-                if amount < MIN_E8S_FOR_SPINUP {
-                    // In case the transfer amount is to low, check if the caller has enough previous balance to spin up a canister
-                    let prev_amount = Tokens::from_e8s(Self::get_caller_local_icp_balance(caller));
-
-                    // if the transfered amount + the previous balance is still to low, return an error
-                    if (amount + prev_amount) < MIN_E8S_FOR_SPINUP {
-                        transaction_data.icp_amount = Some(amount);
-                        transaction_data.status = TransactionStatus::InsufficientIcp;
-                        transaction_data.error_message =
-                            Some("Amount too low to spin up a canister".to_string());
-                        Self::insert_transaction_data(icp_block_index, transaction_data);
-                        return Err("Amount too low to spin up a canister".to_string());
-                    }
-                }
+                Journal::record(
+                    JournalEntryKind::Deposit,
+                    JournalAccount::Ledger,
+                    JournalAccount::Principal(caller),
+                    amount,
+                    Some(icp_block_index),
+                )?;
+                transaction_data.icp_amount = Some(amount);
+                Self::insert_transaction_data(icp_block_index, transaction_data.clone());
+                Events::record(Event {
+                    principal: Some(caller),
+                    icp_block_index: Some(icp_block_index),
+                    amount: Some(amount),
+                    ..Event::new(EventKind::PaymentAccepted)
+                });
+                Ok(transaction_data)
+            }
+            Err(err) => {
+                // Release the block, it was never added to a balance
+                Indexes::remove_transaction(icp_block_index);
+                Err(err)
+            }
+        }
+    }
+
+    // Pass the spin up amount from this canister to the cycles management canister and mint the cycles for this canister,
+    // the transaction data is stored with the status of the last step that was reached.
+    // The caller has to hold the `BlockLock` of the transaction and the `PrincipalLock` of the initializer
+    pub async fn icp_to_cycles(
+        caller: Principal,
+        mut transaction_data: TransactionData,
+        price: SpawnPrice,
+    ) -> Result<Nat, MultisigIndexError> {
+        transaction_data.creation = Some(CanisterCreation::TopUp);
+
+        let cmc_block_index =
+            Self::transfer_to_cmc(caller, &mut transaction_data, &price, MEMO_TOP_UP_CANISTER)
+                .await?;
+
+        Self::notify_top_up(caller, transaction_data, cmc_block_index).await
+    }
+
+    // Trigger the call to send the cycles to this canister, the CMC returns the same cycles for a block that
+    // was already used
+    async fn notify_top_up(
+        caller: Principal,
+        mut transaction_data: TransactionData,
+        cmc_block_index: u64,
+    ) -> Result<Nat, MultisigIndexError> {
+        let icp_block_index = transaction_data.icp_transfer_block_index;
 
-                let catalyze_amount = CATALYZE_E8S_FEE - ICP_TRANSACTION_FEE;
-                let multisig_amount = MIN_E8S_FOR_SPINUP - ICP_TRANSACTION_FEE - catalyze_amount;
-
-                // Create the ledger arguments needed for the transfer call to the ledger canister
-                let multig_spinup_ledger_args = TransferArgs {
-                    memo: MEMO_TOP_UP_CANISTER,
-                    amount: multisig_amount,
-                    fee: ICP_TRANSACTION_FEE,
-                    from_subaccount: None,
-                    to: AccountIdentifier::new(
-                        &MAINNET_CYCLES_MINTING_CANISTER_ID,
-                        &Subaccount::from(id()),
-                    ),
-                    created_at_time: None,
-                };
-
-                // Pass the amount received from the user, from this canister to the cycles management canister (minus the fee)
-                match Ledger::transfer_icp(multig_spinup_ledger_args).await {
-                    // If the transaction is successfull, return the block index of the transaction
-                    Ok(cmc_block_index) => {
-                        // subtract the amount to the callers balance
-                        Self::update_caller_icp_balance(
-                            &caller,
-                            UpdateIcpBalanceArgs::Subtract(amount),
-                        );
-                        // Trigger the call to send the cycles to this canister
-                        match CMC::top_up_self(cmc_block_index).await {
-                            Ok(cycles) => {
-                                transaction_data.cmc_transfer_block_index = Some(cmc_block_index);
-                                transaction_data.icp_amount = Some(amount);
-                                transaction_data.cycles_amount = Some(cycles.clone());
-                                transaction_data.status = TransactionStatus::Success;
-
-                                Self::insert_transaction_data(icp_block_index, transaction_data);
-                                Ok(cycles)
-                            }
-                            Err(err) => {
-                                // if this step fails, the topup needs to be triggered manually with the cmc_block_index
-                                transaction_data.cmc_transfer_block_index = Some(cmc_block_index);
-                                transaction_data.icp_amount = Some(amount);
-                                transaction_data.status = TransactionStatus::CyclesToIndexFailed;
-                                transaction_data.error_message = Some(err.clone());
-                                Self::insert_transaction_data(icp_block_index, transaction_data);
-                                Err(err)
-                            }
-                        }
-                    }
-                    Err(err) => {
-                        transaction_data.icp_amount = Some(amount);
-                        transaction_data.status = TransactionStatus::IcpToCmcFailed;
-                        transaction_data.error_message = Some(err.clone());
-                        Self::insert_transaction_data(icp_block_index, transaction_data);
-
-                        Err(err.to_string())
-                    }
+        match CMC::top_up_self(cmc_block_index).await {
+            Ok(cycles) => {
+                Cycles::record_minted(&cycles);
+                transaction_data.cycles_amount = Some(cycles.clone());
+                transaction_data.status = TransactionStatus::Success;
+                transaction_data.error_message = None;
+
+                Self::insert_transaction_data(icp_block_index, transaction_data);
+                Events::record(Event {
+                    principal: Some(caller),
+                    icp_block_index: Some(icp_block_index),
+                    block_index: Some(cmc_block_index),
+                    cycles: Some(cycles.clone()),
+                    ..Event::new(EventKind::CyclesMinted)
+                });
+                Ok(cycles)
+            }
+            Err(err) => {
+                // if this step fails, the topup is retried with the cmc_block_index when the spawn is resumed,
+                // or by the reconciliation timer for a transaction without a spawn
+                transaction_data.status = TransactionStatus::CyclesToIndexFailed;
+                transaction_data.error_message = Some(err.to_string());
+                Self::insert_transaction_data(icp_block_index, transaction_data);
+                Err(err)
+            }
+        }
+    }
+
+    // Pass the spin up amount to the cycles management canister and let it create the canister directly,
+    // the caller has to hold the `BlockLock` of the transaction and the `PrincipalLock` of the initializer
+    async fn icp_to_canister(
+        caller: Principal,
+        mut transaction_data: TransactionData,
+        price: SpawnPrice,
+        subnet_type: Option<String>,
+    ) -> Result<Principal, MultisigIndexError> {
+        transaction_data.creation = Some(CanisterCreation::Cmc {
+            subnet_type: subnet_type.clone(),
+        });
+
+        let cmc_block_index =
+            Self::transfer_to_cmc(caller, &mut transaction_data, &price, MEMO_CREATE_CANISTER)
+                .await?;
+
+        Self::notify_create_canister(transaction_data, cmc_block_index, subnet_type).await
+    }
+
+    async fn notify_create_canister(
+        mut transaction_data: TransactionData,
+        cmc_block_index: u64,
+        subnet_type: Option<String>,
+    ) -> Result<Principal, MultisigIndexError> {
+        let icp_block_index = transaction_data.icp_transfer_block_index;
+
+        match CMC::create_canister(cmc_block_index, subnet_type).await {
+            Ok(canister_id) => {
+                transaction_data.status = TransactionStatus::Success;
+                transaction_data.error_message = None;
+                Self::insert_transaction_data(icp_block_index, transaction_data);
+                Ok(canister_id)
+            }
+            Err(err) => {
+                // The notify call is retried when the spawn is resumed, the CMC returns the same canister
+                // for a block that was already used
+                transaction_data.status = TransactionStatus::CyclesToIndexFailed;
+                transaction_data.error_message = Some(err.to_string());
+                Self::insert_transaction_data(icp_block_index, transaction_data);
+                Err(err)
+            }
+        }
+    }
+
+    // Charge the price to the callers balance and pass the spin up amount to the CMC with the given memo,
+    // when the ledger rejects the transfer the price is refunded and the transaction is stored as failed
+    async fn transfer_to_cmc(
+        caller: Principal,
+        transaction_data: &mut TransactionData,
+        price: &SpawnPrice,
+        memo: Memo,
+    ) -> Result<u64, MultisigIndexError> {
+        let icp_block_index = transaction_data.icp_transfer_block_index;
+        let transfer_id = Transfers::spawn_payment_id(icp_block_index);
+
+        let (spawn_amount, catalyze_fee) = match Transfers::get_unresolved_transfer(&transfer_id) {
+            // The outcome of the previous transfer is unknown, it was charged and is sent again as it was
+            Some(pending) => (
+                pending.args.amount + pending.args.fee,
+                transaction_data.catalyze_fee.unwrap_or(price.catalyze_fee),
+            ),
+            None => {
+                // The spin up price covers the Catalyze fee, the rest is sent to the CMC
+                let spawn_amount = price.total_e8s - price.catalyze_fee;
+                // Charge the price to the callers balance before the call, so it cannot be spent twice
+                Self::charge_spawn(caller, icp_block_index, spawn_amount, price.catalyze_fee)?;
+                // The fee transfer of the spawn sends the fee that was charged here
+                transaction_data.catalyze_fee = Some(price.catalyze_fee);
+                (spawn_amount, price.catalyze_fee)
+            }
+        };
+        let multisig_amount = spawn_amount - ICP_TRANSACTION_FEE;
+
+        // Create the ledger arguments needed for the transfer call to the ledger canister, for both memos
+        // the subaccount is the canister that is topped up or the controller of the created canister
+        let multig_spinup_ledger_args = TransferArgs {
+            memo,
+            amount: multisig_amount,
+            fee: ICP_TRANSACTION_FEE,
+            from_subaccount: None,
+            to: AccountIdentifier::new(&Config::cmc_canister_id(), &Subaccount::from(id())),
+            created_at_time: None,
+        };
+
+        // Pass the price paid by the user, from this canister to the cycles management canister (minus the fee)
+        match Transfers::transfer(transfer_id.clone(), multig_spinup_ledger_args).await {
+            Ok(cmc_block_index) => {
+                transaction_data.cmc_transfer_block_index = Some(cmc_block_index);
+                Ok(cmc_block_index)
+            }
+            Err(err) => {
+                // the ICP never left this canister when the ledger rejected the transfer, so the price is given
+                // back. A transfer with an unknown outcome stays charged until it is sent again
+                if !Transfers::is_unresolved(&transfer_id) {
+                    Self::refund_spawn(caller, icp_block_index, spawn_amount, catalyze_fee);
                 }
+                transaction_data.status = TransactionStatus::IcpToCmcFailed;
+                transaction_data.error_message = Some(err.to_string());
+                Self::insert_transaction_data(icp_block_index, transaction_data.clone());
+
+                Err(err)
             }
-            Err(err) => Err(err),
         }
     }
 
+    fn charge_spawn(
+        caller: Principal,
+        icp_block_index: u64,
+        spawn_amount: Tokens,
+        catalyze_fee: Tokens,
+    ) -> Result<(), MultisigIndexError> {
+        // Check the full price up front so the caller is never charged only partially
+        let available = Tokens::from_e8s(Self::get_caller_local_icp_balance(caller));
+        if available < spawn_amount + catalyze_fee {
+            return Err(MultisigIndexError::InsufficientIcp {
+                required: spawn_amount + catalyze_fee,
+                available,
+            });
+        }
+
+        Journal::record(
+            JournalEntryKind::SpawnSpend,
+            JournalAccount::Principal(caller),
+            JournalAccount::Cmc,
+            spawn_amount,
+            Some(icp_block_index),
+        )?;
+        Journal::record(
+            JournalEntryKind::ProtocolFee,
+            JournalAccount::Principal(caller),
+            JournalAccount::FeeRecipient,
+            catalyze_fee,
+            Some(icp_block_index),
+        )?;
+        Ok(())
+    }
+
+    // Finish the transaction of a spawn payment after its transfer was reviewed. A payment that reached the CMC
+    // is notified when the spawn is resumed, one that did not is given back like a rejected transfer
+    pub fn resolve_spawn_payment(icp_block_index: u64, transfer: &OutgoingTransfer) {
+        let mut transaction_data = match Self::get_transaction(icp_block_index) {
+            Some(transaction) => transaction,
+            None => return,
+        };
+
+        match transfer.status {
+            OutgoingTransferStatus::Completed(cmc_block_index) => {
+                transaction_data.cmc_transfer_block_index = Some(cmc_block_index);
+                transaction_data.status = TransactionStatus::CyclesToIndexFailed;
+            }
+            _ => Self::refund_spawn(
+                transaction_data.initialized_by,
+                icp_block_index,
+                transfer.args.amount + transfer.args.fee,
+                transaction_data.catalyze_fee.unwrap_or(Tokens::from_e8s(0)),
+            ),
+        }
+        Self::insert_transaction_data(icp_block_index, transaction_data);
+    }
+
+    fn refund_spawn(
+        caller: Principal,
+        icp_block_index: u64,
+        spawn_amount: Tokens,
+        catalyze_fee: Tokens,
+    ) {
+        // Crediting a principal never fails
+        let _ = Journal::record(
+            JournalEntryKind::Refund,
+            JournalAccount::Cmc,
+            JournalAccount::Principal(caller),
+            spawn_amount,
+            Some(icp_block_index),
+        );
+        let _ = Journal::record(
+            JournalEntryKind::Refund,
+            JournalAccount::FeeRecipient,
+            JournalAccount::Principal(caller),
+            catalyze_fee,
+            Some(icp_block_index),
+        );
+    }
+
     pub fn get_initialization_status(group_identifier: Principal) -> Option<InitializeStatus> {
-        INITIALIZING.with(|i| i.borrow().get(&group_identifier.to_string()).clone())
+        INITIALIZING
+            .with(|i| i.borrow().get(&group_identifier.to_string()))
+            .and_then(Stored::valid)
+    }
+
+    pub fn get_spawn(group_identifier: Principal) -> Option<SpawnData> {
+        SPAWNS
+            .with(|s| s.borrow().get(&group_identifier.to_string()))
+            .and_then(Stored::valid)
+    }
+
+    // The ICP block indexes that paid for a spawn
+    pub fn get_spawn_block_indexes() -> BTreeSet<u64> {
+        SPAWNS.with(|s| {
+            s.borrow()
+                .iter()
+                .filter_map(|(_, spawn)| spawn.valid())
+                .map(|spawn| spawn.icp_block_index)
+                .collect()
+        })
     }
 
     pub async fn spawn_multisig(
         caller: Principal,
         icp_block_index: u64,
         group_identifier: Principal,
-    ) -> Result<Principal, String> {
-        // Handle the transaction from the user to this canister
-        let top_up_result = Self::top_up_self(caller, icp_block_index).await;
-        match top_up_result {
-            Ok(cycles) => {
-                // Check if the mutlisig is already being initialized
-                if let Some(status) = Self::get_initialization_status(group_identifier.clone()) {
-                    if status == InitializeStatus::Initializing {
-                        return Err("Multsig for this group is being initialized".to_string());
-                    }
-                }
+        wasm_version: Option<String>,
+        creation: CanisterCreation,
+        price: Option<SpawnPrice>,
+    ) -> Result<Principal, MultisigIndexError> {
+        // Resolve the wasm version before the payment is handled
+        let wasm_version = Wasm::resolve_version(wasm_version)?;
+        Self::check_can_spawn(group_identifier)?;
 
-                // Set the status of the multisig to initializing
-                Self::set_is_initializing(&group_identifier, InitializeStatus::Initializing);
-                let spin_up_result = Self::spawn_canister(cycles).await;
-                match spin_up_result {
-                    Ok(canister_id) => {
-                        let install_result = Self::install_canister(caller, canister_id).await;
-                        match install_result {
-                            Ok(_) => {
-                                ENTRIES.with(|e| {
-                                    e.borrow_mut().insert(
-                                        canister_id.to_string(),
-                                        MultisigData {
-                                            canister_id,
-                                            group_identifier: Some(group_identifier),
-                                            created_by: caller,
-                                            created_at: time(),
-                                            updated_at: time(),
-                                        },
-                                    )
-                                });
-
-                                let catalyze_amount = CATALYZE_E8S_FEE - ICP_TRANSACTION_FEE;
-
-                                let catalyze_fee_ledger_args = TransferArgs {
-                                    memo: Memo(0),
-                                    amount: catalyze_amount,
-                                    fee: ICP_TRANSACTION_FEE,
-                                    from_subaccount: None,
-                                    to: AccountIdentifier::new(
-                                        &Principal::from_text(CATALYZE_MULTI_SIG).unwrap(),
-                                        &DEFAULT_SUBACCOUNT,
-                                    ),
-                                    created_at_time: None,
-                                };
-
-                                let _ = Ledger::transfer_icp(catalyze_fee_ledger_args).await;
-                                Self::set_is_initializing(
-                                    &group_identifier,
-                                    InitializeStatus::Done,
-                                );
-                                Ok(canister_id)
-                            }
-                            Err(err) => {
-                                Self::set_is_initializing(
-                                    &group_identifier,
-                                    InitializeStatus::Error,
-                                );
-                                Err(err)
-                            }
-                        }
-                    }
-                    Err(err) => {
-                        Self::set_is_initializing(&group_identifier, InitializeStatus::Error);
-                        Err(err)
-                    }
-                }
+        let spawn = SpawnData {
+            group_identifier,
+            created_by: caller,
+            icp_block_index,
+            cycles: None,
+            canister_id: None,
+            completed_steps: vec![],
+            error_message: None,
+            created_at: time(),
+            updated_at: time(),
+            wasm_version: Some(wasm_version),
+            creation: Some(creation),
+            price,
+        };
+        Self::insert_spawn(spawn.clone());
+        Self::record_spawn_event(&spawn, Event::new(EventKind::SpawnStarted));
+
+        Self::run_spawn(spawn).await
+    }
+
+    // Spawn a multisig paid with an ICRC-2 approval, the exact spin up price is pulled from the account of
+    // the caller so nothing is left on the local balance
+    pub async fn spawn_multisig_icrc2(
+        caller: Principal,
+        group_identifier: Principal,
+        wasm_version: Option<String>,
+    ) -> Result<Principal, MultisigIndexError> {
+        let wasm_version = Wasm::resolve_version(wasm_version)?;
+        Self::check_can_spawn(group_identifier)?;
+
+        let (icp_block_index, price) = Self::pull_spawn_price(caller).await?;
+
+        // From here on the pulled block is handled the same as a block transferred by the caller, charged with
+        // the price it was pulled for
+        Self::spawn_multisig(
+            caller,
+            icp_block_index,
+            group_identifier,
+            Some(wasm_version),
+            CanisterCreation::TopUp,
+            Some(price),
+        )
+        .await
+    }
+
+    async fn pull_spawn_price(caller: Principal) -> Result<(u64, SpawnPrice), MultisigIndexError> {
+        let _principal_lock = PrincipalLock::acquire(caller)?;
+
+        // A pull with an unknown outcome is sent again with the price it was quoted for, so it is pulled once
+        let pull = match Transfers::get_unresolved_pull(&caller) {
+            Some(pull) => pull,
+            None => Transfers::new_pull(caller, Pricing::get_current_spawn_price().await?),
+        };
+        let quoted_price = pull.price.clone();
+
+        let icp_block_index = Transfers::pull(pull).await?;
+        Self::record_pull(caller, icp_block_index, quoted_price.total_e8s)?;
+
+        Ok((icp_block_index, quoted_price))
+    }
+
+    // The pulled amount is known, so the block is stored as validated and added to the callers balance
+    pub fn record_pull(
+        caller: Principal,
+        icp_block_index: u64,
+        price: Tokens,
+    ) -> Result<(), MultisigIndexError> {
+        Journal::record(
+            JournalEntryKind::Deposit,
+            JournalAccount::Ledger,
+            JournalAccount::Principal(caller),
+            price,
+            Some(icp_block_index),
+        )?;
+        Events::record(Event {
+            principal: Some(caller),
+            icp_block_index: Some(icp_block_index),
+            amount: Some(price),
+            ..Event::new(EventKind::PaymentPulled)
+        });
+        Self::insert_transaction_data(
+            icp_block_index,
+            TransactionData {
+                icp_transfer_block_index: icp_block_index,
+                cmc_transfer_block_index: None,
+                icp_amount: Some(price),
+                cycles_amount: None,
+                initialized_by: caller,
+                created_at: time(),
+                status: TransactionStatus::Reserved,
+                error_message: None,
+                attempts: None,
+                last_attempt_at: None,
+                catalyze_fee: None,
+                creation: None,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn check_can_spawn(group_identifier: Principal) -> Result<(), MultisigIndexError> {
+        // Check if the mutlisig is already being initialized
+        if let Some(status) = Self::get_initialization_status(group_identifier) {
+            if status == InitializeStatus::Initializing {
+                return Err(MultisigIndexError::InitializationInProgress(
+                    group_identifier,
+                ));
+            }
+        }
+
+        // A spawn that already took the payment of the user should be resumed instead of started over,
+        // unless the payment was refunded
+        if let Some(spawn) = Self::get_spawn(group_identifier) {
+            if !spawn.completed_steps.is_empty()
+                && !spawn.completed_steps.contains(&SpawnStep::FeeTransferred)
+                && !Refunds::is_paid_out(spawn.icp_block_index)
+            {
+                return Err(MultisigIndexError::SpawnIncomplete(group_identifier));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn resume_spawn(
+        caller: Principal,
+        group_identifier: Principal,
+    ) -> Result<Principal, MultisigIndexError> {
+        let spawn = Self::get_spawn(group_identifier)
+            .ok_or(MultisigIndexError::SpawnNotFound(group_identifier))?;
+
+        if spawn.created_by != caller {
+            return Err(MultisigIndexError::Unauthorized);
+        }
+
+        if let Some(status) = Self::get_initialization_status(group_identifier) {
+            if status == InitializeStatus::Initializing {
+                return Err(MultisigIndexError::InitializationInProgress(
+                    group_identifier,
+                ));
+            }
+        }
+
+        Refunds::check_can_resume(spawn.icp_block_index)?;
+        Self::record_spawn_event(&spawn, Event::new(EventKind::SpawnResumed));
+
+        Self::run_spawn(spawn).await
+    }
+
+    // Runs every step of the spawn that is not completed yet, each completed step is stored
+    // so a failed spawn can be resumed from the step where it broke
+    async fn run_spawn(mut spawn: SpawnData) -> Result<Principal, MultisigIndexError> {
+        let group_identifier = spawn.group_identifier;
+
+        // Set the status of the multisig to initializing
+        Self::set_is_initializing(&group_identifier, InitializeStatus::Initializing);
+
+        match Self::run_spawn_steps(&mut spawn).await {
+            Ok(canister_id) => {
+                spawn.error_message = None;
+                Refunds::credit_claim(spawn.icp_block_index);
+                Self::record_spawn_event(&spawn, Event::new(EventKind::SpawnCompleted));
+                Self::insert_spawn(spawn);
+                Self::set_is_initializing(&group_identifier, InitializeStatus::Done);
+                Ok(canister_id)
             }
             Err(err) => {
+                spawn.error_message = Some(err.to_string());
+                Refunds::open_claim(&spawn, &err);
+                Self::record_spawn_event(
+                    &spawn,
+                    Event {
+                        error_message: Some(err.to_string()),
+                        ..Event::new(EventKind::SpawnFailed)
+                    },
+                );
+                Self::insert_spawn(spawn);
                 Self::set_is_initializing(&group_identifier, InitializeStatus::Error);
                 Err(err)
             }
         }
     }
 
-    pub async fn spawn_canister(cycles: Nat) -> Result<Principal, String> {
+    async fn run_spawn_steps(spawn: &mut SpawnData) -> Result<Principal, MultisigIndexError> {
+        match spawn.creation.clone() {
+            // The CMC creates the canister from the transaction of the user, so there is no top up step
+            Some(CanisterCreation::Cmc { subnet_type }) => {
+                if !spawn.completed_steps.contains(&SpawnStep::CanisterCreated) {
+                    let canister_id = Self::create_canister_with_payment(
+                        spawn.created_by,
+                        spawn.icp_block_index,
+                        subnet_type,
+                    )
+                    .await?;
+                    spawn.canister_id = Some(canister_id);
+                    Self::complete_spawn_step(spawn, SpawnStep::CanisterCreated);
+                    Self::record_spawn_event(spawn, Event::new(EventKind::CanisterCreated));
+                }
+            }
+            _ => {
+                // Handle the transaction from the user to this canister
+                if !spawn.completed_steps.contains(&SpawnStep::ToppedUp) {
+                    let cycles = Self::top_up_self(spawn).await?;
+                    spawn.cycles = Some(cycles);
+                    Self::complete_spawn_step(spawn, SpawnStep::ToppedUp);
+                }
+
+                if !spawn.completed_steps.contains(&SpawnStep::CanisterCreated) {
+                    let cycles = spawn.cycles.clone().unwrap_or_default();
+                    let canister_id = Self::spawn_canister(cycles.clone()).await?;
+                    Cycles::record_attached(&cycles);
+                    spawn.canister_id = Some(canister_id);
+                    Self::complete_spawn_step(spawn, SpawnStep::CanisterCreated);
+                    Self::record_spawn_event(
+                        spawn,
+                        Event {
+                            cycles: Some(cycles),
+                            ..Event::new(EventKind::CanisterCreated)
+                        },
+                    );
+                }
+            }
+        }
+
+        let canister_id = spawn
+            .canister_id
+            .ok_or(MultisigIndexError::SpawnNotFound(spawn.group_identifier))?;
+
+        let (wasm_data, wasm) = Wasm::get_wasm(spawn.wasm_version.clone())?;
+
+        if !spawn.completed_steps.contains(&SpawnStep::CodeInstalled) {
+            Self::install_canister(spawn.created_by, canister_id, wasm).await?;
+            Self::complete_spawn_step(spawn, SpawnStep::CodeInstalled);
+            Self::record_spawn_event(spawn, Event::new(EventKind::CodeInstalled));
+        }
+
+        if !spawn.completed_steps.contains(&SpawnStep::Registered) {
+            Self::insert_multisig(MultisigData {
+                canister_id,
+                group_identifier: Some(spawn.group_identifier),
+                created_by: spawn.created_by,
+                created_at: time(),
+                updated_at: time(),
+                wasm_version: Some(wasm_data.version),
+                wasm_hash: Some(wasm_data.hash),
+                controllers: Some(vec![id()]),
+            });
+            Self::complete_spawn_step(spawn, SpawnStep::Registered);
+            Self::record_spawn_event(spawn, Event::new(EventKind::MultisigRegistered));
+        }
+
+        if !spawn.completed_steps.contains(&SpawnStep::FeeTransferred) {
+            let catalyze_fee = Self::get_transaction(spawn.icp_block_index)
+                .and_then(|transaction| transaction.catalyze_fee)
+                .unwrap_or(Config::get().catalyze_e8s_fee);
+
+            // The fee is owed to the fee recipient from here on, a payout that fails is sent again by `Fees`
+            Fees::record_payout(spawn.icp_block_index, catalyze_fee).await;
+            Self::complete_spawn_step(spawn, SpawnStep::FeeTransferred);
+        }
+
+        Ok(canister_id)
+    }
+
+    // Record the event with the details of the spawn
+    fn record_spawn_event(spawn: &SpawnData, event: Event) {
+        Events::record(Event {
+            principal: Some(spawn.created_by),
+            group_identifier: Some(spawn.group_identifier),
+            canister_id: spawn.canister_id,
+            icp_block_index: Some(spawn.icp_block_index),
+            ..event
+        });
+    }
+
+    fn complete_spawn_step(spawn: &mut SpawnData, step: SpawnStep) {
+        spawn.completed_steps.push(step);
+        spawn.updated_at = time();
+        Self::insert_spawn(spawn.clone());
+    }
+
+    fn insert_spawn(spawn: SpawnData) {
+        SPAWNS.with(|s| {
+            s.borrow_mut()
+                .insert(spawn.group_identifier.to_string(), spawn.into())
+        });
+    }
+
+    pub async fn spawn_canister(cycles: Nat) -> Result<Principal, MultisigIndexError> {
         let args = CreateCanisterArgument {
             settings: Some(CanisterSettings {
                 controllers: Some(vec![id()]),
@@ -342,84 +1203,132 @@ impl Store {
             }),
         };
 
-        let result = create_canister(args, Self::nat_to_u128(cycles.clone())).await;
+        let result = create_canister(args, Self::nat_to_u128(cycles)).await;
         match result {
             Ok((canister_record,)) => Ok(canister_record.canister_id),
-            Err((_, err)) => Err(err),
+            Err(err) => Err(err.into()),
         }
     }
 
     pub async fn install_canister(
         owner: Principal,
         canister_id: Principal,
-    ) -> Result<Principal, String> {
-        let multisig_wasm = include_bytes!("../../wasm/multisig.wasm.gz");
-
+        wasm: Vec<u8>,
+    ) -> Result<Principal, MultisigIndexError> {
         let args = InstallCodeArgument {
             mode: CanisterInstallMode::Install,
             canister_id,
-            wasm_module: multisig_wasm.to_vec(),
+            wasm_module: wasm,
             arg: Encode!((&owner)).unwrap(),
         };
         let result = install_code(args).await;
 
         match result {
             Ok(()) => Ok(canister_id),
-            Err((_, err)) => Err(err),
+            Err(err) => Err(err.into()),
         }
     }
 
-    pub async fn withdraw_balance(principal: Principal) -> Result<(), String> {
-        let balance = CALLER_ICP_BALANCE.with(|c| c.borrow().get(&principal.to_string()));
-        if balance.unwrap_or_default() == 0 {
-            return Err("No balance found".to_string());
+    pub async fn withdraw_balance(principal: Principal) -> Result<(), MultisigIndexError> {
+        let _principal_lock = PrincipalLock::acquire(principal)?;
+
+        // A withdrawal with an unknown outcome is finished first, its amount was already taken from the balance
+        let pending = Transfers::get_pending_transfers(&Transfers::withdrawal_prefix(&principal));
+        if !pending.is_empty() {
+            for transfer in pending {
+                Self::send_withdrawal(principal, transfer.id, transfer.args).await?;
+            }
+            return Ok(());
+        }
+
+        let balance = Self::get_caller_local_icp_balance(principal);
+        if balance == 0 {
+            return Err(MultisigIndexError::NoBalance);
+        }
+        // The ledger fee is paid from the balance, a balance that does not cover it would be lost to the fee
+        if balance <= ICP_TRANSACTION_FEE.e8s() {
+            return Err(MultisigIndexError::InsufficientIcp {
+                required: ICP_TRANSACTION_FEE,
+                available: Tokens::from_e8s(balance),
+            });
         }
 
+        // subtract the balance before the call, so it cannot be withdrawn or spent twice
+        let entry = Journal::record(
+            JournalEntryKind::Withdrawal,
+            JournalAccount::Principal(principal),
+            JournalAccount::Ledger,
+            Tokens::from_e8s(balance),
+            None,
+        )?;
+        let transfer_id = Transfers::withdrawal_id(&principal, entry.id);
+
         let ledger_args = TransferArgs {
-            memo: Memo(0),
-            amount: Tokens::from_e8s(balance.unwrap_or_default() - ICP_TRANSACTION_FEE.e8s()),
+            memo: Transfers::memo(&transfer_id),
+            amount: Tokens::from_e8s(balance - ICP_TRANSACTION_FEE.e8s()),
             fee: ICP_TRANSACTION_FEE,
             from_subaccount: None,
             to: AccountIdentifier::new(&principal, &DEFAULT_SUBACCOUNT),
             created_at_time: None,
         };
 
-        match Ledger::transfer_icp(ledger_args).await {
-            Ok(_) => {
-                Self::update_caller_icp_balance(
-                    &principal,
-                    UpdateIcpBalanceArgs::Subtract(Tokens::from_e8s(balance.unwrap_or_default())),
-                );
+        Self::send_withdrawal(principal, transfer_id, ledger_args).await
+    }
+
+    async fn send_withdrawal(
+        principal: Principal,
+        transfer_id: String,
+        ledger_args: TransferArgs,
+    ) -> Result<(), MultisigIndexError> {
+        let withdrawn = ledger_args.amount + ledger_args.fee;
+
+        match Transfers::transfer(transfer_id.clone(), ledger_args).await {
+            Ok(block_index) => {
+                Events::record(Event {
+                    principal: Some(principal),
+                    block_index: Some(block_index),
+                    amount: Some(withdrawn),
+                    ..Event::new(EventKind::Withdrawal)
+                });
                 Ok(())
             }
-            Err(err) => Err(err),
+            Err(err) => {
+                Events::record(Event {
+                    principal: Some(principal),
+                    amount: Some(withdrawn),
+                    error_message: Some(err.to_string()),
+                    ..Event::new(EventKind::WithdrawalFailed)
+                });
+                // Only given back when the ledger rejected the transfer, otherwise it is sent again on the next call
+                if !Transfers::is_unresolved(&transfer_id) {
+                    Self::refund_withdrawal(principal, withdrawn);
+                }
+                Err(err)
+            }
         }
     }
 
-    fn set_is_initializing(canister_id: &Principal, status: InitializeStatus) {
-        INITIALIZING.with(|i| i.borrow_mut().insert(canister_id.to_string(), status));
+    // Give back the amount and fee of a withdrawal that did not go through
+    pub fn refund_withdrawal(principal: Principal, withdrawn: Tokens) {
+        // Crediting a principal never fails
+        let _ = Journal::record(
+            JournalEntryKind::Refund,
+            JournalAccount::Ledger,
+            JournalAccount::Principal(principal),
+            withdrawn,
+            None,
+        );
     }
 
-    fn insert_transaction_data(icp_block_index: u64, transaction_data: TransactionData) {
-        TRANSACTIONS.with(|t| {
-            t.borrow_mut()
-                .insert(icp_block_index, transaction_data.clone())
+    fn set_is_initializing(canister_id: &Principal, status: InitializeStatus) {
+        INITIALIZING.with(|i| {
+            i.borrow_mut()
+                .insert(canister_id.to_string(), status.into())
         });
     }
 
-    fn update_caller_icp_balance(caller: &Principal, args: UpdateIcpBalanceArgs) {
-        CALLER_ICP_BALANCE.with(|c| {
-            let mut balance = c.borrow_mut();
-            let current_balance = balance.get(&caller.to_string()).unwrap_or(0);
-            match args {
-                UpdateIcpBalanceArgs::Add(amount) => {
-                    balance.insert(caller.to_string(), current_balance + amount.e8s());
-                }
-                UpdateIcpBalanceArgs::Subtract(amount) => {
-                    balance.insert(caller.to_string(), current_balance - amount.e8s());
-                }
-            }
-        });
+    pub fn insert_transaction_data(icp_block_index: u64, transaction_data: TransactionData) {
+        Indexes::insert_transaction(icp_block_index, transaction_data);
     }
 
     fn nat_to_u128(value: Nat) -> u128 {
@@ -0,0 +1,410 @@
+use std::convert::TryInto;
+
+use candid::Principal;
+use ic_cdk::api::time;
+use ic_ledger_types::{Memo, Timestamp, TransferArgs, TransferError};
+use sha2::{Digest, Sha256};
+
+use crate::rust_declarations::{
+    icp_ledger_service::TransferFromError,
+    stored::Stored,
+    types::{
+        MultisigIndexError, OutgoingTransfer, OutgoingTransferStatus, PaymentPull, SpawnPrice,
+        TransferResolution,
+    },
+};
+
+use super::{
+    deposit::Deposit,
+    fees::Fees,
+    ledger::Ledger,
+    store::{Store, OUTGOING_TRANSFERS, PAYMENT_PULLS},
+};
+
+// Every ICP transfer from this canister goes through here. A transfer gets an id that is derived from what it
+// pays for, and its arguments are stored before the ledger is called. When the outcome of a call is unknown
+// the transfer stays pending and is sent again with the same memo and `created_at_time`, so the ledger
+// returns the block of the first attempt as a duplicate instead of paying twice
+pub struct Transfers;
+
+impl Transfers {
+    pub fn spawn_payment_id(icp_block_index: u64) -> String {
+        format!("spawn:{:020}", icp_block_index)
+    }
+
+    pub fn fee_id(icp_block_index: u64) -> String {
+        format!("fee:{:020}", icp_block_index)
+    }
+
+    pub fn fee_sweep_id(created_at: u64) -> String {
+        format!("fee-sweep:{:020}", created_at)
+    }
+
+    pub fn withdrawal_id(principal: &Principal, journal_entry_id: u64) -> String {
+        format!(
+            "{}{:020}",
+            Self::withdrawal_prefix(principal),
+            journal_entry_id
+        )
+    }
+
+    pub fn withdrawal_prefix(principal: &Principal) -> String {
+        format!("withdrawal:{}:", principal)
+    }
+
+    pub fn sweep_id(principal: &Principal, created_at: u64) -> String {
+        format!("{}{:020}", Self::sweep_prefix(principal), created_at)
+    }
+
+    pub fn sweep_prefix(principal: &Principal) -> String {
+        format!("sweep:{}:", principal)
+    }
+
+    pub fn top_up_id(canister_id: &Principal, created_at: u64) -> String {
+        format!("top-up:{}:{:020}", canister_id, created_at)
+    }
+
+    pub fn pull_id(principal: &Principal, created_at: u64) -> String {
+        format!("{}{:020}", Self::pull_prefix(principal), created_at)
+    }
+
+    pub fn pull_prefix(principal: &Principal) -> String {
+        format!("pull:{}:", principal)
+    }
+
+    // The same id always gives the same memo, transfers to the CMC use the memo the CMC expects instead
+    pub fn memo(id: &str) -> Memo {
+        let hash = Sha256::digest(id.as_bytes());
+        Memo(u64::from_be_bytes(
+            hash[..8].try_into().expect("A hash has at least 8 bytes"),
+        ))
+    }
+
+    pub fn get_transfer(id: &str) -> Option<OutgoingTransfer> {
+        OUTGOING_TRANSFERS
+            .with(|o| o.borrow().get(&id.to_string()))
+            .and_then(Stored::valid)
+    }
+
+    // A transfer that may have happened, what it pays for stays charged until it is sent again or reviewed
+    pub fn is_unresolved(id: &str) -> bool {
+        Self::get_unresolved_transfer(id).is_some()
+    }
+
+    pub fn get_unresolved_transfer(id: &str) -> Option<OutgoingTransfer> {
+        Self::get_transfer(id).filter(|transfer| Self::is_unresolved_status(&transfer.status))
+    }
+
+    fn is_unresolved_status(status: &OutgoingTransferStatus) -> bool {
+        matches!(
+            status,
+            OutgoingTransferStatus::Pending | OutgoingTransferStatus::NeedsReview(_)
+        )
+    }
+
+    // Unresolved transfers with an id that starts with the prefix, all unresolved transfers for an empty prefix
+    pub fn get_pending_transfers(prefix: &str) -> Vec<OutgoingTransfer> {
+        OUTGOING_TRANSFERS.with(|o| {
+            o.borrow()
+                .range(prefix.to_string()..)
+                .take_while(|(id, _)| id.starts_with(prefix))
+                .filter_map(|(_, transfer)| transfer.valid())
+                .filter(|transfer| Self::is_unresolved_status(&transfer.status))
+                .collect()
+        })
+    }
+
+    // Send the transfer with the given id, returns the block index of the transfer. The arguments are only
+    // used for a new transfer, a pending transfer is always sent with its stored arguments
+    pub async fn transfer(id: String, args: TransferArgs) -> Result<u64, MultisigIndexError> {
+        let mut transfer = match Self::get_transfer(&id) {
+            Some(transfer) => match transfer.status {
+                OutgoingTransferStatus::Completed(block_index) => return Ok(block_index),
+                OutgoingTransferStatus::Pending => transfer,
+                OutgoingTransferStatus::NeedsReview(_) => {
+                    return Err(MultisigIndexError::TransferNeedsReview(id))
+                }
+                // Nothing was transferred, so it is sent as a new transfer
+                OutgoingTransferStatus::Failed(_) => {
+                    Self::new_transfer(id, args, transfer.attempts)
+                }
+            },
+            None => Self::new_transfer(id, args, 0),
+        };
+
+        transfer.attempts += 1;
+        transfer.status = OutgoingTransferStatus::Pending;
+        Self::insert_transfer(transfer.clone());
+
+        let result = match Ledger::transfer_icp(transfer.args.clone()).await {
+            // An earlier attempt of this transfer went through
+            Err(MultisigIndexError::LedgerTransfer(TransferError::TxDuplicate {
+                duplicate_of,
+            })) => Ok(duplicate_of),
+            result => result,
+        };
+
+        match &result {
+            Ok(block_index) => transfer.status = OutgoingTransferStatus::Completed(*block_index),
+            // A retry outside of the deduplication window of the ledger, the ledger can no longer tell whether an
+            // earlier attempt went through
+            Err(MultisigIndexError::LedgerTransfer(
+                err @ (TransferError::TxTooOld { .. } | TransferError::TxCreatedInFuture),
+            )) if transfer.attempts > 1 => {
+                transfer.status = OutgoingTransferStatus::NeedsReview(err.to_string())
+            }
+            // The ledger rejected the transfer
+            Err(MultisigIndexError::LedgerTransfer(err)) => {
+                transfer.status = OutgoingTransferStatus::Failed(err.to_string())
+            }
+            // The call failed, the transfer may or may not have happened so it stays pending
+            Err(_) => {}
+        }
+        Self::insert_transfer(transfer);
+
+        result
+    }
+
+    // Settle a transfer that needs a review with the outcome the admin found on the ledger. What the transfer paid
+    // for is finished when it went through, otherwise the charged amount is given back like for a rejected
+    // transfer. A top up transfer is picked up by the next monitor run in both cases
+    pub fn resolve_transfer(
+        id: String,
+        resolution: TransferResolution,
+    ) -> Result<(), MultisigIndexError> {
+        if id.starts_with("pull:") {
+            return Self::resolve_pull(id, resolution);
+        }
+
+        let mut transfer = Self::get_transfer(&id)
+            .filter(|transfer| matches!(transfer.status, OutgoingTransferStatus::NeedsReview(_)))
+            .ok_or_else(|| MultisigIndexError::TransferNotInReview(id.clone()))?;
+        transfer.status = Self::resolved_status(&resolution);
+        Self::insert_transfer(transfer.clone());
+
+        // The id tells what the transfer paid for, see the id functions above
+        let mut parts = id.split(':');
+        let kind = parts.next().unwrap_or_default();
+        let key = parts.next().unwrap_or_default();
+        let principal = Principal::from_text(key).ok();
+        match (kind, principal, resolution) {
+            ("spawn", _, _) => {
+                if let Ok(icp_block_index) = key.parse() {
+                    Store::resolve_spawn_payment(icp_block_index, &transfer);
+                }
+            }
+            ("withdrawal", Some(principal), TransferResolution::Failed) => {
+                Store::refund_withdrawal(principal, transfer.args.amount + transfer.args.fee)
+            }
+            // The deposit left the subaccount, so it is added to the local balance
+            ("sweep", Some(principal), TransferResolution::Completed(block_index)) => {
+                Deposit::record_deposit(principal, transfer.args.amount, block_index)?;
+            }
+            // A completed payout transfer is marked as paid by the next run
+            ("fee" | "fee-sweep", _, TransferResolution::Failed) => Fees::unlink_transfer(&id),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn resolve_pull(id: String, resolution: TransferResolution) -> Result<(), MultisigIndexError> {
+        let mut pull = PAYMENT_PULLS
+            .with(|p| p.borrow().get(&id))
+            .and_then(Stored::valid)
+            .filter(|pull| matches!(pull.status, OutgoingTransferStatus::NeedsReview(_)))
+            .ok_or_else(|| MultisigIndexError::TransferNotInReview(id.clone()))?;
+        pull.status = Self::resolved_status(&resolution);
+        Self::insert_pull(pull.clone());
+
+        // The pulled amount is added to the local balance, the caller can spawn with the block or withdraw it
+        if let TransferResolution::Completed(block_index) = resolution {
+            Store::record_pull(pull.principal, block_index, pull.price.total_e8s)?;
+        }
+        Ok(())
+    }
+
+    fn resolved_status(resolution: &TransferResolution) -> OutgoingTransferStatus {
+        match resolution {
+            TransferResolution::Completed(block_index) => {
+                OutgoingTransferStatus::Completed(*block_index)
+            }
+            TransferResolution::Failed => {
+                OutgoingTransferStatus::Failed("Resolved as failed after a review".to_string())
+            }
+        }
+    }
+
+    // Pulls with an unknown outcome, see `resolve_transfer`
+    pub fn get_pending_pulls() -> Vec<PaymentPull> {
+        PAYMENT_PULLS.with(|p| {
+            p.borrow()
+                .iter()
+                .filter_map(|(_, pull)| pull.valid())
+                .filter(|pull| Self::is_unresolved_status(&pull.status))
+                .collect()
+        })
+    }
+
+    // The pull of the principal with an unknown outcome, it is sent again before a new pull is started
+    pub fn get_unresolved_pull(principal: &Principal) -> Option<PaymentPull> {
+        let prefix = Self::pull_prefix(principal);
+        PAYMENT_PULLS.with(|p| {
+            p.borrow()
+                .range(prefix.clone()..)
+                .take_while(|(id, _)| id.starts_with(&prefix))
+                .filter_map(|(_, pull)| pull.valid())
+                .find(|pull| Self::is_unresolved_status(&pull.status))
+        })
+    }
+
+    pub fn new_pull(principal: Principal, price: SpawnPrice) -> PaymentPull {
+        PaymentPull {
+            id: Self::pull_id(&principal, time()),
+            principal,
+            price,
+            created_at_time: time(),
+            status: OutgoingTransferStatus::Pending,
+            attempts: 0,
+            created_at: time(),
+            updated_at: time(),
+        }
+    }
+
+    // Pull the quoted price from the account of the principal, returns the block index of the pull. A pull is
+    // retried like a transfer, with its stored memo and `created_at_time`
+    pub async fn pull(mut pull: PaymentPull) -> Result<u64, MultisigIndexError> {
+        if let OutgoingTransferStatus::NeedsReview(_) = pull.status {
+            return Err(MultisigIndexError::TransferNeedsReview(pull.id));
+        }
+
+        pull.attempts += 1;
+        pull.status = OutgoingTransferStatus::Pending;
+        Self::insert_pull(pull.clone());
+
+        let result = match Ledger::transfer_from(
+            pull.principal,
+            pull.price.total_e8s,
+            Self::memo(&pull.id),
+            pull.created_at_time,
+        )
+        .await
+        {
+            // An earlier attempt of this pull went through
+            Err(MultisigIndexError::LedgerTransferFrom(TransferFromError::Duplicate {
+                duplicate_of,
+            })) => Ok(Ledger::nat_to_u64(duplicate_of)),
+            result => result,
+        };
+
+        match &result {
+            Ok(block_index) => pull.status = OutgoingTransferStatus::Completed(*block_index),
+            Err(MultisigIndexError::LedgerTransferFrom(
+                err @ (TransferFromError::TooOld | TransferFromError::CreatedInFuture { .. }),
+            )) if pull.attempts > 1 => {
+                pull.status = OutgoingTransferStatus::NeedsReview(format!("{:?}", err))
+            }
+            Err(MultisigIndexError::LedgerTransferFrom(err)) => {
+                pull.status = OutgoingTransferStatus::Failed(format!("{:?}", err))
+            }
+            Err(_) => {}
+        }
+        Self::insert_pull(pull);
+
+        result
+    }
+
+    fn insert_pull(mut pull: PaymentPull) {
+        pull.updated_at = time();
+        PAYMENT_PULLS.with(|p| p.borrow_mut().insert(pull.id.clone(), pull.into()));
+    }
+
+    fn new_transfer(id: String, mut args: TransferArgs, attempts: u32) -> OutgoingTransfer {
+        args.created_at_time = Some(Timestamp {
+            timestamp_nanos: time(),
+        });
+        OutgoingTransfer {
+            id,
+            args,
+            status: OutgoingTransferStatus::Pending,
+            attempts,
+            created_at: time(),
+            updated_at: time(),
+        }
+    }
+
+    fn insert_transfer(mut transfer: OutgoingTransfer) {
+        transfer.updated_at = time();
+        OUTGOING_TRANSFERS.with(|o| o.borrow_mut().insert(transfer.id.clone(), transfer.into()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_ledger_types::{AccountIdentifier, Tokens, DEFAULT_SUBACCOUNT};
+
+    use super::*;
+
+    fn insert(id: &str, status: OutgoingTransferStatus) {
+        let transfer = OutgoingTransfer {
+            id: id.to_string(),
+            args: TransferArgs {
+                memo: Transfers::memo(id),
+                amount: Tokens::from_e8s(1),
+                fee: Tokens::from_e8s(10_000),
+                from_subaccount: None,
+                to: AccountIdentifier::new(&Principal::anonymous(), &DEFAULT_SUBACCOUNT),
+                created_at_time: None,
+            },
+            status,
+            attempts: 1,
+            created_at: 0,
+            updated_at: 0,
+        };
+        OUTGOING_TRANSFERS.with(|o| o.borrow_mut().insert(id.to_string(), transfer.into()));
+    }
+
+    #[test]
+    fn transfers_needing_review_stay_unresolved() {
+        let principal = Principal::anonymous();
+        let prefix = Transfers::withdrawal_prefix(&principal);
+        insert(
+            &Transfers::withdrawal_id(&principal, 1),
+            OutgoingTransferStatus::Pending,
+        );
+        insert(
+            &Transfers::withdrawal_id(&principal, 2),
+            OutgoingTransferStatus::NeedsReview("too old".to_string()),
+        );
+        insert(
+            &Transfers::withdrawal_id(&principal, 3),
+            OutgoingTransferStatus::Failed("rejected".to_string()),
+        );
+        insert(
+            &Transfers::withdrawal_id(&principal, 4),
+            OutgoingTransferStatus::Completed(1),
+        );
+        insert(&Transfers::fee_id(1), OutgoingTransferStatus::Pending);
+
+        let unresolved: Vec<String> = Transfers::get_pending_transfers(&prefix)
+            .into_iter()
+            .map(|transfer| transfer.id)
+            .collect();
+        assert_eq!(
+            unresolved,
+            vec![
+                Transfers::withdrawal_id(&principal, 1),
+                Transfers::withdrawal_id(&principal, 2)
+            ]
+        );
+        assert!(Transfers::is_unresolved(&Transfers::withdrawal_id(
+            &principal, 2
+        )));
+        assert!(!Transfers::is_unresolved(&Transfers::withdrawal_id(
+            &principal, 3
+        )));
+        assert!(!Transfers::is_unresolved(&Transfers::withdrawal_id(
+            &principal, 4
+        )));
+        assert!(!Transfers::is_unresolved("missing"));
+    }
+}
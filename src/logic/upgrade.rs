@@ -0,0 +1,163 @@
+use candid::{Encode, Principal};
+use ic_cdk::api::{
+    management_canister::main::{
+        install_code, start_canister, stop_canister, CanisterIdRecord, CanisterInstallMode,
+        InstallCodeArgument,
+    },
+    time,
+};
+
+use crate::rust_declarations::{
+    stored::Stored,
+    types::{MultisigData, MultisigIndexError, MultisigUpgradeData, UpgradeStatus, WasmData},
+};
+
+use super::{
+    controllers::Controllers,
+    store::{Store, ENTRIES, UPGRADES},
+    wasm::Wasm,
+};
+
+pub static DEFAULT_UPGRADE_BATCH_SIZE: usize = 10;
+// An upgrade that is not finished after this was interrupted by a trap, so it is upgraded again
+pub static UPGRADE_TIMEOUT_NANOS: u64 = 60 * 60 * 1_000_000_000;
+
+pub struct Upgrade;
+
+impl Upgrade {
+    pub fn get_upgrade_statuses() -> Vec<MultisigUpgradeData> {
+        UPGRADES.with(|u| u.borrow().iter().filter_map(|(_, v)| v.valid()).collect())
+    }
+
+    // Upgrade the next batch of multisigs that are not running the default wasm version. Upgrades that were
+    // interrupted are picked up again by the next batch, upgrades that failed for this wasm only when
+    // `retry_failed` is set so they do not keep the other multisigs from being upgraded
+    pub async fn upgrade_multisigs(
+        batch_size: Option<u64>,
+        retry_failed: Option<bool>,
+    ) -> Result<Vec<MultisigUpgradeData>, MultisigIndexError> {
+        let (wasm_data, wasm) = Wasm::get_wasm(None)?;
+        let batch_size = batch_size
+            .map(|size| size as usize)
+            .unwrap_or(DEFAULT_UPGRADE_BATCH_SIZE);
+
+        let batch: Vec<MultisigData> = ENTRIES.with(|e| {
+            e.borrow()
+                .iter()
+                .filter_map(|(_, v)| v.valid())
+                .filter(|v| v.wasm_hash.as_ref() != Some(&wasm_data.hash))
+                .filter(|v| !Self::is_upgrading(v.canister_id))
+                .filter(|v| {
+                    retry_failed.unwrap_or_default()
+                        || !Self::has_failed(v.canister_id, &wasm_data.hash)
+                })
+                // A multisig that was handed over to its group can only be upgraded by its new controllers
+                .filter(Controllers::is_index_controller)
+                .take(batch_size)
+                .collect()
+        });
+
+        // The whole batch is marked before the first await, so a call that runs meanwhile picks other multisigs
+        let batch: Vec<(MultisigData, MultisigUpgradeData)> = batch
+            .into_iter()
+            .map(|multisig| {
+                let upgrade = MultisigUpgradeData {
+                    canister_id: multisig.canister_id,
+                    wasm_version: wasm_data.version.clone(),
+                    wasm_hash: wasm_data.hash.clone(),
+                    status: UpgradeStatus::Upgrading,
+                    error_message: None,
+                    updated_at: time(),
+                    started_at: Some(time()),
+                };
+                Self::insert_upgrade(upgrade.clone());
+                (multisig, upgrade)
+            })
+            .collect();
+
+        let mut result = vec![];
+        for (multisig, upgrade) in batch {
+            result.push(Self::upgrade_multisig(multisig, upgrade, &wasm_data, &wasm).await);
+        }
+        Ok(result)
+    }
+
+    async fn upgrade_multisig(
+        mut multisig: MultisigData,
+        mut upgrade: MultisigUpgradeData,
+        wasm_data: &WasmData,
+        wasm: &[u8],
+    ) -> MultisigUpgradeData {
+        match Self::upgrade_canister(multisig.canister_id, wasm).await {
+            Ok(()) => {
+                multisig.wasm_version = Some(wasm_data.version.clone());
+                multisig.wasm_hash = Some(wasm_data.hash.clone());
+                multisig.updated_at = time();
+                Store::insert_multisig(multisig);
+                upgrade.status = UpgradeStatus::Done;
+            }
+            Err(err) => {
+                upgrade.status = UpgradeStatus::Failed;
+                upgrade.error_message = Some(err.to_string());
+            }
+        }
+
+        upgrade.updated_at = time();
+        Self::insert_upgrade(upgrade.clone());
+        upgrade
+    }
+
+    // The canister is stopped during the upgrade so no calls are open while the code is replaced
+    async fn upgrade_canister(
+        canister_id: Principal,
+        wasm: &[u8],
+    ) -> Result<(), MultisigIndexError> {
+        stop_canister(CanisterIdRecord { canister_id }).await?;
+
+        let result = install_code(InstallCodeArgument {
+            mode: CanisterInstallMode::Upgrade,
+            canister_id,
+            wasm_module: wasm.to_vec(),
+            arg: Encode!().unwrap(),
+        })
+        .await;
+
+        // Always start the canister again, also when the upgrade failed
+        let start_result = start_canister(CanisterIdRecord { canister_id }).await;
+
+        result?;
+        start_result?;
+        Ok(())
+    }
+
+    fn is_upgrading(canister_id: Principal) -> bool {
+        UPGRADES.with(|u| {
+            u.borrow()
+                .get(&canister_id.to_string())
+                .and_then(Stored::valid)
+                .is_some_and(|upgrade| {
+                    upgrade.status == UpgradeStatus::Upgrading
+                        && upgrade.started_at.unwrap_or(upgrade.updated_at) + UPGRADE_TIMEOUT_NANOS
+                            > time()
+                })
+        })
+    }
+
+    fn has_failed(canister_id: Principal, wasm_hash: &str) -> bool {
+        UPGRADES.with(|u| {
+            u.borrow()
+                .get(&canister_id.to_string())
+                .and_then(Stored::valid)
+                .is_some_and(|upgrade| {
+                    upgrade.status == UpgradeStatus::Failed && upgrade.wasm_hash == wasm_hash
+                })
+        })
+    }
+
+    fn insert_upgrade(upgrade: MultisigUpgradeData) {
+        UPGRADES.with(|u| {
+            u.borrow_mut()
+                .insert(upgrade.canister_id.to_string(), upgrade.into())
+        });
+    }
+}
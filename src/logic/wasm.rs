@@ -0,0 +1,191 @@
+use candid::Principal;
+use ic_cdk::api::time;
+use sha2::{Digest, Sha256};
+
+use crate::rust_declarations::{
+    stored::Stored,
+    types::{MultisigIndexError, WasmData},
+};
+
+use super::store::{DEFAULT_WASM_VERSION, MULTISIG_WASM, WASM_CHUNKS, WASM_MODULES, WASM_VERSIONS};
+
+// Version used for the wasm that is compiled into this canister, used when no default version is set
+pub static BUNDLED_WASM_VERSION: &str = "bundled";
+
+pub struct Wasm;
+
+impl Wasm {
+    pub fn wasm_hash(wasm: &[u8]) -> String {
+        hex::encode(Sha256::digest(wasm))
+    }
+
+    pub fn get_wasm_versions() -> Vec<WasmData> {
+        WASM_VERSIONS.with(|w| w.borrow().iter().filter_map(|(_, v)| v.valid()).collect())
+    }
+
+    pub fn get_default_wasm_version() -> Option<String> {
+        DEFAULT_WASM_VERSION.with(|d| {
+            let version = d.borrow().get().clone();
+            (!version.is_empty()).then_some(version)
+        })
+    }
+
+    // The bundled version clears the default, so the wasm compiled into this canister is used again
+    pub fn set_default_wasm_version(version: String) -> Result<(), MultisigIndexError> {
+        let version = if version == BUNDLED_WASM_VERSION {
+            String::new()
+        } else if WASM_VERSIONS.with(|w| w.borrow().contains_key(&version)) {
+            version
+        } else {
+            return Err(MultisigIndexError::WasmVersionNotFound(version));
+        };
+
+        DEFAULT_WASM_VERSION
+            .with(|d| d.borrow_mut().set(version))
+            .expect("Failed to set the default wasm version");
+        Ok(())
+    }
+
+    // Append a chunk to the staged wasm of the given version, returns the total staged size
+    pub fn upload_wasm_chunk(version: String, chunk: Vec<u8>) -> Result<u64, MultisigIndexError> {
+        if version == BUNDLED_WASM_VERSION
+            || WASM_VERSIONS.with(|w| w.borrow().contains_key(&version))
+        {
+            return Err(MultisigIndexError::WasmVersionExists(version));
+        }
+
+        WASM_CHUNKS.with(|w| {
+            let mut chunks = w.borrow_mut();
+            let mut staged = chunks.get(&version).unwrap_or_default();
+            staged.extend(chunk);
+            let size = staged.len() as u64;
+            chunks.insert(version, staged);
+            Ok(size)
+        })
+    }
+
+    pub fn clear_wasm_chunks(version: String) {
+        WASM_CHUNKS.with(|w| w.borrow_mut().remove(&version));
+    }
+
+    // Move the staged wasm into the registry after checking it against the expected hash
+    pub fn finalize_wasm(
+        caller: Principal,
+        version: String,
+        expected_hash: String,
+        release_notes: String,
+    ) -> Result<WasmData, MultisigIndexError> {
+        let wasm = WASM_CHUNKS
+            .with(|w| w.borrow().get(&version))
+            .ok_or_else(|| MultisigIndexError::WasmVersionNotFound(version.clone()))?;
+
+        let hash = Self::wasm_hash(&wasm);
+        if hash != expected_hash.to_lowercase() {
+            return Err(MultisigIndexError::WasmHashMismatch {
+                expected: expected_hash,
+                actual: hash,
+            });
+        }
+
+        let wasm_data = WasmData {
+            version: version.clone(),
+            hash,
+            release_notes,
+            size: wasm.len() as u64,
+            uploaded_by: caller,
+            created_at: time(),
+        };
+
+        WASM_MODULES.with(|w| w.borrow_mut().insert(version.clone(), wasm));
+        WASM_VERSIONS.with(|w| {
+            w.borrow_mut()
+                .insert(version.clone(), wasm_data.clone().into())
+        });
+        Self::clear_wasm_chunks(version);
+        Ok(wasm_data)
+    }
+
+    // Returns the given version, or the default version if none is given. If there is no default version
+    // the wasm compiled into this canister is used
+    pub fn get_wasm(version: Option<String>) -> Result<(WasmData, Vec<u8>), MultisigIndexError> {
+        match version.or_else(Self::get_default_wasm_version) {
+            Some(version) if version != BUNDLED_WASM_VERSION => {
+                let wasm_data = WASM_VERSIONS
+                    .with(|w| w.borrow().get(&version))
+                    .and_then(Stored::valid)
+                    .ok_or_else(|| MultisigIndexError::WasmVersionNotFound(version.clone()))?;
+                let wasm = WASM_MODULES
+                    .with(|w| w.borrow().get(&version))
+                    .ok_or(MultisigIndexError::WasmVersionNotFound(version))?;
+                Ok((wasm_data, wasm))
+            }
+            _ => Ok((Self::bundled_wasm_data(), MULTISIG_WASM.to_vec())),
+        }
+    }
+
+    // Resolves the version that would be installed without loading the wasm itself
+    pub fn resolve_version(version: Option<String>) -> Result<String, MultisigIndexError> {
+        match version.or_else(Self::get_default_wasm_version) {
+            Some(version) if version != BUNDLED_WASM_VERSION => {
+                if WASM_VERSIONS.with(|w| w.borrow().contains_key(&version)) {
+                    Ok(version)
+                } else {
+                    Err(MultisigIndexError::WasmVersionNotFound(version))
+                }
+            }
+            _ => Ok(BUNDLED_WASM_VERSION.to_string()),
+        }
+    }
+
+    fn bundled_wasm_data() -> WasmData {
+        WasmData {
+            version: BUNDLED_WASM_VERSION.to_string(),
+            hash: Self::wasm_hash(MULTISIG_WASM),
+            release_notes: "Wasm compiled into the index canister".to_string(),
+            size: MULTISIG_WASM.len() as u64,
+            uploaded_by: ic_cdk::id(),
+            created_at: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use candid::Principal;
+
+    use super::*;
+
+    #[test]
+    fn bundled_version_clears_the_default() {
+        WASM_VERSIONS.with(|w| {
+            w.borrow_mut().insert(
+                "v2".to_string(),
+                WasmData {
+                    version: "v2".to_string(),
+                    hash: String::new(),
+                    release_notes: String::new(),
+                    size: 0,
+                    uploaded_by: Principal::anonymous(),
+                    created_at: 0,
+                }
+                .into(),
+            )
+        });
+
+        Wasm::set_default_wasm_version("v2".to_string()).unwrap();
+        assert_eq!(Wasm::get_default_wasm_version(), Some("v2".to_string()));
+        assert_eq!(Wasm::resolve_version(None), Ok("v2".to_string()));
+
+        Wasm::set_default_wasm_version(BUNDLED_WASM_VERSION.to_string()).unwrap();
+        assert_eq!(Wasm::get_default_wasm_version(), None);
+        assert_eq!(
+            Wasm::resolve_version(None),
+            Ok(BUNDLED_WASM_VERSION.to_string())
+        );
+
+        assert_eq!(
+            Wasm::set_default_wasm_version("v3".to_string()),
+            Err(MultisigIndexError::WasmVersionNotFound("v3".to_string()))
+        );
+    }
+}
@@ -1,20 +1,70 @@
 use candid::Principal;
-use ic_cdk::{caller, id, query, update};
+use ic_cdk::{caller, id, init, post_upgrade, query, update};
 
 use crate::{
-    logic::store::Store,
+    guards::is_admin,
+    logic::{
+        cmc::CMC, config::Config, controllers::Controllers, cycles::Cycles, deposit::Deposit,
+        events::Events, fees::Fees, http::Http, journal::Journal, monitor::Monitor,
+        pricing::Pricing, reconciliation::Reconciliation, refunds::Refunds, schema::Schema,
+        store::Store, transfers::Transfers, upgrade::Upgrade, wasm::Wasm,
+    },
     rust_declarations::types::{
-        InitializeStatus, MultisigData, TransactionData, TransactionStatus,
+        BalanceMismatch, CanisterCreation, CanisterStatusEntry, ControllerUpdate, CyclesReport,
+        DepositAccount, EventFilter, EventsPage, FeePayout, FeePayoutsPage, GetFeePayoutsArgs,
+        GetMultisigsArgs, GetTransactionsArgs, HttpRequest, HttpResponse, IndexConfig, InitArgs,
+        InitializeStatus, JournalEntry, MultisigData, MultisigIndexError, MultisigUpgradeData,
+        MultisigsPage, OutgoingTransfer, PaymentPull, PrepaidBalance, RefundClaim, RefundMethod,
+        RefundResolution, SpawnData, SpawnPrice, StorageReport, TransactionData, TransactionsPage,
+        TransferResolution, WasmData,
     },
 };
 
+#[init]
+fn init(args: Option<InitArgs>) {
+    if let Some(args) = args {
+        Config::apply_init_args(args);
+    }
+    Cycles::open();
+    // Nothing to migrate on a new canister, this only marks the migrations as done
+    Schema::run_migrations();
+    Reconciliation::start_timer();
+    Pricing::start_timer();
+    Monitor::start_timer();
+    Fees::start_timer();
+}
+
+#[post_upgrade]
+fn post_upgrade(args: Option<InitArgs>) {
+    if let Some(args) = args {
+        Config::apply_init_args(args);
+    }
+    Journal::open_balances();
+    Cycles::open();
+    Schema::run_migrations();
+    Reconciliation::start_timer();
+    Pricing::start_timer();
+    Monitor::start_timer();
+    Fees::start_timer();
+}
+
 #[query]
 fn get_cycles() -> u64 {
     Store::get_cycles()
 }
 
+#[query]
+fn get_cycles_report() -> Result<CyclesReport, MultisigIndexError> {
+    Cycles::get_report()
+}
+
+#[query]
+fn http_request(request: HttpRequest) -> HttpResponse {
+    Http::handle(request)
+}
+
 #[update]
-async fn get_cmc_icp_balance() -> Result<u64, String> {
+async fn get_cmc_icp_balance() -> Result<u64, MultisigIndexError> {
     Store::get_icp_balance(id()).await
 }
 
@@ -29,8 +79,23 @@ async fn get_principal_local_balance(principal: Principal) -> u64 {
 }
 
 #[query]
-fn get_transactions(status: Option<TransactionStatus>) -> Vec<TransactionData> {
-    Store::get_transactions(status)
+fn get_balance_history(principal: Principal) -> Vec<JournalEntry> {
+    Journal::get_balance_history(principal)
+}
+
+#[query(guard = "is_admin")]
+fn get_balance_mismatches() -> Vec<BalanceMismatch> {
+    Journal::get_balance_mismatches()
+}
+
+#[query(guard = "is_admin")]
+fn get_storage_report() -> StorageReport {
+    Schema::get_storage_report()
+}
+
+#[query]
+fn get_transactions(args: GetTransactionsArgs) -> TransactionsPage {
+    Store::get_transactions_page(args)
 }
 
 #[query]
@@ -39,16 +104,149 @@ fn get_multisig_by_group_identifier(identifier: Principal) -> Option<MultisigDat
 }
 
 #[query]
-fn get_multisigs() -> Vec<MultisigData> {
-    Store::get_multisigs()
+fn get_multisigs_by_creator(created_by: Principal) -> Vec<MultisigData> {
+    Store::get_multisigs_by_creator(created_by)
+}
+
+#[query]
+fn get_transactions_by_initializer(initialized_by: Principal) -> Vec<TransactionData> {
+    Store::get_transactions_by_initializer(initialized_by)
+}
+
+#[query]
+fn get_multisigs(args: GetMultisigsArgs) -> MultisigsPage {
+    Store::get_multisigs(args)
+}
+
+#[query]
+fn get_spawn_price() -> Result<SpawnPrice, MultisigIndexError> {
+    Pricing::get_spawn_price()
 }
 
 #[update]
 async fn spawn_multisig(
     blockheight: u64,
     group_identifier: Principal, // can be an identifier
-) -> Result<Principal, String> {
-    Store::spawn_multisig(caller(), blockheight, group_identifier).await
+    wasm_version: Option<String>,
+) -> Result<Principal, MultisigIndexError> {
+    Store::spawn_multisig(
+        caller(),
+        blockheight,
+        group_identifier,
+        wasm_version,
+        CanisterCreation::TopUp,
+        None,
+    )
+    .await
+}
+
+// The CMC creates the multisig canister from the transferred ICP, on a subnet of the given type when passed
+#[update]
+async fn spawn_multisig_via_cmc(
+    blockheight: u64,
+    group_identifier: Principal,
+    wasm_version: Option<String>,
+    subnet_type: Option<String>,
+) -> Result<Principal, MultisigIndexError> {
+    Store::spawn_multisig(
+        caller(),
+        blockheight,
+        group_identifier,
+        wasm_version,
+        CanisterCreation::Cmc { subnet_type },
+        None,
+    )
+    .await
+}
+
+// The subnet types are read from the CMC, which lives on another subnet so this can not be a query call
+#[update]
+async fn get_subnet_types() -> Result<Vec<String>, MultisigIndexError> {
+    CMC::get_subnet_types().await
+}
+
+// The caller approves this canister on the ledger for the spin up price plus the ledger fee beforehand
+#[update]
+async fn spawn_multisig_icrc2(
+    group_identifier: Principal,
+    wasm_version: Option<String>,
+) -> Result<Principal, MultisigIndexError> {
+    Store::spawn_multisig_icrc2(caller(), group_identifier, wasm_version).await
+}
+
+// Called by the creator of the multisig or by the multisig itself
+#[update]
+async fn update_multisig_controllers(
+    canister_id: Principal,
+    update: ControllerUpdate,
+) -> Result<MultisigData, MultisigIndexError> {
+    Controllers::update_controllers(caller(), canister_id, update).await
+}
+
+#[update]
+async fn resume_spawn(group_identifier: Principal) -> Result<Principal, MultisigIndexError> {
+    Store::resume_spawn(caller(), group_identifier).await
+}
+
+#[query]
+fn get_refund_claims(claimant: Principal) -> Vec<RefundClaim> {
+    Refunds::get_claims_by_claimant(claimant)
+}
+
+#[update]
+async fn claim_refund(
+    icp_block_index: u64,
+    method: RefundMethod,
+) -> Result<RefundClaim, MultisigIndexError> {
+    Refunds::claim_refund(caller(), icp_block_index, method).await
+}
+
+#[query(guard = "is_admin")]
+fn get_refund_review_queue() -> Vec<RefundClaim> {
+    Refunds::get_review_queue()
+}
+
+#[update(guard = "is_admin")]
+fn resolve_refund_claim(
+    icp_block_index: u64,
+    resolution: RefundResolution,
+) -> Result<RefundClaim, MultisigIndexError> {
+    Refunds::resolve_claim(icp_block_index, resolution)
+}
+
+#[query(guard = "is_admin")]
+fn get_events(from: Option<u64>, limit: Option<u64>, filter: Option<EventFilter>) -> EventsPage {
+    Events::get_events(from, limit, filter)
+}
+
+#[query(guard = "is_admin")]
+fn get_pending_transfers() -> Vec<OutgoingTransfer> {
+    Transfers::get_pending_transfers("")
+}
+
+#[query(guard = "is_admin")]
+fn get_pending_pulls() -> Vec<PaymentPull> {
+    Transfers::get_pending_pulls()
+}
+
+#[update(guard = "is_admin")]
+fn resolve_transfer(id: String, resolution: TransferResolution) -> Result<(), MultisigIndexError> {
+    Transfers::resolve_transfer(id, resolution)
+}
+
+#[query(guard = "is_admin")]
+fn get_fee_payouts(args: GetFeePayoutsArgs) -> FeePayoutsPage {
+    Fees::get_payouts_page(args)
+}
+
+#[update(guard = "is_admin")]
+async fn pay_out_fees() -> Vec<FeePayout> {
+    Fees::pay_out_fees().await
+}
+
+#[query]
+fn get_spawn(group_identifier: Principal) -> Option<SpawnData> {
+    Store::get_spawn(group_identifier)
 }
 
 #[query]
@@ -56,11 +254,159 @@ fn get_initialization_status(group_identifier: Principal) -> Option<InitializeSt
     Store::get_initialization_status(group_identifier)
 }
 
+#[query]
+fn get_deposit_account() -> DepositAccount {
+    Deposit::get_deposit_account(caller())
+}
+
 #[update]
-async fn withdraw_balance() -> Result<(), String> {
+async fn notify_deposit() -> Result<TransactionData, MultisigIndexError> {
+    Deposit::notify_deposit(caller()).await
+}
+
+#[update]
+async fn withdraw_balance() -> Result<(), MultisigIndexError> {
     Store::withdraw_balance(caller()).await
 }
 
+#[update(guard = "is_admin")]
+async fn reconcile_transactions() -> Vec<TransactionData> {
+    Reconciliation::reconcile_transactions().await
+}
+
+#[update(guard = "is_admin")]
+async fn monitor_multisigs() -> Vec<(Principal, CanisterStatusEntry)> {
+    Monitor::monitor_multisigs().await
+}
+
+#[query]
+fn get_status_history(canister_id: Principal) -> Vec<CanisterStatusEntry> {
+    Monitor::get_status_history(canister_id)
+}
+
+#[query]
+fn get_prepaid_balance(canister_id: Principal) -> PrepaidBalance {
+    Monitor::get_prepaid_balance(canister_id)
+}
+
+// Anyone can fund a multisig from their local balance
+#[update]
+fn fund_multisig_icp(
+    canister_id: Principal,
+    e8s: u64,
+) -> Result<PrepaidBalance, MultisigIndexError> {
+    Monitor::fund_with_icp(caller(), canister_id, e8s)
+}
+
+// The cycles attached to the call are added to the prepaid balance
+#[update]
+fn fund_multisig_cycles(canister_id: Principal) -> Result<PrepaidBalance, MultisigIndexError> {
+    Monitor::fund_with_cycles(canister_id)
+}
+
+#[update(guard = "is_admin")]
+async fn upgrade_multisigs(
+    batch_size: Option<u64>,
+    retry_failed: Option<bool>,
+) -> Result<Vec<MultisigUpgradeData>, MultisigIndexError> {
+    Upgrade::upgrade_multisigs(batch_size, retry_failed).await
+}
+
+#[query]
+fn get_upgrade_statuses() -> Vec<MultisigUpgradeData> {
+    Upgrade::get_upgrade_statuses()
+}
+
+#[query]
+fn get_wasm_versions() -> Vec<WasmData> {
+    Wasm::get_wasm_versions()
+}
+
+#[query]
+fn get_default_wasm_version() -> Option<String> {
+    Wasm::get_default_wasm_version()
+}
+
+#[update(guard = "is_admin")]
+fn upload_wasm_chunk(version: String, chunk: Vec<u8>) -> Result<u64, MultisigIndexError> {
+    Wasm::upload_wasm_chunk(version, chunk)
+}
+
+#[update(guard = "is_admin")]
+fn clear_wasm_chunks(version: String) {
+    Wasm::clear_wasm_chunks(version)
+}
+
+#[update(guard = "is_admin")]
+fn finalize_wasm(
+    version: String,
+    expected_hash: String,
+    release_notes: String,
+) -> Result<WasmData, MultisigIndexError> {
+    Wasm::finalize_wasm(caller(), version, expected_hash, release_notes)
+}
+
+#[update(guard = "is_admin")]
+fn set_default_wasm_version(version: String) -> Result<(), MultisigIndexError> {
+    Wasm::set_default_wasm_version(version)
+}
+
+#[query]
+fn get_config() -> IndexConfig {
+    Config::get()
+}
+
+#[query]
+fn get_admins() -> Vec<Principal> {
+    Config::get_admins()
+}
+
+#[update(guard = "is_admin")]
+fn add_admin(principal: Principal) {
+    Config::add_admin(principal)
+}
+
+#[update(guard = "is_admin")]
+fn remove_admin(principal: Principal) -> Result<(), MultisigIndexError> {
+    Config::remove_admin(principal)
+}
+
+#[update(guard = "is_admin")]
+fn set_fee_recipient(fee_recipient: Principal) -> Result<IndexConfig, MultisigIndexError> {
+    Config::set_fee_recipient(fee_recipient)
+}
+
+#[update(guard = "is_admin")]
+fn set_catalyze_e8s_fee(e8s: u64) -> Result<IndexConfig, MultisigIndexError> {
+    Config::set_catalyze_e8s_fee(e8s)
+}
+
+#[update(guard = "is_admin")]
+fn set_min_e8s_for_spinup(e8s: u64) -> Result<IndexConfig, MultisigIndexError> {
+    Config::set_min_e8s_for_spinup(e8s)
+}
+
+#[update(guard = "is_admin")]
+fn set_spawn_pricing(
+    multisig_cycles: Option<u64>,
+    catalyze_fee_percentage: Option<u64>,
+) -> Result<IndexConfig, MultisigIndexError> {
+    Config::set_spawn_pricing(multisig_cycles, catalyze_fee_percentage)
+}
+
+#[update(guard = "is_admin")]
+fn set_batch_fee_payouts(batch_fee_payouts: bool) -> Result<IndexConfig, MultisigIndexError> {
+    Config::set_batch_fee_payouts(batch_fee_payouts)
+}
+
+#[update(guard = "is_admin")]
+fn set_top_up_config(
+    top_up_threshold_cycles: Option<u64>,
+    top_up_cycles: Option<u64>,
+) -> Result<IndexConfig, MultisigIndexError> {
+    Config::set_top_up_config(top_up_threshold_cycles, top_up_cycles)
+}
+
 // Method used to save the candid interface to a file
 #[test]
 pub fn candid() {
@@ -71,5 +417,5 @@ pub fn candid() {
     export_service!();
     let dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
     let dir = dir.parent().unwrap().join("candid");
-    write(dir.join(format!("multisig_index.did")), __export_service()).expect("Write failed.");
+    write(dir.join("multisig_index.did"), __export_service()).expect("Write failed.");
 }
@@ -25,20 +25,20 @@ pub struct CyclesCanisterInitPayload {
 
 #[derive(CandidType, Deserialize)]
 pub struct IcpXdrConversionRate {
-    xdr_permyriad_per_icp: u64,
-    timestamp_seconds: u64,
+    pub xdr_permyriad_per_icp: u64,
+    pub timestamp_seconds: u64,
 }
 
 #[derive(CandidType, Deserialize)]
 pub struct IcpXdrConversionRateResponse {
-    certificate: Vec<u8>,
-    data: IcpXdrConversionRate,
-    hash_tree: Vec<u8>,
+    pub certificate: Vec<u8>,
+    pub data: IcpXdrConversionRate,
+    pub hash_tree: Vec<u8>,
 }
 
 #[derive(CandidType, Deserialize)]
 pub struct SubnetTypesToSubnetsResponse {
-    data: Vec<(String, Vec<Principal>)>,
+    pub data: Vec<(String, Vec<Principal>)>,
 }
 
 pub type BlockIndex = u64;
@@ -49,7 +49,7 @@ pub struct NotifyCreateCanisterArg {
     pub subnet_type: Option<String>,
 }
 
-#[derive(CandidType, Deserialize, Debug)]
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
 pub enum NotifyError {
     Refunded {
         block_index: Option<BlockIndex>,
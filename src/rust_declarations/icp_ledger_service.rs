@@ -3,10 +3,10 @@
 use candid::{self, CandidType, Deserialize, Principal};
 use ic_cdk::api::call::CallResult as Result;
 
-#[derive(CandidType, Deserialize)]
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
 pub struct Account {
-    owner: Principal,
-    subaccount: Option<Vec<u8>>,
+    pub owner: Principal,
+    pub subaccount: Option<Vec<u8>>,
 }
 
 #[derive(CandidType, Deserialize)]
@@ -51,6 +51,7 @@ pub struct InitArgs {
     token_name: Option<String>,
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(CandidType, Deserialize)]
 pub enum LedgerCanisterPayload {
     Upgrade(Option<UpgradeArgs>),
@@ -302,6 +303,51 @@ pub enum Result1 {
     Err(TransferError1),
 }
 
+#[derive(CandidType, Deserialize)]
+pub struct TransferFromArgs {
+    pub to: Account,
+    pub fee: Option<candid::Nat>,
+    pub spender_subaccount: Option<Vec<u8>>,
+    pub from: Account,
+    pub memo: Option<Vec<u8>>,
+    pub created_at_time: Option<u64>,
+    pub amount: candid::Nat,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum TransferFromError {
+    GenericError {
+        message: String,
+        error_code: candid::Nat,
+    },
+    TemporarilyUnavailable,
+    InsufficientAllowance {
+        allowance: candid::Nat,
+    },
+    BadBurn {
+        min_burn_amount: candid::Nat,
+    },
+    Duplicate {
+        duplicate_of: candid::Nat,
+    },
+    BadFee {
+        expected_fee: candid::Nat,
+    },
+    CreatedInFuture {
+        ledger_time: u64,
+    },
+    TooOld,
+    InsufficientFunds {
+        balance: candid::Nat,
+    },
+}
+
+#[derive(CandidType, Deserialize)]
+pub enum Result3 {
+    Ok(candid::Nat),
+    Err(TransferFromError),
+}
+
 #[derive(CandidType, Deserialize)]
 pub struct TransferFeeArg0 {}
 
@@ -360,6 +406,9 @@ impl IcpLedgerService {
     pub async fn icrc1_transfer(&self, arg0: TransferArg) -> Result<(Result2,)> {
         ic_cdk::call(self.0, "icrc1_transfer", (arg0,)).await
     }
+    pub async fn icrc2_transfer_from(&self, arg0: TransferFromArgs) -> Result<(Result3,)> {
+        ic_cdk::call(self.0, "icrc2_transfer_from", (arg0,)).await
+    }
     pub async fn name(&self) -> Result<(Name,)> {
         ic_cdk::call(self.0, "name", ()).await
     }
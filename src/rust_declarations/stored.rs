@@ -0,0 +1,184 @@
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Encode};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::de::DeserializeOwned;
+
+// Every candid message starts with this magic, records stored before the schema versions have no header
+const CANDID_MAGIC: &[u8] = b"DIDL";
+// First byte of a versioned record, it can never be the first byte of the candid magic
+const VERSION_MARKER: u8 = 0xFF;
+// Version of the records that were stored without a header
+pub const LEGACY_VERSION: u8 = 0;
+
+// A type that is stored in stable memory as `[VERSION_MARKER, VERSION, candid..]`
+pub trait Versioned: CandidType + DeserializeOwned {
+    // Raise when a record of the previous version no longer decodes as the type (a field that changes its type,
+    // a new field that is not optional or a removed enum variant), and handle the older versions in
+    // `decode_version`. A new optional field or a new enum variant still decodes and keeps the version
+    const VERSION: u8;
+
+    // Decode the candid of a record that was stored with the given version
+    fn decode_version(_version: u8, bytes: &[u8]) -> Result<Self, String> {
+        Decode!(bytes, Self).map_err(|err| err.to_string())
+    }
+}
+
+pub fn encode<T: Versioned>(value: &T) -> Vec<u8> {
+    let mut bytes = vec![VERSION_MARKER, T::VERSION];
+    bytes.extend(Encode!(value).expect("Failed to encode a stored record"));
+    bytes
+}
+
+// Returns the stored version and the error when the record can not be decoded
+pub fn decode<T: Versioned>(bytes: &[u8]) -> Result<T, (Option<u8>, String)> {
+    match bytes {
+        [VERSION_MARKER, version, candid @ ..] => {
+            T::decode_version(*version, candid).map_err(|err| (Some(*version), err))
+        }
+        _ if bytes.starts_with(CANDID_MAGIC) => {
+            T::decode_version(LEGACY_VERSION, bytes).map_err(|err| (Some(LEGACY_VERSION), err))
+        }
+        _ => Err((None, "Unknown record format".to_string())),
+    }
+}
+
+// A record as it is read from a stable map. Records that can not be decoded are kept as they are instead of
+// trapping, so a single bad record does not block every call that reads the map and it can still be inspected
+#[derive(Clone, Debug)]
+pub enum Stored<T> {
+    Valid(T),
+    Corrupt {
+        version: Option<u8>,
+        error: String,
+        bytes: Vec<u8>,
+    },
+}
+
+impl<T> Stored<T> {
+    pub fn valid(self) -> Option<T> {
+        match self {
+            Stored::Valid(value) => Some(value),
+            Stored::Corrupt { .. } => None,
+        }
+    }
+
+    pub fn as_valid(&self) -> Option<&T> {
+        match self {
+            Stored::Valid(value) => Some(value),
+            Stored::Corrupt { .. } => None,
+        }
+    }
+}
+
+impl<T> From<T> for Stored<T> {
+    fn from(value: T) -> Self {
+        Stored::Valid(value)
+    }
+}
+
+impl<T: Versioned> Storable for Stored<T> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match self {
+            Stored::Valid(value) => Cow::Owned(encode(value)),
+            // Written back unchanged so nothing is lost until the record is repaired
+            Stored::Corrupt { bytes, .. } => Cow::Borrowed(bytes),
+        }
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        match decode(bytes.as_ref()) {
+            Ok(value) => Stored::Valid(value),
+            Err((version, error)) => Stored::Corrupt {
+                version,
+                error,
+                bytes: bytes.into_owned(),
+            },
+        }
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(CandidType, Deserialize, Debug, PartialEq)]
+    struct Record {
+        value: u64,
+    }
+
+    impl Versioned for Record {
+        const VERSION: u8 = 2;
+
+        fn decode_version(version: u8, bytes: &[u8]) -> Result<Self, String> {
+            match version {
+                // The first version stored the value as text
+                1 => {
+                    let value = Decode!(bytes, String).map_err(|err| err.to_string())?;
+                    value
+                        .parse()
+                        .map(|value| Record { value })
+                        .map_err(|err| err.to_string())
+                }
+                _ => Decode!(bytes, Self).map_err(|err| err.to_string()),
+            }
+        }
+    }
+
+    #[test]
+    fn encode_writes_the_header() {
+        let bytes = encode(&Record { value: 7 });
+        assert_eq!(&bytes[..2], &[VERSION_MARKER, 2]);
+        assert!(bytes[2..].starts_with(CANDID_MAGIC));
+        assert_eq!(decode::<Record>(&bytes), Ok(Record { value: 7 }));
+    }
+
+    #[test]
+    fn legacy_records_decode_without_header() {
+        let bytes = Encode!(&Record { value: 7 }).unwrap();
+        assert_eq!(decode::<Record>(&bytes), Ok(Record { value: 7 }));
+    }
+
+    #[test]
+    fn older_versions_use_decode_version() {
+        let mut bytes = vec![VERSION_MARKER, 1];
+        bytes.extend(Encode!(&"7".to_string()).unwrap());
+        assert_eq!(decode::<Record>(&bytes), Ok(Record { value: 7 }));
+    }
+
+    #[test]
+    fn undecodable_records_return_the_version() {
+        let mut bytes = vec![VERSION_MARKER, 2];
+        bytes.extend(Encode!(&"not a record".to_string()).unwrap());
+        assert!(matches!(decode::<Record>(&bytes), Err((Some(2), _))));
+
+        let legacy = Encode!(&"not a record".to_string()).unwrap();
+        assert!(matches!(
+            decode::<Record>(&legacy),
+            Err((Some(LEGACY_VERSION), _))
+        ));
+
+        assert!(matches!(decode::<Record>(&[1, 2, 3]), Err((None, _))));
+    }
+
+    #[test]
+    fn corrupt_records_keep_their_bytes() {
+        let bytes = vec![1, 2, 3];
+        let stored = Stored::<Record>::from_bytes(Cow::Borrowed(&bytes));
+
+        assert!(stored.as_valid().is_none());
+        assert_eq!(stored.to_bytes().as_ref(), bytes.as_slice());
+        assert!(stored.valid().is_none());
+    }
+
+    #[test]
+    fn valid_records_round_trip() {
+        let stored = Stored::from(Record { value: 7 });
+        let read = Stored::<Record>::from_bytes(stored.to_bytes());
+        assert_eq!(read.valid(), Some(Record { value: 7 }));
+    }
+}
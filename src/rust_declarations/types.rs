@@ -1,11 +1,17 @@
 use candid::{CandidType, Nat, Principal};
-use ic_ledger_types::Tokens;
+use ic_cdk::api::{call::RejectionCode, management_canister::main::CanisterStatusType};
+use ic_ledger_types::{Subaccount, Tokens, TransferArgs, TransferError};
 use serde::Deserialize;
 use std::borrow::Cow;
 
-use candid::{Decode, Encode};
 use ic_stable_structures::{storable::Bound, Storable};
 
+use super::{
+    cmc_service::NotifyError,
+    icp_ledger_service::TransferFromError,
+    stored::{decode, encode, Versioned},
+};
+
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct MultisigData {
     pub canister_id: Principal,
@@ -13,18 +19,14 @@ pub struct MultisigData {
     pub created_by: Principal,
     pub created_at: u64,
     pub updated_at: u64,
+    pub wasm_version: Option<String>,
+    pub wasm_hash: Option<String>,
+    // Not set for multisigs registered before the controllers were tracked, those are only controlled by this canister
+    pub controllers: Option<Vec<Principal>>,
 }
 
-impl Storable for MultisigData {
-    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
-        Cow::Owned(Encode!(self).unwrap())
-    }
-
-    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        Decode!(bytes.as_ref(), Self).unwrap()
-    }
-
-    const BOUND: Bound = Bound::Unbounded;
+impl Versioned for MultisigData {
+    const VERSION: u8 = 1;
 }
 #[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub enum InitializeStatus {
@@ -33,16 +35,8 @@ pub enum InitializeStatus {
     Error,
 }
 
-impl Storable for InitializeStatus {
-    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
-        Cow::Owned(Encode!(self).unwrap())
-    }
-
-    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        Decode!(bytes.as_ref(), Self).unwrap()
-    }
-
-    const BOUND: Bound = Bound::Unbounded;
+impl Versioned for InitializeStatus {
+    const VERSION: u8 = 1;
 }
 
 #[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
@@ -53,6 +47,9 @@ pub enum TransactionStatus {
     InsufficientIcp,
     Success,
     Pending,
+    RecoveryFailed,
+    // The block is claimed by a call that has not finished validating or transferring it yet
+    Reserved,
 }
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
@@ -65,20 +62,626 @@ pub struct TransactionData {
     pub created_at: u64,
     pub status: TransactionStatus,
     pub error_message: Option<String>,
+    pub attempts: Option<u32>,
+    pub last_attempt_at: Option<u64>,
+    // The Catalyze fee charged for the spin up paid with this transaction
+    pub catalyze_fee: Option<Tokens>,
+    // Not set for transactions stored before the CMC could create the canister
+    pub creation: Option<CanisterCreation>,
+}
+
+impl Versioned for TransactionData {
+    const VERSION: u8 = 1;
+}
+
+// Account of this canister where a principal can deposit ICP from any source
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct DepositAccount {
+    pub owner: Principal,
+    pub subaccount: Subaccount,
+    pub account_identifier: String,
+}
+
+// How the canister of a spawn is created
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum CanisterCreation {
+    // This canister tops itself up through the CMC and creates the canister from its own cycles
+    TopUp,
+    // The CMC creates the canister from the ICP of the user, on a subnet of the given type when set
+    Cmc { subnet_type: Option<String> },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum SpawnStep {
+    ToppedUp,
+    CanisterCreated,
+    CodeInstalled,
+    Registered,
+    FeeTransferred,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SpawnData {
+    pub group_identifier: Principal,
+    pub created_by: Principal,
+    pub icp_block_index: u64,
+    pub cycles: Option<Nat>,
+    pub canister_id: Option<Principal>,
+    pub completed_steps: Vec<SpawnStep>,
+    pub error_message: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub wasm_version: Option<String>,
+    // Not set for spawns created before the CMC could create the canister
+    pub creation: Option<CanisterCreation>,
+    // The price a pulled payment was quoted for, the spawn is charged this price instead of the current one
+    pub price: Option<SpawnPrice>,
+}
+
+impl Versioned for SpawnData {
+    const VERSION: u8 = 1;
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum RefundStatus {
+    // The claimant can retry the spawn with it or request a payout
+    Open,
+    // An ICP payout was requested that an admin has to review
+    PendingReview,
+    // A retry of the spawn succeeded with the payment
+    Credited,
+    PaidOut,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum RefundMethod {
+    // Deposit the cycles that were minted for the spawn to the given canister
+    Cycles(Principal),
+    // Credit ICP to the local balance of the claimant, after a review by an admin
+    Icp,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum RefundResolution {
+    // Credit the e8s to the local balance of the claimant
+    Approve(u64),
+    // Open the claim again with the reason as review note
+    Reject(String),
+}
+
+// The payment of a spawn that failed after the ICP was converted to cycles
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct RefundClaim {
+    pub icp_block_index: u64,
+    pub group_identifier: Principal,
+    pub claimant: Principal,
+    // The whole deposit of the block, what the spawn did not spend stayed on the local balance
+    pub icp_amount: Option<Tokens>,
+    // The ICP the spawn sent to the CMC, an ICP payout can not be more than this
+    pub cmc_amount: Option<Tokens>,
+    // Kept by this canister until the spawn is paid out, it is refunded with every payout
+    pub catalyze_fee: Option<Tokens>,
+    // Cycles this canister still holds for the spawn, not set once a canister was created with them
+    pub cycles: Option<Nat>,
+    pub canister_id: Option<Principal>,
+    pub status: RefundStatus,
+    pub method: Option<RefundMethod>,
+    pub error_message: Option<String>,
+    pub review_note: Option<String>,
+    pub paid_e8s: Option<Tokens>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+impl Versioned for RefundClaim {
+    const VERSION: u8 = 1;
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum JournalAccount {
+    // The local balance of a principal on this canister
+    Principal(Principal),
+    // ICP outside of this canister, deposits come from and withdrawals go to the ledger
+    Ledger,
+    // ICP sent to the CMC to mint cycles for a spawn
+    Cmc,
+    // ICP owed to the Catalyze fee recipient
+    FeeRecipient,
+    // The prepaid balance used to top up a multisig
+    Multisig(Principal),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum JournalEntryKind {
+    Deposit,
+    SpawnSpend,
+    ProtocolFee,
+    Refund,
+    Withdrawal,
+    // Balance that existed before the journal was introduced
+    OpeningBalance,
+    MultisigFunding,
+    MultisigTopUp,
+    // The Catalyze fee is transferred to the fee recipient
+    FeePayout,
+}
+
+// Every entry moves the amount from one account to the other, entries are never changed or removed
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct JournalEntry {
+    pub id: u64,
+    pub kind: JournalEntryKind,
+    pub from: JournalAccount,
+    pub to: JournalAccount,
+    pub amount: Tokens,
+    // The ICP transfer block index of the user this movement belongs to
+    pub block_index: Option<u64>,
+    pub created_at: u64,
+}
+
+impl Versioned for JournalEntry {
+    const VERSION: u8 = 1;
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum OutgoingTransferStatus {
+    // Sent without a known outcome, it is sent again with the same arguments
+    Pending,
+    Completed(u64),
+    // Rejected by the ledger, nothing was transferred
+    Failed(String),
+    // A retry that the ledger can no longer compare with the earlier attempts, it is unknown whether one of them
+    // went through. It is not sent again and nothing is given back until an admin settles it, see
+    // `resolve_transfer`
+    NeedsReview(String),
+}
+
+// A ledger transfer from this canister. The arguments, including the memo and `created_at_time`, are stored
+// before the first attempt so a retry is recognized by the ledger as the same transfer
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct OutgoingTransfer {
+    pub id: String,
+    pub args: TransferArgs,
+    pub status: OutgoingTransferStatus,
+    pub attempts: u32,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+impl Versioned for OutgoingTransfer {
+    const VERSION: u8 = 1;
+}
+
+// An ICRC-2 pull of the spin up price from the account of the principal. Like an `OutgoingTransfer` the memo
+// and `created_at_time` are fixed before the first attempt, so a retry is recognized by the ledger
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PaymentPull {
+    pub id: String,
+    pub principal: Principal,
+    pub price: SpawnPrice,
+    pub created_at_time: u64,
+    pub status: OutgoingTransferStatus,
+    pub attempts: u32,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+impl Versioned for PaymentPull {
+    const VERSION: u8 = 1;
+}
+
+// The outcome of a transfer or pull that needs a review, as found on the ledger
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum TransferResolution {
+    // The transfer went through in the block
+    Completed(u64),
+    // None of the attempts went through
+    Failed,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum FeePayoutStatus {
+    // Owed to the fee recipient, it is sent by the next payout run
+    Pending,
+    // Paid with the transfer in this block, the payouts of a batched sweep share the block
+    Paid(u64),
+}
+
+// The Catalyze fee of a completed spawn, keyed by the ICP transfer block index of the spawn
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct FeePayout {
+    pub icp_block_index: u64,
+    pub amount: Tokens,
+    pub status: FeePayoutStatus,
+    // The transfer that pays the fee, set when the transfer is created
+    pub transfer_id: Option<String>,
+    pub recipient: Option<Principal>,
+    pub attempts: u32,
+    pub error_message: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+impl Versioned for FeePayout {
+    const VERSION: u8 = 1;
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct GetFeePayoutsArgs {
+    // The block index to start the page from, use `next` of the previous page
+    pub start: Option<u64>,
+    pub limit: Option<u64>,
+    pub direction: Option<SortDirection>,
+    pub pending_only: Option<bool>,
+}
+
+// The totals cover every payout, not only the ones in the page
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct FeePayoutsPage {
+    pub payouts: Vec<FeePayout>,
+    pub next: Option<u64>,
+    pub total: u64,
+    pub owed: Tokens,
+    pub paid: Tokens,
+}
+
+// Running totals of the cycles that moved in and out of this canister since the accounting was opened
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct CycleTotals {
+    // The balance when the accounting was opened, zero until then
+    pub opening_balance: Nat,
+    pub opened_at: u64,
+    // Minted by the CMC for spawns that create the canister from this canister
+    pub minted: Nat,
+    // Attached to calls by multisigs that prepay their top ups
+    pub received: Nat,
+    // Attached to the canisters created by spawns
+    pub attached: Nat,
+    // Deposited to canisters for top ups and refunds
+    pub deposited: Nat,
+    pub created_canisters: u64,
+    pub updated_at: u64,
+}
+
+impl Versioned for CycleTotals {
+    const VERSION: u8 = 1;
+}
+
+// `overhead` is what was burned on calls, install code, storage and compute, it is not recorded directly but
+// derived from the totals and the current balance
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CyclesReport {
+    pub balance: Nat,
+    pub opening_balance: Nat,
+    pub opened_at: u64,
+    pub minted: Nat,
+    pub received: Nat,
+    pub attached: Nat,
+    pub deposited: Nat,
+    pub overhead: Nat,
+    pub created_canisters: u64,
+    // Set when more cycles left this canister than came in since the accounting was opened
+    pub spending_exceeds_recovery: bool,
 }
 
-impl Storable for TransactionData {
-    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
-        Cow::Owned(Encode!(self).unwrap())
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct BalanceMismatch {
+    pub principal: Principal,
+    pub balance: u64,
+    pub journal_balance: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum UpgradeStatus {
+    Upgrading,
+    Done,
+    Failed,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct MultisigUpgradeData {
+    pub canister_id: Principal,
+    pub wasm_version: String,
+    pub wasm_hash: String,
+    pub status: UpgradeStatus,
+    pub error_message: Option<String>,
+    pub updated_at: u64,
+    // Not set for upgrades stored before it was recorded, `updated_at` is the start of those
+    pub started_at: Option<u64>,
+}
+
+impl Versioned for MultisigUpgradeData {
+    const VERSION: u8 = 1;
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct WasmData {
+    pub version: String,
+    pub hash: String,
+    pub release_notes: String,
+    pub size: u64,
+    pub uploaded_by: Principal,
+    pub created_at: u64,
+}
+
+impl Versioned for WasmData {
+    const VERSION: u8 = 1;
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct IndexConfig {
+    pub fee_recipient: Principal,
+    pub catalyze_e8s_fee: Tokens,
+    pub min_e8s_for_spinup: Tokens,
+    // When not set the mainnet canisters are used
+    pub ledger_canister_id: Option<Principal>,
+    pub cmc_canister_id: Option<Principal>,
+    // When set the spin up price follows the ICP/XDR conversion rate of the CMC instead of the fixed e8s values
+    pub multisig_cycles: Option<u64>,
+    pub catalyze_fee_percentage: Option<u64>,
+    // Multisigs below the threshold are topped up with the amount of cycles, the defaults are used when not set
+    pub top_up_threshold_cycles: Option<u64>,
+    pub top_up_cycles: Option<u64>,
+    // When set the fees are paid out in one periodic transfer instead of one transfer per spawn
+    pub batch_fee_payouts: Option<bool>,
+}
+
+impl Storable for IndexConfig {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(encode(self))
     }
 
+    // Every call depends on the config, so a config that can not be decoded traps. In `post_upgrade` this
+    // rolls the upgrade back
     fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        Decode!(bytes.as_ref(), Self).unwrap()
+        decode(bytes.as_ref()).unwrap_or_else(|(_, err)| {
+            ic_cdk::trap(&format!("Failed to decode the config: {}", err))
+        })
     }
 
     const BOUND: Bound = Bound::Unbounded;
 }
 
+impl Versioned for IndexConfig {
+    const VERSION: u8 = 1;
+}
+
+// Used for both the init and the upgrade arguments, values that are not set are left unchanged
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct InitArgs {
+    pub admins: Option<Vec<Principal>>,
+    pub fee_recipient: Option<Principal>,
+    pub catalyze_e8s_fee: Option<u64>,
+    pub min_e8s_for_spinup: Option<u64>,
+    pub ledger_canister_id: Option<Principal>,
+    pub cmc_canister_id: Option<Principal>,
+    pub multisig_cycles: Option<u64>,
+    pub catalyze_fee_percentage: Option<u64>,
+    pub top_up_threshold_cycles: Option<u64>,
+    pub top_up_cycles: Option<u64>,
+    pub batch_fee_payouts: Option<bool>,
+}
+
+// Quote for a spin up, `total_e8s` is what the user pays including the Catalyze fee
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SpawnPrice {
+    pub total_e8s: Tokens,
+    pub catalyze_fee: Tokens,
+    pub multisig_cycles: Option<u64>,
+    pub xdr_permyriad_per_icp: Option<u64>,
+    // Not set for a fixed price
+    pub expires_at: Option<u64>,
+}
+
+// Funds used to top up a multisig when its cycles drop below the threshold, prepaid cycles are used first
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PrepaidBalance {
+    pub canister_id: Principal,
+    pub icp: Tokens,
+    pub cycles: Nat,
+    // ICP that was sent to the CMC for a top up that is not notified yet
+    pub pending_top_up: Option<PendingTopUp>,
+    // Outgoing transfer to the CMC for a top up whose outcome is not known yet
+    pub pending_transfer: Option<String>,
+    pub updated_at: u64,
+}
+
+impl Versioned for PrepaidBalance {
+    const VERSION: u8 = 1;
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PendingTopUp {
+    pub cmc_block_index: u64,
+    pub amount: Tokens,
+}
+
+// A single check of a multisig by the monitor
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CanisterStatusEntry {
+    // Not set when the status could not be fetched
+    pub status: Option<CanisterStatusType>,
+    pub cycles: Option<Nat>,
+    pub memory_size: Option<Nat>,
+    pub topped_up_cycles: Option<Nat>,
+    pub error_message: Option<String>,
+    pub checked_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CanisterStatusHistory {
+    pub canister_id: Principal,
+    pub entries: Vec<CanisterStatusEntry>,
+}
+
+impl Versioned for CanisterStatusHistory {
+    const VERSION: u8 = 1;
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+// Filters that are not set match every multisig, the time range includes `created_after` and excludes `created_before`
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct GetMultisigsArgs {
+    // The canister id to start the page from, use `next` of the previous page
+    pub start: Option<Principal>,
+    pub limit: Option<u64>,
+    pub direction: Option<SortDirection>,
+    pub created_by: Option<Principal>,
+    pub created_after: Option<u64>,
+    pub created_before: Option<u64>,
+    pub has_group_identifier: Option<bool>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct MultisigsPage {
+    pub multisigs: Vec<MultisigData>,
+    // Not set on the last page
+    pub next: Option<Principal>,
+    // The number of multisigs that match the filters over all pages
+    pub total: u64,
+}
+
+// Filters that are not set match every transaction, the block range includes both ends
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct GetTransactionsArgs {
+    // The block index to start the page from, use `next` of the previous page
+    pub start: Option<u64>,
+    pub limit: Option<u64>,
+    pub direction: Option<SortDirection>,
+    pub initialized_by: Option<Principal>,
+    pub statuses: Option<Vec<TransactionStatus>>,
+    pub created_after: Option<u64>,
+    pub created_before: Option<u64>,
+    pub from_block: Option<u64>,
+    pub to_block: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct TransactionsPage {
+    pub transactions: Vec<TransactionData>,
+    pub next: Option<u64>,
+    pub total: u64,
+}
+
+// A stored record that can not be decoded, it is skipped by every call until it is repaired
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CorruptEntry {
+    pub map: String,
+    pub key: String,
+    // Not set when the record has no known format
+    pub version: Option<u8>,
+    pub error: String,
+    pub size: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct StorageReport {
+    // The last migration that ran
+    pub schema_version: u64,
+    pub corrupt_entries: Vec<CorruptEntry>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    SpawnStarted,
+    SpawnResumed,
+    SpawnCompleted,
+    SpawnFailed,
+    // The ICP of a block was validated and added to the local balance of the principal
+    PaymentAccepted,
+    PaymentPulled,
+    CyclesMinted,
+    CanisterCreated,
+    CodeInstalled,
+    MultisigRegistered,
+    Deposit,
+    Withdrawal,
+    WithdrawalFailed,
+    FeePaid,
+    RefundClaimOpened,
+    RefundPaidOut,
+    // Calls to the ledger and the CMC, the block index is the block of the transfer
+    LedgerTransfer,
+    LedgerTransferFailed,
+    CmcNotified,
+    CmcNotifyFailed,
+}
+
+// An entry of the event log, only the fields that apply to the kind are set
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Event {
+    pub id: u64,
+    pub kind: EventKind,
+    // The user the event belongs to
+    pub principal: Option<Principal>,
+    pub group_identifier: Option<Principal>,
+    pub canister_id: Option<Principal>,
+    // The ICP transfer block index of the spawn the event belongs to
+    pub icp_block_index: Option<u64>,
+    pub block_index: Option<u64>,
+    pub amount: Option<Tokens>,
+    pub cycles: Option<Nat>,
+    pub error_message: Option<String>,
+    pub created_at: u64,
+}
+
+impl Versioned for Event {
+    const VERSION: u8 = 1;
+}
+
+// Filters that are not set match every event, the time range includes `created_after` and excludes `created_before`
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct EventFilter {
+    pub kinds: Option<Vec<EventKind>>,
+    pub principal: Option<Principal>,
+    pub group_identifier: Option<Principal>,
+    pub canister_id: Option<Principal>,
+    pub icp_block_index: Option<u64>,
+    pub created_after: Option<u64>,
+    pub created_before: Option<u64>,
+}
+
+// `total` counts every event in the log. `next` is the id to continue from, it is also set when the scan limit
+// was reached before the page was full
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct EventsPage {
+    pub events: Vec<Event>,
+    pub next: Option<u64>,
+    pub total: u64,
+}
+
+// Request and response of the HTTP gateway interface
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub certificate_version: Option<u16>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct HttpResponse {
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+// A change to the controllers of a multisig, requested by its creator or by the multisig itself
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum ControllerUpdate {
+    Add(Principal),
+    // This canister can not be removed, use `HandOver` to give up control
+    Remove(Principal),
+    // Replace all controllers including this canister, the multisig can no longer be upgraded by the index
+    HandOver(Vec<Principal>),
+}
+
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub enum UpdateCycleBalanceArgs {
     Add(Nat),
@@ -90,3 +693,167 @@ pub enum UpdateIcpBalanceArgs {
     Add(Tokens),
     Subtract(Tokens),
 }
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum MultisigIndexError {
+    // The ledger, the CMC or the management canister rejected the call
+    CallRejected {
+        code: RejectionCode,
+        message: String,
+    },
+    // The ledger accepted the call but refused the transfer
+    LedgerTransfer(TransferError),
+    // The CMC accepted the call but refused to mint the cycles
+    CmcNotify(NotifyError),
+    // The block passed by the caller is not a valid payment to this canister
+    InvalidTransaction(TransactionValidationError),
+    // The transferred amount plus the local balance is lower as the spin up price
+    InsufficientIcp {
+        required: Tokens,
+        available: Tokens,
+    },
+    // The block passed by the caller was already used
+    BlockAlreadyProcessed(u64),
+    // A multisig for the group identifier is already being initialized
+    InitializationInProgress(Principal),
+    // The caller has no local balance to withdraw
+    NoBalance,
+    // No spawn was started for the group identifier
+    SpawnNotFound(Principal),
+    // A previous spawn for the group identifier has unfinished steps and should be resumed
+    SpawnIncomplete(Principal),
+    // The caller is not allowed to perform this action
+    Unauthorized,
+    // The wasm version is not in the registry
+    WasmVersionNotFound(String),
+    // The wasm version is already in the registry
+    WasmVersionExists(String),
+    // The uploaded wasm does not match the expected hash
+    WasmHashMismatch {
+        expected: String,
+        actual: String,
+    },
+    // The config value is not accepted
+    InvalidConfig(String),
+    // Removing this admin would leave the canister without admins
+    LastAdmin,
+    // Another call is changing the local balance of this principal
+    PrincipalLocked(Principal),
+    // Another call is processing this block
+    BlockLocked(u64),
+    // The ledger refused to pull the spin up price from the approved account
+    LedgerTransferFrom(TransferFromError),
+    // No recent ICP/XDR conversion rate is known to calculate the spin up price
+    PriceUnavailable,
+    // No multisig is registered with this canister id
+    MultisigNotFound(Principal),
+    // The multisig was handed over, so this canister can no longer change it
+    NotController(Principal),
+    // The requested controllers can not be set on the multisig
+    InvalidControllers(String),
+    // A stored record that is needed for the call can not be decoded, see `get_storage_report`
+    CorruptRecord(String),
+    // No refund claim exists for this block index
+    RefundClaimNotFound(u64),
+    // The claim can not be handled this way in its current state
+    RefundUnavailable(String),
+    // The outcome of an earlier attempt of the transfer is unknown, see `get_pending_transfers`
+    TransferNeedsReview(String),
+    // No transfer or pull with this id waits for a review
+    TransferNotInReview(String),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum TransactionValidationError {
+    BlockNotFound(u64),
+    NoOperation,
+    NotATransfer,
+    InvalidSender,
+    InvalidReceiver,
+}
+
+impl From<(RejectionCode, String)> for MultisigIndexError {
+    fn from((code, message): (RejectionCode, String)) -> Self {
+        MultisigIndexError::CallRejected { code, message }
+    }
+}
+
+impl std::fmt::Display for MultisigIndexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MultisigIndexError::CallRejected { code, message } => {
+                write!(f, "Call rejected ({:?}): {}", code, message)
+            }
+            MultisigIndexError::LedgerTransfer(err) => write!(f, "{}", err),
+            MultisigIndexError::CmcNotify(err) => write!(f, "{:?}", err),
+            MultisigIndexError::InvalidTransaction(err) => write!(f, "{:?}", err),
+            MultisigIndexError::InsufficientIcp { .. } => {
+                write!(f, "Amount too low to spin up a canister")
+            }
+            MultisigIndexError::BlockAlreadyProcessed(_) => {
+                write!(f, "Transaction already processed")
+            }
+            MultisigIndexError::InitializationInProgress(_) => {
+                write!(f, "Multisig for this group is being initialized")
+            }
+            MultisigIndexError::NoBalance => write!(f, "No balance found"),
+            MultisigIndexError::SpawnNotFound(_) => write!(f, "No spawn found for this group"),
+            MultisigIndexError::SpawnIncomplete(_) => {
+                write!(
+                    f,
+                    "Previous spawn for this group is incomplete, resume it instead"
+                )
+            }
+            MultisigIndexError::Unauthorized => write!(f, "Unauthorized"),
+            MultisigIndexError::WasmVersionNotFound(version) => {
+                write!(f, "Wasm version {} not found", version)
+            }
+            MultisigIndexError::WasmVersionExists(version) => {
+                write!(f, "Wasm version {} already exists", version)
+            }
+            MultisigIndexError::WasmHashMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Wasm hash mismatch, expected {} got {}",
+                    expected, actual
+                )
+            }
+            MultisigIndexError::InvalidConfig(message) => write!(f, "Invalid config: {}", message),
+            MultisigIndexError::LastAdmin => write!(f, "Cannot remove the last admin"),
+            MultisigIndexError::PrincipalLocked(_) => {
+                write!(f, "Another call for this principal is in progress")
+            }
+            MultisigIndexError::BlockLocked(_) => {
+                write!(f, "Transaction is being processed by another call")
+            }
+            MultisigIndexError::LedgerTransferFrom(err) => write!(f, "{:?}", err),
+            MultisigIndexError::PriceUnavailable => {
+                write!(f, "Spin up price is unavailable, try again later")
+            }
+            MultisigIndexError::MultisigNotFound(_) => write!(f, "Multisig not found"),
+            MultisigIndexError::NotController(_) => {
+                write!(f, "The index is no longer a controller of this multisig")
+            }
+            MultisigIndexError::InvalidControllers(message) => {
+                write!(f, "Invalid controllers: {}", message)
+            }
+            MultisigIndexError::CorruptRecord(message) => {
+                write!(f, "Stored record can not be read: {}", message)
+            }
+            MultisigIndexError::RefundClaimNotFound(_) => write!(f, "Refund claim not found"),
+            MultisigIndexError::RefundUnavailable(message) => {
+                write!(f, "Refund unavailable: {}", message)
+            }
+            MultisigIndexError::TransferNeedsReview(id) => {
+                write!(
+                    f,
+                    "The outcome of transfer {} is unknown and needs a review",
+                    id
+                )
+            }
+            MultisigIndexError::TransferNotInReview(id) => {
+                write!(f, "Transfer {} does not need a review", id)
+            }
+        }
+    }
+}